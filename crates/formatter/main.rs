@@ -0,0 +1,71 @@
+use clap::{Parser, ValueEnum};
+use std::{fs, io, process::ExitCode};
+use mxmlextrema_as3parser::ns::*;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Arguments {
+    /// ActionScript 3 source files.
+    source_paths: Vec<String>,
+
+    /// Overwrites the source files instead of printing to the standard output.
+    #[arg(short, long)]
+    write: bool,
+
+    /// Only reports the source files that are not formatted.
+    #[arg(long)]
+    check: bool,
+
+    #[arg(long, default_value_t = 4)]
+    indent_width: usize,
+
+    #[arg(long, value_enum, default_value_t = BraceStyleArgument::SameLine)]
+    brace_style: BraceStyleArgument,
+
+    #[arg(long, default_value_t = 100)]
+    max_line_width: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum BraceStyleArgument {
+    SameLine,
+    NextLine,
+}
+
+fn main() -> io::Result<ExitCode> {
+    let arguments = Arguments::parse();
+    let options = PrinterOptions {
+        indent_width: arguments.indent_width,
+        brace_style: match arguments.brace_style {
+            BraceStyleArgument::SameLine => BraceStyle::SameLine,
+            BraceStyleArgument::NextLine => BraceStyle::NextLine,
+        },
+        max_line_width: arguments.max_line_width,
+    };
+    let mut success = true;
+    for source_path in &arguments.source_paths {
+        let source_content = fs::read_to_string(source_path)?;
+        let compilation_unit = CompilationUnit::new(Some(source_path.clone()), source_content.clone());
+        let Some(formatted) = FormatterFacade(&compilation_unit, options.clone()).format_program() else {
+            compilation_unit.sort_diagnostics();
+            for diagnostic in compilation_unit.nested_diagnostics() {
+                eprintln!("{}", diagnostic.format_english());
+            }
+            success = false;
+            continue;
+        };
+        if arguments.check {
+            if formatted != source_content {
+                println!("{source_path}");
+                success = false;
+            }
+        } else if arguments.write {
+            if formatted != source_content {
+                fs::write(source_path, formatted)?;
+            }
+        } else {
+            print!("{formatted}");
+        }
+    }
+    Ok(if success { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
@@ -0,0 +1,109 @@
+use lsp_types::{DiagnosticSeverity, Position, Range, Url};
+use mxmlextrema_as3parser::ns::*;
+
+/// An open text document along with its parsed tree.
+pub struct Document {
+    pub compilation_unit: Rc<CompilationUnit>,
+    pub tree: DocumentTree,
+}
+
+pub enum DocumentTree {
+    Program(Rc<Program>),
+    Mxml(Rc<Mxml>),
+    Css(Rc<CssDocument>),
+}
+
+impl Document {
+    /// Parses a document, choosing the grammar from the file extension
+    /// or else from the language identifier. Included sources are loaded
+    /// from `source_provider`.
+    pub fn new(uri: &Url, language_id: &str, text: String, source_provider: Rc<dyn SourceProvider>) -> Self {
+        let file_path = uri.to_file_path().ok().map(|path| path.to_string_lossy().into_owned());
+        let extension = uri.path().rsplit_once('.').map(|(_, extension)| extension.to_lowercase()).unwrap_or_default();
+        let language = if ["as", "mxml", "css"].contains(&extension.as_str()) { extension.as_str() } else { language_id };
+        let compilation_unit = CompilationUnit::new(file_path, text);
+        let options = ParserOptions { source_provider, ..default() };
+        let tree = match language {
+            "mxml" => DocumentTree::Mxml(ParserFacade(&compilation_unit, options).parse_mxml()),
+            "css" => DocumentTree::Css(CssParserFacade(&compilation_unit, options).parse_document()),
+            _ => DocumentTree::Program(ParserFacade(&compilation_unit, options).parse_program()),
+        };
+        Self { compilation_unit, tree }
+    }
+
+    /// Returns the diagnostics of the document and of its included sources,
+    /// grouped by the document they belong to. The diagnostics of this
+    /// document come first.
+    pub fn diagnostics(&self, uri: &Url) -> Vec<(Url, Vec<lsp_types::Diagnostic>)> {
+        let mut groups: Vec<(Url, Vec<lsp_types::Diagnostic>)> = vec![(uri.clone(), vec![])];
+        self.compilation_unit.sort_diagnostics();
+        for diagnostic in self.compilation_unit.nested_diagnostics() {
+            let location = diagnostic.location();
+            let compilation_unit = location.compilation_unit();
+            let group_uri = if Rc::ptr_eq(&compilation_unit, &self.compilation_unit) {
+                uri.clone()
+            } else if let Some(uri) = compilation_unit.file_path().and_then(|path| Url::from_file_path(path).ok()) {
+                uri
+            } else {
+                continue;
+            };
+            let diagnostic = lsp_types::Diagnostic {
+                range: range(&location),
+                severity: Some(if diagnostic.is_warning() { DiagnosticSeverity::WARNING } else { DiagnosticSeverity::ERROR }),
+                source: Some("as3parser".into()),
+                message: diagnostic.format_message_english(),
+                ..Default::default()
+            };
+            if let Some((_, list)) = groups.iter_mut().find(|(u, _)| *u == group_uri) {
+                list.push(diagnostic);
+            } else {
+                groups.push((group_uri, vec![diagnostic]));
+            }
+        }
+        groups
+    }
+
+    /// Whether a location belongs to this document rather than to an included source.
+    pub fn is_local(&self, location: &Location) -> bool {
+        Rc::ptr_eq(&location.compilation_unit(), &self.compilation_unit)
+    }
+
+    /// Converts a LSP position into a byte offset.
+    pub fn offset(&self, position: Position) -> usize {
+        let text = self.compilation_unit.text();
+        let Some(line_offset) = self.compilation_unit.get_line_offset(position.line as usize + 1) else {
+            return text.len();
+        };
+        let mut character = 0;
+        for (i, ch) in text[line_offset..].char_indices() {
+            if character >= position.character as usize || CharacterValidator::is_line_terminator(ch) {
+                return line_offset + i;
+            }
+            character += ch.len_utf16();
+        }
+        text.len()
+    }
+}
+
+/// Converts a byte offset into a LSP position, whose character is counted
+/// in UTF-16 code units.
+pub fn position(compilation_unit: &CompilationUnit, offset: usize) -> Position {
+    let line_offset = compilation_unit.get_line_offset_from_offset(offset);
+    Position {
+        line: (compilation_unit.get_line_number(offset) - 1) as u32,
+        character: compilation_unit.text()[line_offset..offset].encode_utf16().count() as u32,
+    }
+}
+
+pub fn range(location: &Location) -> Range {
+    let compilation_unit = location.compilation_unit();
+    Range {
+        start: position(&compilation_unit, location.first_offset()),
+        end: position(&compilation_unit, location.last_offset()),
+    }
+}
+
+/// Whether `offset` is within `location`, inclusive of both ends.
+pub fn contains(location: &Location, offset: usize) -> bool {
+    location.first_offset() <= offset && offset <= location.last_offset()
+}
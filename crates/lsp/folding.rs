@@ -0,0 +1,107 @@
+use crate::document::*;
+use lsp_types::{FoldingRange, FoldingRangeKind};
+use mxmlextrema_as3parser::ns::*;
+
+impl Document {
+    pub fn folding_ranges(&self) -> Vec<FoldingRange> {
+        let mut collector = FoldingRangeCollector { document: self, ranges: vec![] };
+        match &self.tree {
+            DocumentTree::Program(program) => {
+                for comment in self.compilation_unit.comments() {
+                    collector.add(&comment.location(), Some(FoldingRangeKind::Comment), false);
+                }
+                collector.visit_program(program);
+            },
+            DocumentTree::Mxml(mxml) => collector.add_mxml(&mxml.content),
+            DocumentTree::Css(css) => {
+                for directive in &css.directives {
+                    collector.add(&directive.location(), None, true);
+                    if let CssDirective::MediaQuery(media) = directive.as_ref() {
+                        for rule in &media.rules {
+                            collector.add(&rule.location, None, true);
+                        }
+                    }
+                }
+            },
+        }
+        collector.ranges.sort_by_key(|range| (range.start_line, range.end_line));
+        collector.ranges
+    }
+}
+
+struct FoldingRangeCollector<'a> {
+    document: &'a Document,
+    ranges: Vec<FoldingRange>,
+}
+
+impl FoldingRangeCollector<'_> {
+    /// Adds a folding range for a multi-line location. If `keep_last_line` is true,
+    /// the last line is left visible, such as for a closing brace.
+    fn add(&mut self, location: &Location, kind: Option<FoldingRangeKind>, keep_last_line: bool) {
+        if !self.document.is_local(location) {
+            return;
+        }
+        let range = range(location);
+        let end_line = if keep_last_line { range.end.line.saturating_sub(1) } else { range.end.line };
+        if end_line > range.start.line {
+            self.ranges.push(FoldingRange {
+                start_line: range.start.line,
+                end_line,
+                kind,
+                ..Default::default()
+            });
+        }
+    }
+
+    fn add_mxml(&mut self, content: &[Rc<MxmlContent>]) {
+        for node in content {
+            match node.as_ref() {
+                MxmlContent::Element(element) => {
+                    self.add(&element.location, None, true);
+                    if let Some(content) = &element.content {
+                        self.add_mxml(content);
+                    }
+                },
+                MxmlContent::Comment((_, location)) => self.add(location, Some(FoldingRangeKind::Comment), false),
+                _ => {},
+            }
+        }
+    }
+
+    /// Adds a range for every run of consecutive import directives.
+    fn add_imports(&mut self, directives: &[Rc<Directive>]) {
+        for run in directives.chunk_by(|a, b| matches!(a.as_ref(), Directive::ImportDirective(_)) && matches!(b.as_ref(), Directive::ImportDirective(_))) {
+            if let (Directive::ImportDirective(first), Directive::ImportDirective(last)) = (run[0].as_ref(), run[run.len() - 1].as_ref()) {
+                self.add(&first.location.combine_with(last.location.clone()), Some(FoldingRangeKind::Imports), false);
+            }
+        }
+    }
+}
+
+impl Visitor for FoldingRangeCollector<'_> {
+    fn visit_program(&mut self, node: &Rc<Program>) {
+        self.add_imports(&node.directives);
+        walk_program(self, node);
+    }
+
+    fn visit_block(&mut self, node: &Block) {
+        self.add(&node.location, None, true);
+        self.add_imports(&node.directives);
+        walk_block(self, node);
+    }
+
+    fn visit_switch_statement(&mut self, node: &SwitchStatement) {
+        self.add(&node.location, None, true);
+        walk_switch_statement(self, node);
+    }
+
+    fn visit_object_initializer(&mut self, node: &ObjectInitializer) {
+        self.add(&node.location, None, true);
+        walk_object_initializer(self, node);
+    }
+
+    fn visit_array_literal(&mut self, node: &ArrayLiteral) {
+        self.add(&node.location, None, true);
+        walk_array_literal(self, node);
+    }
+}
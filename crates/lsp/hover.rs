@@ -0,0 +1,85 @@
+use crate::document::*;
+use lsp_types::{Hover, HoverContents, MarkupContent, MarkupKind, Position};
+use mxmlextrema_as3parser::ns::*;
+
+impl Document {
+    /// Shows the ASDoc main body of the definition whose name is at `position`.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let DocumentTree::Program(program) = &self.tree else {
+            return None;
+        };
+        let mut finder = AsdocFinder { document: self, offset: self.offset(position), result: None };
+        finder.visit_program(program);
+        let (asdoc, location) = finder.result?;
+        let (main_body, _) = asdoc.main_body.as_ref()?;
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: main_body.clone(),
+            }),
+            range: Some(range(&location)),
+        })
+    }
+}
+
+struct AsdocFinder<'a> {
+    document: &'a Document,
+    offset: usize,
+    result: Option<(Rc<Asdoc>, Location)>,
+}
+
+impl AsdocFinder<'_> {
+    fn check(&mut self, asdoc: &Option<Rc<Asdoc>>, name: &Location) {
+        if let Some(asdoc) = asdoc {
+            if self.document.is_local(name) && contains(name, self.offset) {
+                self.result = Some((asdoc.clone(), name.clone()));
+            }
+        }
+    }
+}
+
+impl Visitor for AsdocFinder<'_> {
+    fn visit_package_definition(&mut self, node: &Rc<PackageDefinition>) {
+        if let Some((first, last)) = node.name.first().zip(node.name.last()) {
+            self.check(&node.asdoc, &first.1.combine_with(last.1.clone()));
+        }
+        walk_package_definition(self, node);
+    }
+
+    fn visit_variable_definition(&mut self, node: &VariableDefinition) {
+        for binding in &node.bindings {
+            self.check(&node.asdoc, &binding.destructuring.destructuring.location());
+        }
+        walk_variable_definition(self, node);
+    }
+
+    fn visit_function_definition(&mut self, node: &FunctionDefinition) {
+        self.check(&node.asdoc, &node.name.location());
+        walk_function_definition(self, node);
+    }
+
+    fn visit_class_definition(&mut self, node: &ClassDefinition) {
+        self.check(&node.asdoc, &node.name.1);
+        walk_class_definition(self, node);
+    }
+
+    fn visit_enum_definition(&mut self, node: &EnumDefinition) {
+        self.check(&node.asdoc, &node.name.1);
+        walk_enum_definition(self, node);
+    }
+
+    fn visit_interface_definition(&mut self, node: &InterfaceDefinition) {
+        self.check(&node.asdoc, &node.name.1);
+        walk_interface_definition(self, node);
+    }
+
+    fn visit_type_definition(&mut self, node: &TypeDefinition) {
+        self.check(&node.asdoc, &node.left.1);
+        walk_type_definition(self, node);
+    }
+
+    fn visit_namespace_definition(&mut self, node: &NamespaceDefinition) {
+        self.check(&node.asdoc, &node.left.1);
+        walk_namespace_definition(self, node);
+    }
+}
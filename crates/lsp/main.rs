@@ -0,0 +1,20 @@
+use lsp_server::Connection;
+use std::process::ExitCode;
+
+mod document;
+mod folding;
+mod hover;
+mod selection;
+mod server;
+mod symbols;
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+    let result = server::run(&connection);
+    drop(connection);
+    if let Err(error) = result.map_err(|error| error.to_string()).and_then(|_| io_threads.join().map_err(|error| error.to_string())) {
+        eprintln!("as3parser-lsp: {error}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
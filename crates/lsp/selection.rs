@@ -0,0 +1,55 @@
+use crate::document::*;
+use lsp_types::{Position, SelectionRange};
+use mxmlextrema_as3parser::ns::*;
+
+impl Document {
+    pub fn selection_range(&self, position: Position) -> SelectionRange {
+        let offset = self.offset(position);
+        let path = match &self.tree {
+            DocumentTree::Program(program) => NodePath::in_program(program, offset),
+            DocumentTree::Mxml(mxml) => NodePath::in_mxml(mxml, offset),
+            DocumentTree::Css(css) => NodePath::in_css(css, offset),
+        };
+
+        // Besides the covering nodes, select the parts of a node
+        // that are not nodes themselves, such as attribute names.
+        let mut locations = vec![];
+        for node in &path.nodes {
+            locations.push(node.location());
+            match node {
+                CoveringNode::FunctionCommon(common) => {
+                    locations.push(common.signature.location.clone());
+                    if let Some(FunctionBody::Block(block)) = &common.body {
+                        locations.push(block.location.clone());
+                    }
+                },
+                CoveringNode::MxmlElement(element) => locations.push(element.name.location.clone()),
+                CoveringNode::MxmlAttribute(attribute) => {
+                    locations.push(attribute.name.location.clone());
+                    locations.push(attribute.value.1.clone());
+                },
+                CoveringNode::CssProperty(property) => {
+                    locations.push(property.name.1.clone());
+                    locations.push(property.value.location());
+                },
+                _ => {},
+            }
+        }
+        locations.retain(|location| self.is_local(location) && contains(location, offset));
+
+        // Build the chain from the innermost location.
+        locations.sort_by_key(|location| (location.last_offset() - location.first_offset(), location.first_offset()));
+        locations.dedup_by_key(|location| (location.first_offset(), location.last_offset()));
+        let mut result: Option<SelectionRange> = None;
+        for location in locations.iter().rev() {
+            result = Some(SelectionRange {
+                range: range(location),
+                parent: result.map(Box::new),
+            });
+        }
+        result.unwrap_or(SelectionRange {
+            range: lsp_types::Range { start: position, end: position },
+            parent: None,
+        })
+    }
+}
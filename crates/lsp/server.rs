@@ -0,0 +1,290 @@
+use crate::document::*;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics};
+use lsp_types::request::{DocumentSymbolRequest, FoldingRangeRequest, HoverRequest, Request as _, SelectionRangeRequest};
+use lsp_types::*;
+use mxmlextrema_as3parser::ns::{FileSystemSourceProvider, InMemorySourceProvider, Rc};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Serves LSP requests over `connection` until the client shuts down.
+pub fn run(connection: &Connection) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                connection.sender.send(Message::Response(server.handle_request(request)))?;
+            },
+            Message::Notification(notification) => {
+                for notification in server.handle_notification(notification) {
+                    connection.sender.send(Message::Notification(notification))?;
+                }
+            },
+            Message::Response(_) => {},
+        }
+    }
+    Ok(())
+}
+
+struct Server {
+    documents: HashMap<Url, Document>,
+    /// Included sources that diagnostics were published to, per document.
+    included_uris: HashMap<Url, Vec<Url>>,
+    /// The text of open documents, which takes precedence over
+    /// the file system when loading included sources.
+    sources: Rc<InMemorySourceProvider>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self {
+            documents: HashMap::new(),
+            included_uris: HashMap::new(),
+            sources: Rc::new(InMemorySourceProvider::with_fallback(Rc::new(FileSystemSourceProvider))),
+        }
+    }
+}
+
+impl Server {
+    fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            DocumentSymbolRequest::METHOD => self.dispatch::<DocumentSymbolRequest>(request, |document, _| {
+                Some(DocumentSymbolResponse::Nested(document.symbols()))
+            }, |params| &params.text_document.uri),
+            FoldingRangeRequest::METHOD => self.dispatch::<FoldingRangeRequest>(request, |document, _| {
+                Some(document.folding_ranges())
+            }, |params| &params.text_document.uri),
+            SelectionRangeRequest::METHOD => self.dispatch::<SelectionRangeRequest>(request, |document, params| {
+                Some(params.positions.iter().map(|position| document.selection_range(*position)).collect())
+            }, |params| &params.text_document.uri),
+            HoverRequest::METHOD => self.dispatch::<HoverRequest>(request, |document, params| {
+                document.hover(params.text_document_position_params.position)
+            }, |params| &params.text_document_position_params.text_document.uri),
+            _ => Response::new_err(request.id, ErrorCode::MethodNotFound as i32, format!("Unsupported request: {}", request.method)),
+        }
+    }
+
+    fn dispatch<R: lsp_types::request::Request>(
+        &self,
+        request: Request,
+        handler: impl FnOnce(&Document, &R::Params) -> R::Result,
+        uri: impl FnOnce(&R::Params) -> &Url,
+    ) -> Response {
+        let params = match serde_json::from_value::<R::Params>(request.params) {
+            Ok(params) => params,
+            Err(error) => return Response::new_err(request.id, ErrorCode::InvalidParams as i32, error.to_string()),
+        };
+        let Some(document) = self.documents.get(uri(&params)) else {
+            return Response::new_err(request.id, ErrorCode::InvalidParams as i32, "Document is not open.".into());
+        };
+        Response::new_ok(request.id, handler(document, &params))
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Vec<Notification> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) = serde_json::from_value::<DidOpenTextDocumentParams>(notification.params) else {
+                    return vec![];
+                };
+                let document = params.text_document;
+                self.open(document.uri, &document.language_id, document.text)
+            },
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) = serde_json::from_value::<DidChangeTextDocumentParams>(notification.params) else {
+                    return vec![];
+                };
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return vec![];
+                };
+                self.open(params.text_document.uri, "", change.text)
+            },
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) = serde_json::from_value::<DidCloseTextDocumentParams>(notification.params) else {
+                    return vec![];
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                if let Ok(path) = uri.to_file_path() {
+                    self.sources.remove(&path.to_string_lossy());
+                }
+                let mut uris = self.included_uris.remove(&uri).unwrap_or_default();
+                uris.push(uri);
+                uris.into_iter().map(|uri| publish_diagnostics(uri, vec![])).collect()
+            },
+            _ => vec![],
+        }
+    }
+
+    /// Parses a document and publishes its diagnostics. An empty language
+    /// identifier keeps the grammar of the document being replaced.
+    fn open(&mut self, uri: Url, language_id: &str, text: String) -> Vec<Notification> {
+        let language_id = match self.documents.get(&uri).map(|document| &document.tree) {
+            Some(DocumentTree::Mxml(_)) if language_id.is_empty() => "mxml",
+            Some(DocumentTree::Css(_)) if language_id.is_empty() => "css",
+            _ => language_id,
+        };
+        if let Ok(path) = uri.to_file_path() {
+            self.sources.insert(&path.to_string_lossy(), &text);
+        }
+        let document = Document::new(&uri, language_id, text, self.sources.clone());
+        let groups = document.diagnostics(&uri);
+        self.documents.insert(uri.clone(), document);
+
+        let included_uris: Vec<Url> = groups.iter().skip(1).map(|(uri, _)| uri.clone()).collect();
+        let stale_uris: Vec<Url> = self.included_uris.insert(uri, included_uris.clone()).unwrap_or_default()
+            .into_iter().filter(|uri| !included_uris.contains(uri)).collect();
+        groups.into_iter().map(|(uri, diagnostics)| publish_diagnostics(uri, diagnostics))
+            .chain(stale_uris.into_iter().map(|uri| publish_diagnostics(uri, vec![])))
+            .collect()
+    }
+}
+
+fn publish_diagnostics(uri: Url, diagnostics: Vec<Diagnostic>) -> Notification {
+    Notification::new(PublishDiagnostics::METHOD.into(), PublishDiagnosticsParams { uri, diagnostics, version: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_server::RequestId;
+    use lsp_types::notification::{Exit, Initialized};
+    use lsp_types::request::{Initialize, Shutdown};
+
+    struct FakeClient {
+        connection: Connection,
+        next_id: i32,
+    }
+
+    impl FakeClient {
+        fn request<R: lsp_types::request::Request>(&mut self, params: R::Params) -> R::Result {
+            self.next_id += 1;
+            let id = RequestId::from(self.next_id);
+            self.connection.sender.send(Message::Request(Request::new(id.clone(), R::METHOD.into(), params))).unwrap();
+            loop {
+                if let Message::Response(response) = self.connection.receiver.recv().unwrap() {
+                    assert_eq!(response.id, id);
+                    assert!(response.error.is_none(), "{:?}", response.error);
+                    return serde_json::from_value(response.result.unwrap()).unwrap();
+                }
+            }
+        }
+
+        fn notify<N: lsp_types::notification::Notification>(&self, params: N::Params) {
+            self.connection.sender.send(Message::Notification(Notification::new(N::METHOD.into(), params))).unwrap();
+        }
+
+        fn open(&self, uri: &Url, language_id: &str, text: &str) -> PublishDiagnosticsParams {
+            self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+                text_document: TextDocumentItem::new(uri.clone(), language_id.into(), 1, text.into()),
+            });
+            let Message::Notification(notification) = self.connection.receiver.recv().unwrap() else {
+                panic!("Expected diagnostics.");
+            };
+            assert_eq!(notification.method, PublishDiagnostics::METHOD);
+            serde_json::from_value(notification.params).unwrap()
+        }
+    }
+
+    fn symbol_names(symbols: &[DocumentSymbol]) -> Vec<String> {
+        symbols.iter().flat_map(|symbol| std::iter::once(symbol.name.clone()).chain(symbol_names(symbol.children.as_deref().unwrap_or_default()))).collect()
+    }
+
+    #[test]
+    fn test_server() {
+        let (server_connection, connection) = Connection::memory();
+        let server = std::thread::spawn(move || run(&server_connection).unwrap());
+        let mut client = FakeClient { connection, next_id: 0 };
+        client.request::<Initialize>(InitializeParams::default());
+        client.notify::<Initialized>(InitializedParams {});
+
+        let uri = Url::parse("file:///project/src/p/C.as").unwrap();
+        let text = "package p {\n    public class C {\n        /** Counts things. */\n        public function count(): Number {\n            return 0\n        }\n    }\n}\nx = ;\n";
+        let diagnostics = client.open(&uri, "actionscript", text);
+        assert_eq!(diagnostics.diagnostics.len(), 1);
+        assert_eq!(diagnostics.diagnostics[0].range.start, Position::new(8, 4));
+
+        let document = TextDocumentIdentifier::new(uri.clone());
+        let Some(DocumentSymbolResponse::Nested(symbols)) = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: document.clone(),
+            work_done_progress_params: default(),
+            partial_result_params: default(),
+        }) else {
+            panic!();
+        };
+        assert_eq!(symbol_names(&symbols), ["p", "C", "count"]);
+
+        let hover = client.request::<HoverRequest>(HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(document.clone(), Position::new(3, 26)),
+            work_done_progress_params: default(),
+        }).unwrap();
+        assert!(matches!(hover.contents, HoverContents::Markup(markup) if markup.value == "Counts things."));
+
+        let folding_ranges = client.request::<FoldingRangeRequest>(FoldingRangeParams {
+            text_document: document.clone(),
+            work_done_progress_params: default(),
+            partial_result_params: default(),
+        }).unwrap();
+        assert_eq!(folding_ranges.iter().map(|range| (range.start_line, range.end_line)).collect::<Vec<_>>(), [(0, 6), (1, 5), (3, 4)]);
+
+        let selection_ranges = client.request::<SelectionRangeRequest>(SelectionRangeParams {
+            text_document: document.clone(),
+            positions: vec![Position::new(4, 19)],
+            work_done_progress_params: default(),
+            partial_result_params: default(),
+        }).unwrap();
+        let mut selection_range = Some(&selection_ranges[0]);
+        let mut count = 0;
+        while let Some(range) = selection_range {
+            count += 1;
+            selection_range = range.parent.as_deref();
+        }
+        assert_eq!(selection_ranges[0].range, Range::new(Position::new(4, 19), Position::new(4, 20)));
+        assert!(count > 5);
+
+        let mxml_uri = Url::parse("file:///project/src/App.mxml").unwrap();
+        let diagnostics = client.open(&mxml_uri, "xml", "<s:Application xmlns:s=\"library://ns.adobe.com/flex/spark\">\n    <s:Button id=\"button\"/>\n</s:Application>");
+        assert!(diagnostics.diagnostics.is_empty());
+        let Some(DocumentSymbolResponse::Nested(symbols)) = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(mxml_uri),
+            work_done_progress_params: default(),
+            partial_result_params: default(),
+        }) else {
+            panic!();
+        };
+        assert_eq!(symbol_names(&symbols), ["s:Application", "s:Button"]);
+        assert_eq!(symbols[0].children.as_ref().unwrap()[0].detail.as_deref(), Some("button"));
+
+        let css_uri = Url::parse("file:///project/src/style.css").unwrap();
+        let diagnostics = client.open(&css_uri, "css", "s|Button {\n    color: red;\n}\n");
+        assert!(diagnostics.diagnostics.is_empty());
+        let Some(DocumentSymbolResponse::Nested(symbols)) = client.request::<DocumentSymbolRequest>(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(css_uri),
+            work_done_progress_params: default(),
+            partial_result_params: default(),
+        }) else {
+            panic!();
+        };
+        assert_eq!(symbol_names(&symbols), ["s|Button", "color"]);
+
+        client.request::<Shutdown>(());
+        client.notify::<Exit>(());
+        server.join().unwrap();
+    }
+
+    fn default<T: Default>() -> T {
+        T::default()
+    }
+}
@@ -0,0 +1,99 @@
+use crate::document::*;
+use lsp_types::{DocumentSymbol, SymbolKind};
+use mxmlextrema_as3parser::ns::*;
+
+impl Document {
+    pub fn symbols(&self) -> Vec<DocumentSymbol> {
+        match &self.tree {
+            DocumentTree::Program(program) => self.outline_symbols(&OutlineSymbol::outline(program), None),
+            DocumentTree::Mxml(mxml) => mxml_symbols(&mxml.content),
+            DocumentTree::Css(css) => css.directives.iter().filter_map(|directive| css_directive_symbol(directive)).collect(),
+        }
+    }
+
+    /// Converts outline entries, leaving out those from included sources.
+    fn outline_symbols(&self, outline: &[OutlineSymbol], parent: Option<OutlineSymbolKind>) -> Vec<DocumentSymbol> {
+        outline.iter().filter(|entry| self.is_local(&entry.location)).map(|entry| {
+            let member = matches!(parent, Some(OutlineSymbolKind::Class | OutlineSymbolKind::Interface));
+            let kind = match entry.kind {
+                OutlineSymbolKind::Package => SymbolKind::PACKAGE,
+                OutlineSymbolKind::Class | OutlineSymbolKind::TypeAlias => SymbolKind::CLASS,
+                OutlineSymbolKind::Interface => SymbolKind::INTERFACE,
+                OutlineSymbolKind::Enum => SymbolKind::ENUM,
+                OutlineSymbolKind::Namespace => SymbolKind::NAMESPACE,
+                OutlineSymbolKind::Function => SymbolKind::FUNCTION,
+                OutlineSymbolKind::Method => SymbolKind::METHOD,
+                OutlineSymbolKind::Getter | OutlineSymbolKind::Setter => SymbolKind::PROPERTY,
+                OutlineSymbolKind::Constructor => SymbolKind::CONSTRUCTOR,
+                OutlineSymbolKind::Variable | OutlineSymbolKind::Constant if member => SymbolKind::FIELD,
+                OutlineSymbolKind::Variable => SymbolKind::VARIABLE,
+                OutlineSymbolKind::Constant => SymbolKind::CONSTANT,
+                OutlineSymbolKind::EnumMember => SymbolKind::ENUM_MEMBER,
+            };
+            let name = if entry.name.is_empty() { "(default package)".into() } else { entry.name.clone() };
+            let children = self.outline_symbols(&entry.children, Some(entry.kind));
+            let mut symbol = symbol(name, kind, &entry.location, &entry.name_location, children);
+            symbol.detail = Some(entry.signature.clone());
+            symbol
+        }).collect()
+    }
+}
+
+fn mxml_symbols(content: &[Rc<MxmlContent>]) -> Vec<DocumentSymbol> {
+    let mut symbols = vec![];
+    for node in content {
+        let MxmlContent::Element(element) = node.as_ref() else {
+            continue;
+        };
+        let name = element.name.prefix.as_ref().map_or(element.name.name.clone(), |prefix| format!("{prefix}:{}", element.name.name));
+        let id = element.attributes.iter().find(|attribute| attribute.name.prefix.is_none() && attribute.name.name == "id");
+        let children = element.content.as_ref().map(|content| mxml_symbols(content)).unwrap_or_default();
+        let mut symbol = symbol(name, SymbolKind::OBJECT, &element.location, &element.name.location, children);
+        symbol.detail = id.map(|attribute| attribute.value.0.clone());
+        symbols.push(symbol);
+    }
+    symbols
+}
+
+fn css_directive_symbol(directive: &CssDirective) -> Option<DocumentSymbol> {
+    match directive {
+        CssDirective::Rule(rule) => Some(css_rule_symbol(rule)),
+        CssDirective::MediaQuery(media) => {
+            let children = media.rules.iter().map(|rule| css_rule_symbol(rule)).collect();
+            Some(symbol("@media".into(), SymbolKind::MODULE, &media.location, &media.location, children))
+        },
+        CssDirective::FontFace(font_face) => {
+            let children = font_face.properties.iter().map(|property| css_property_symbol(property)).collect();
+            Some(symbol("@font-face".into(), SymbolKind::MODULE, &font_face.location, &font_face.location, children))
+        },
+        CssDirective::NamespaceDefinition(defn) => {
+            Some(symbol(defn.prefix.0.clone(), SymbolKind::NAMESPACE, &defn.location, &defn.prefix.1, vec![]))
+        },
+        CssDirective::Invalidated(_) => None,
+    }
+}
+
+fn css_rule_symbol(rule: &CssRule) -> DocumentSymbol {
+    let selectors = rule.selectors.first().zip(rule.selectors.last())
+        .map_or(rule.location.clone(), |(first, last)| first.location().combine_with(last.location()));
+    let children = rule.properties.iter().map(|property| css_property_symbol(property)).collect();
+    symbol(selectors.text(), SymbolKind::CLASS, &rule.location, &selectors, children)
+}
+
+fn css_property_symbol(property: &CssProperty) -> DocumentSymbol {
+    symbol(property.name.0.clone(), SymbolKind::PROPERTY, &property.location, &property.name.1, vec![])
+}
+
+#[allow(deprecated)]
+fn symbol(name: String, kind: SymbolKind, location: &Location, selection_location: &Location, children: Vec<DocumentSymbol>) -> DocumentSymbol {
+    DocumentSymbol {
+        name,
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: range(location),
+        selection_range: range(selection_location),
+        children: if children.is_empty() { None } else { Some(children) },
+    }
+}
@@ -5,4 +5,14 @@ pub use compilation_unit::*;
 mod comment;
 pub use comment::*;
 mod location;
-pub use location::*;
\ No newline at end of file
+pub use location::*;
+mod located_tree;
+pub use located_tree::*;
+mod tree_cache;
+pub use tree_cache::*;
+mod syntax_token;
+pub use syntax_token::*;
+#[cfg(feature = "sync")]
+mod source_text;
+mod text_edit;
+pub use text_edit::*;
\ No newline at end of file
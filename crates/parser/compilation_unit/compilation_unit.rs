@@ -1,21 +1,26 @@
-use std::{any::Any, cell::RefMut};
 use std::fmt::{Debug, Formatter};
 use crate::ns::*;
+#[cfg(not(feature = "sync"))]
 use hydroperfox_sourcetext::SourceText;
+#[cfg(feature = "sync")]
+use super::source_text::SourceText;
 
 /// `CompilationUnit` identifies an AS3 compilation unit and contains
 /// a source text.
 pub struct CompilationUnit {
     pub(crate) file_path: Option<String>,
     pub(crate) source_text: SourceText,
-    pub(crate) compiler_options: RefCell<Option<Rc<dyn Any>>>,
+    pub(crate) compiler_options: RefCell<Option<Rc<DynAny>>>,
     pub(crate) diagnostics: RefCell<Vec<Diagnostic>>,
     pub(crate) error_count: Cell<u32>,
     pub(crate) warning_count: Cell<u32>,
     pub(crate) invalidated: Cell<bool>,
     pub(crate) comments: RefCell<Vec<Rc<Comment>>>,
+    pub(crate) tokens: RefCell<Vec<Rc<SyntaxToken>>>,
     pub(crate) included_from: RefCell<Option<Rc<CompilationUnit>>>,
+    pub(crate) include_location: RefCell<Option<Location>>,
     pub(crate) nested_compilation_units: RefCell<Vec<Rc<CompilationUnit>>>,
+    pub(crate) node_index: RefCell<NodeIndex>,
 }
 
 impl Debug for CompilationUnit {
@@ -35,8 +40,11 @@ impl Default for CompilationUnit {
             error_count: Cell::new(0),
             warning_count: Cell::new(0),
             comments: RefCell::new(vec![]),
+            tokens: RefCell::new(vec![]),
             nested_compilation_units: RefCell::new(vec![]),
             included_from: RefCell::new(None),
+            include_location: RefCell::new(None),
+            node_index: RefCell::new(NodeIndex::default()),
         }
     }
 }
@@ -53,8 +61,11 @@ impl CompilationUnit {
             error_count: Cell::new(0),
             warning_count: Cell::new(0),
             comments: RefCell::new(vec![]),
+            tokens: RefCell::new(vec![]),
             nested_compilation_units: RefCell::new(vec![]),
             included_from: RefCell::new(None),
+            include_location: RefCell::new(None),
+            node_index: RefCell::new(NodeIndex::default()),
         })
     }
 
@@ -69,15 +80,39 @@ impl CompilationUnit {
     }
 
     /// Compiler options.
-    pub fn compiler_options(&self) -> Option<Rc<dyn Any>> {
+    pub fn compiler_options(&self) -> Option<Rc<DynAny>> {
         self.compiler_options.borrow().clone()
     }
 
     /// Set compiler options.
-    pub fn set_compiler_options(&self, options: Option<Rc<dyn Any>>) {
+    pub fn set_compiler_options(&self, options: Option<Rc<DynAny>>) {
         self.compiler_options.replace(options);
     }
 
+    /// Applies non-overlapping edits to the source text, returning an
+    /// unparsed compilation unit with the same file path and compiler options,
+    /// along with the mapping of offsets from this unit to the new one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two edits overlap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    ///
+    /// let cu = CompilationUnit::new(None, "x = 10; y = 20;".into());
+    /// let (new_cu, offsets) = cu.apply_edits(&[TextEdit::new(4..6, "100")]);
+    /// assert_eq!(new_cu.text(), "x = 100; y = 20;");
+    /// assert_eq!(offsets.map(8), 9);
+    /// ```
+    pub fn apply_edits(&self, edits: &[TextEdit]) -> (Rc<CompilationUnit>, OffsetMap) {
+        let compilation_unit = CompilationUnit::new(self.file_path(), TextEdit::apply_all(self.text(), edits));
+        compilation_unit.set_compiler_options(self.compiler_options());
+        (compilation_unit, OffsetMap::new(edits))
+    }
+
     /// Whether the source contains any errors after parsing
     /// and/or verification.
     pub fn invalidated(&self) -> bool {
@@ -115,6 +150,31 @@ impl CompilationUnit {
         }
     }
 
+    /// Returns the node of type `T` identified by `id`, if it is alive.
+    pub fn node<T: IdentifiedNode>(&self, id: NodeId) -> Option<Rc<T>> {
+        self.node_index.borrow().get(id)
+    }
+
+    /// The number of node identifiers assigned in the compilation unit.
+    pub fn node_count(&self) -> usize {
+        self.node_index.borrow().len()
+    }
+
+    /// The tokens of the source file along with their trivia. This collection
+    /// is only filled when parsing with the `lossless` option.
+    pub fn tokens(&self) -> Vec<Rc<SyntaxToken>> {
+        self.tokens.borrow().clone()
+    }
+
+    /// The tokens that a node at `location` consists of, when parsing
+    /// with the `lossless` option.
+    pub fn tokens_within(&self, location: &Location) -> Vec<Rc<SyntaxToken>> {
+        let tokens = self.tokens.borrow();
+        let i = tokens.partition_point(|t| t.location.first_offset() < location.first_offset());
+        let j = tokens.partition_point(|t| t.location.first_offset() < location.last_offset());
+        tokens[i..j].to_vec()
+    }
+
     /// Diagnostics of the source file after parsing and/or
     /// verification.
     pub fn diagnostics(&self) -> Vec<Diagnostic> {
@@ -180,12 +240,22 @@ impl CompilationUnit {
         self.included_from.replace(included_from);
     }
 
-    pub(crate) fn include_directive_is_circular(&self, file_path: &str) -> bool {
-        if canonicalize_path(&self.file_path.clone().unwrap_or("".into())) == canonicalize_path(file_path) {
+    /// If this compilation unit is subsequent of an include directive in another
+    /// compilation unit, returns the location of that include directive.
+    pub fn include_location(&self) -> Option<Location> {
+        self.include_location.borrow().clone()
+    }
+
+    pub(crate) fn set_include_location(&self, include_location: Option<Location>) {
+        self.include_location.replace(include_location);
+    }
+
+    pub(crate) fn include_directive_is_circular(&self, file_path: &str, source_provider: &dyn SourceProvider) -> bool {
+        if self.file_path.as_ref().is_some_and(|path| source_provider.canonicalize(path) == source_provider.canonicalize(file_path)) {
             return true;
         }
         if let Some(included_from) = self.included_from() {
-            return included_from.include_directive_is_circular(file_path);
+            return included_from.include_directive_is_circular(file_path, source_provider);
         }
         return false;
     }
@@ -237,6 +307,16 @@ impl CompilationUnit {
         self.source_text.get_line_offset(line)
     }
 
+    /// Retrieves offset from a line number (counted from one) and a zero based
+    /// column, as returned by `get_line_number()` and `get_column()`. A column
+    /// past the end of the line resolves to the end of that line.
+    pub fn get_offset(&self, line: usize, column: usize) -> Option<usize> {
+        let line_offset = self.get_line_offset(line)?;
+        let text = &self.source_text.contents[line_offset..];
+        let line_length = text.find(['\n', '\r', '\u{2028}', '\u{2029}']).unwrap_or(text.len());
+        Some(line_offset + text[..line_length].char_indices().nth(column).map_or(line_length, |(i, _)| i))
+    }
+
     /// Retrieves the offset from the corresponding line of an offset.
     pub fn get_line_offset_from_offset(&self, offset: usize) -> usize {
         self.source_text.get_line_offset_from_offset(offset)
@@ -246,8 +326,4 @@ impl CompilationUnit {
         let line_offset = self.get_line_offset(line).unwrap();
         CharacterValidator::indent_count(&self.source_text.contents[line_offset..])
     }
-}
-
-fn canonicalize_path(path: &str) -> String {
-    std::path::Path::new(path).canonicalize().unwrap_or(std::path::PathBuf::new()).to_string_lossy().into_owned()
 }
\ No newline at end of file
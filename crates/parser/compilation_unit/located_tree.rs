@@ -0,0 +1,397 @@
+use crate::ns::*;
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use serde::de::{self, MapAccess, SeqAccess};
+use serde::ser::SerializeStruct;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+thread_local! {
+    /// The compilation units of the `LocatedTree` being serialized
+    /// or deserialized in the current thread.
+    static LOCATED_UNITS: std::cell::RefCell<Option<LocatedUnits>> = const { std::cell::RefCell::new(None) };
+}
+
+struct LocatedUnits {
+    units: Vec<Rc<CompilationUnit>>,
+    /// First offset of the previous location, from which the next one
+    /// is encoded in formats that are not human-readable.
+    previous_offset: usize,
+}
+
+/// A tree along with the compilation units that its locations refer to.
+///
+/// A `LocatedTree` serializes a table of compilation units, including their
+/// source text and any compilation units nested through include directives,
+/// followed by the tree, where every location is serialized as a unit index
+/// and a pair of offsets rather than as a `"line:column-line:column"` string.
+/// Deserializing it re-creates the compilation units and binds the tree to them.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let cu = CompilationUnit::new(None, "x = 10;".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let json = serde_json::to_string(&LocatedTree::new(program)).unwrap();
+///
+/// let located: LocatedTree<Rc<Program>> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(located.compilation_unit.text(), "x = 10;");
+/// assert_eq!(located.tree.directives[0].location().text(), "x = 10;");
+/// ```
+pub struct LocatedTree<T> {
+    pub compilation_unit: Rc<CompilationUnit>,
+    pub tree: T,
+}
+
+impl<T: TreeRoot> LocatedTree<T> {
+    pub fn new(tree: T) -> Self {
+        Self { compilation_unit: tree.root_location().compilation_unit(), tree }
+    }
+}
+
+/// The root of a tree that can be serialized as a [`LocatedTree`].
+pub trait TreeRoot: Serialize + for<'de> Deserialize<'de> {
+    fn root_location(&self) -> Location;
+
+    /// Completes a deserialized tree, such as by assigning node identifiers.
+    fn complete(self) -> Self;
+}
+
+impl TreeRoot for Rc<Program> {
+    fn root_location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn complete(self) -> Self {
+        NodeIdAssigner.visit_program(&self);
+        self
+    }
+}
+
+impl TreeRoot for Rc<Expression> {
+    fn root_location(&self) -> Location {
+        self.location()
+    }
+
+    fn complete(self) -> Self {
+        NodeIdAssigner.visit_expression(&self);
+        self
+    }
+}
+
+impl TreeRoot for Rc<Mxml> {
+    fn root_location(&self) -> Location {
+        self.location.clone()
+    }
+
+    /// Rebuilds the namespace mappings of the elements,
+    /// which are not serialized.
+    fn complete(self) -> Self {
+        let namespace = Rc::new(MxmlNamespace::new(None));
+        let mxml = Rc::new(Mxml {
+            content: complete_mxml_content(&self.content, &namespace),
+            ..self.as_ref().clone()
+        });
+        NodeIdAssigner::assign_mxml(&mxml);
+        mxml
+    }
+}
+
+fn complete_mxml_content(content: &[Rc<MxmlContent>], parent_namespace: &Rc<MxmlNamespace>) -> Vec<Rc<MxmlContent>> {
+    content.iter().map(|node| {
+        let MxmlContent::Element(element) = node.as_ref() else {
+            return node.clone();
+        };
+        let namespace = Rc::new(MxmlNamespace::new(Some(parent_namespace)));
+        for attribute in element.attributes.iter().filter(|attribute| attribute.xmlns) {
+            let prefix = if attribute.name.prefix.is_some() { attribute.name.name.as_str() } else { MxmlNamespace::DEFAULT_NAMESPACE };
+            namespace.set(prefix, &attribute.value.0);
+        }
+        Rc::new(MxmlContent::Element(Rc::new(MxmlElement {
+            content: element.content.as_ref().map(|content| complete_mxml_content(content, &namespace)),
+            namespace,
+            ..element.as_ref().clone()
+        })))
+    }).collect()
+}
+
+impl TreeRoot for Rc<CssDocument> {
+    fn root_location(&self) -> Location {
+        self.location.clone()
+    }
+
+    fn complete(self) -> Self {
+        NodeIdAssigner::assign_css(&self);
+        self
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CompilationUnitEntry {
+    file_path: Option<String>,
+    text: String,
+    /// Index of the compilation unit that includes this one.
+    included_from: Option<usize>,
+    comments: Vec<CommentEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommentEntry {
+    multiline: bool,
+    content: String,
+    first_offset: usize,
+    last_offset: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LocationEntry {
+    unit: usize,
+    first_offset: usize,
+    last_offset: usize,
+}
+
+/// A location as encoded in formats that are not human-readable:
+/// the distance from the first offset of the previous location,
+/// followed by the length.
+#[derive(Serialize, Deserialize)]
+struct CompactLocationEntry(usize, i64, usize);
+
+/// Installs the compilation units of a `LocatedTree` for the current thread,
+/// restoring the previous ones when dropped.
+pub(crate) struct LocatedUnitsGuard(Option<LocatedUnits>);
+
+impl LocatedUnitsGuard {
+    pub fn new(units: Vec<Rc<CompilationUnit>>) -> Self {
+        Self(LOCATED_UNITS.with(|cell| cell.replace(Some(LocatedUnits { units, previous_offset: 0 }))))
+    }
+}
+
+impl Drop for LocatedUnitsGuard {
+    fn drop(&mut self) {
+        LOCATED_UNITS.with(|cell| cell.replace(self.0.take()));
+    }
+}
+
+/// Returns the index of a compilation unit in the `LocatedTree` being
+/// serialized or deserialized, or `None` if there is no such tree.
+fn located_unit_index(compilation_unit: &Rc<CompilationUnit>) -> Option<Option<usize>> {
+    LOCATED_UNITS.with(|cell| cell.borrow().as_ref().map(|located| located.units.iter().position(|unit| Rc::ptr_eq(unit, compilation_unit))))
+}
+
+fn located_unit<E: de::Error>(index: usize) -> Result<Rc<CompilationUnit>, E> {
+    LOCATED_UNITS.with(|cell| match cell.borrow().as_ref() {
+        Some(located) => located.units.get(index).cloned().ok_or_else(|| E::custom(format!("unknown compilation unit {index}"))),
+        None => Err(E::custom("a location can only be deserialized as part of a LocatedTree")),
+    })
+}
+
+fn located_location<E: de::Error>(unit: usize, first_offset: usize, last_offset: usize) -> Result<Location, E> {
+    let compilation_unit = located_unit::<E>(unit)?;
+    let text = compilation_unit.text();
+    if first_offset > last_offset || !text.is_char_boundary(first_offset) || !text.is_char_boundary(last_offset) {
+        return Err(E::custom(format!("invalid offsets {first_offset}..{last_offset} in compilation unit {unit}")));
+    }
+    Ok(Location::with_offsets(&compilation_unit, first_offset, last_offset))
+}
+
+/// Serializes a location as a unit index and offsets if it is part
+/// of a `LocatedTree`, and gives the serializer back otherwise.
+pub(crate) fn serialize_located_location<S: Serializer>(location: &Location, serializer: S) -> Result<Result<S::Ok, S::Error>, S> {
+    match located_unit_index(&location.compilation_unit) {
+        None => Err(serializer),
+        Some(None) => Ok(Err(serde::ser::Error::custom("location refers to a compilation unit outside of the LocatedTree"))),
+        Some(Some(unit)) if serializer.is_human_readable() => {
+            Ok(LocationEntry { unit, first_offset: location.first_offset, last_offset: location.last_offset }.serialize(serializer))
+        },
+        Some(Some(unit)) => {
+            let Some(length) = location.last_offset.checked_sub(location.first_offset) else {
+                return Ok(Err(serde::ser::Error::custom("location ends before it starts")));
+            };
+            let previous_offset = replace_previous_offset(location.first_offset);
+            let distance = location.first_offset as i64 - previous_offset as i64;
+            Ok(CompactLocationEntry(unit, distance, length).serialize(serializer))
+        },
+    }
+}
+
+pub(crate) fn deserialize_located_location<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Location, D::Error> {
+    if deserializer.is_human_readable() {
+        let entry = LocationEntry::deserialize(deserializer)?;
+        return located_location(entry.unit, entry.first_offset, entry.last_offset);
+    }
+    let CompactLocationEntry(unit, distance, length) = CompactLocationEntry::deserialize(deserializer)?;
+    let previous_offset = LOCATED_UNITS.with(|cell| cell.borrow().as_ref().map_or(0, |located| located.previous_offset));
+    let first_offset = previous_offset.checked_add_signed(distance as isize)
+        .ok_or_else(|| de::Error::custom("invalid location offset"))?;
+    replace_previous_offset(first_offset);
+    located_location(unit, first_offset, first_offset.checked_add(length).ok_or_else(|| de::Error::custom("invalid location length"))?)
+}
+
+fn replace_previous_offset(offset: usize) -> usize {
+    LOCATED_UNITS.with(|cell| cell.borrow_mut().as_mut().map_or(0, |located| std::mem::replace(&mut located.previous_offset, offset)))
+}
+
+/// Whether a value is serialized outside of a `LocatedTree`.
+pub(crate) fn outside_located_tree<T>(_: &T) -> bool {
+    LOCATED_UNITS.with(|cell| cell.borrow().is_none())
+}
+
+/// Serializes a reference to a compilation unit of a `LocatedTree` as its index.
+pub(crate) fn serialize_unit_reference<S: Serializer>(compilation_unit: &Rc<CompilationUnit>, serializer: S) -> Result<S::Ok, S::Error> {
+    match located_unit_index(compilation_unit).flatten() {
+        Some(index) => serializer.serialize_u64(index as u64),
+        None => Err(serde::ser::Error::custom("compilation unit outside of the LocatedTree")),
+    }
+}
+
+pub(crate) fn deserialize_unit_reference<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rc<CompilationUnit>, D::Error> {
+    located_unit(usize::deserialize(deserializer)?)
+}
+
+impl<T: TreeRoot> Serialize for LocatedTree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut units = vec![self.compilation_unit.clone()];
+        let mut i = 0;
+        while i < units.len() {
+            units.extend(units[i].nested_compilation_units());
+            i += 1;
+        }
+        let entries: Vec<CompilationUnitEntry> = units.iter().map(|unit| CompilationUnitEntry {
+            file_path: unit.file_path(),
+            text: unit.text().clone(),
+            included_from: unit.included_from().and_then(|parent| units.iter().position(|unit| Rc::ptr_eq(unit, &parent))),
+            comments: unit.comments().iter().map(|comment| {
+                let location = comment.location();
+                CommentEntry {
+                    multiline: comment.multiline(),
+                    content: comment.content(),
+                    first_offset: location.first_offset(),
+                    last_offset: location.last_offset(),
+                }
+            }).collect(),
+        }).collect();
+
+        let _guard = LocatedUnitsGuard::new(units);
+        let mut state = serializer.serialize_struct("LocatedTree", 2)?;
+        state.serialize_field("compilation_units", &entries)?;
+        state.serialize_field("tree", &self.tree)?;
+        state.end()
+    }
+}
+
+impl<'de, T: TreeRoot> Deserialize<'de> for LocatedTree<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("LocatedTree", &["compilation_units", "tree"], LocatedTreeVisitor(PhantomData))
+    }
+}
+
+struct LocatedTreeVisitor<T>(PhantomData<T>);
+
+impl<T: TreeRoot> LocatedTreeVisitor<T> {
+    fn create_units<E: de::Error>(entries: Vec<CompilationUnitEntry>) -> Result<Vec<Rc<CompilationUnit>>, E> {
+        if entries.is_empty() {
+            return Err(E::custom("a LocatedTree requires at least one compilation unit"));
+        }
+        let mut units: Vec<Rc<CompilationUnit>> = vec![];
+        for entry in &entries {
+            let unit = CompilationUnit::new(entry.file_path.clone(), entry.text.clone());
+            if let Some(parent) = entry.included_from {
+                units.get(parent).ok_or_else(|| E::custom(format!("unknown compilation unit {parent}")))?
+                    .add_nested_compilation_unit(unit.clone());
+            }
+            units.push(unit);
+        }
+        let _guard = LocatedUnitsGuard::new(units.clone());
+        for (index, entry) in entries.into_iter().enumerate() {
+            for comment in entry.comments {
+                let location = located_location::<E>(index, comment.first_offset, comment.last_offset)?;
+                units[index].comments_mut().push(Rc::new(Comment::new(comment.multiline, comment.content, location)));
+            }
+        }
+        Ok(units)
+    }
+}
+
+impl<'de, T: TreeRoot> de::Visitor<'de> for LocatedTreeVisitor<T> {
+    type Value = LocatedTree<T>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("struct LocatedTree")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let entries: Vec<CompilationUnitEntry> = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let units = Self::create_units(entries)?;
+        let _guard = LocatedUnitsGuard::new(units.clone());
+        let tree: T = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(LocatedTree { compilation_unit: units[0].clone(), tree: tree.complete() })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut units: Option<Vec<Rc<CompilationUnit>>> = None;
+        let mut tree: Option<T> = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "compilation_units" => {
+                    units = Some(Self::create_units(map.next_value()?)?);
+                },
+                "tree" => {
+                    let Some(units) = &units else {
+                        return Err(de::Error::custom("compilation_units must precede tree"));
+                    };
+                    let _guard = LocatedUnitsGuard::new(units.clone());
+                    tree = Some(map.next_value()?);
+                },
+                _ => {
+                    map.next_value::<de::IgnoredAny>()?;
+                },
+            }
+        }
+        let units = units.ok_or_else(|| de::Error::missing_field("compilation_units"))?;
+        let tree = tree.ok_or_else(|| de::Error::missing_field("tree"))?;
+        Ok(LocatedTree { compilation_unit: units[0].clone(), tree: tree.complete() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    fn root_element(mxml: &Rc<Mxml>) -> Rc<MxmlElement> {
+        mxml.content.iter().find_map(|node| match node.as_ref() {
+            MxmlContent::Element(element) => Some(element.clone()),
+            _ => None,
+        }).unwrap()
+    }
+
+    fn first_child_element(element: &Rc<MxmlElement>) -> Rc<MxmlElement> {
+        element.content.as_ref().unwrap().iter().find_map(|node| match node.as_ref() {
+            MxmlContent::Element(element) => Some(element.clone()),
+            _ => None,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_located_tree() {
+        let cu = CompilationUnit::new(Some("App.mxml".into()), "<s:Application xmlns:s=\"library://ns.adobe.com/flex/spark\">\n    <!-- \u{e9} -->\n    <s:Button label=\"OK\"/>\n</s:Application>".into());
+        let mxml = ParserFacade(&cu, default()).parse_mxml();
+        let json = serde_json::to_string(&LocatedTree::new(mxml.clone())).unwrap();
+        let located: LocatedTree<Rc<Mxml>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(located.compilation_unit.file_path(), Some("App.mxml".into()));
+        assert_eq!(located.compilation_unit.text(), cu.text());
+        assert_eq!(serde_json::to_string(&located).unwrap(), json);
+
+        let original_button = first_child_element(&root_element(&mxml));
+        let button = first_child_element(&root_element(&located.tree));
+        assert_eq!(button.location.text(), original_button.location.text());
+        assert_eq!((button.location.first_line_number(), button.location.first_column()), (3, 5));
+        assert_eq!(button.namespace.get("s"), Some("library://ns.adobe.com/flex/spark".into()));
+        assert_eq!(button.attributes[0].node_id(), original_button.attributes[0].node_id());
+
+        // Locations keep their string form outside of a located tree,
+        // and cannot be deserialized on their own.
+        assert_eq!(serde_json::to_string(&button.location).unwrap(), "\"3:6-3:27\"");
+        assert!(serde_json::from_str::<Location>("\"3:6-3:27\"").is_err());
+    }
+}
@@ -1,25 +1,28 @@
 use std::cmp::Ordering;
 use std::fmt::Debug;
-use serde::{Serialize, Deserialize, Serializer};
-use std::rc::Rc;
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
 use crate::compilation_unit::*;
-use crate::util::{CharacterReader, count_first_whitespace_characters};
+use crate::tree::ignoring_locations;
+use crate::util::{CharacterReader, Rc, count_first_whitespace_characters};
 use crate::parser::CharacterValidator;
 
 /// Represents a source location. This location includes
 /// spanning lines and columns and the reference compilation unit.
-#[derive(Clone, Deserialize)]
+///
+/// A location serializes as a `"line:column-line:column"` string, unless it is
+/// serialized as part of a [`LocatedTree`], in which case it serializes as the
+/// index of its compilation unit and its offsets. A location can only be
+/// deserialized as part of a [`LocatedTree`]. Locations are left out when
+/// comparing nodes through [`StructuralEq`](crate::tree::StructuralEq).
+#[derive(Clone)]
 pub struct Location {
     /// The compilation unit that this location belongs to.
-    #[serde(skip)]
     pub(crate) compilation_unit: Rc<CompilationUnit>,
 
     /// First UTF-8 offset.
-    #[serde(skip)]
     pub(crate) first_offset: usize,
 
     /// Last UTF-8 offset.
-    #[serde(skip)]
     pub(crate) last_offset: usize,
 }
 
@@ -28,10 +31,26 @@ impl Serialize for Location {
     where
         S: Serializer,
     {
+        if ignoring_locations() {
+            return serializer.serialize_unit();
+        }
+        let serializer = match serialize_located_location(self, serializer) {
+            Ok(result) => return result,
+            Err(serializer) => serializer,
+        };
         serializer.serialize_str(&format!("{}:{}-{}:{}", self.first_line_number(), self.first_column() + 1, self.last_line_number(), self.last_column() + 1))
     }
 }
 
+impl<'de> Deserialize<'de> for Location {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_located_location(deserializer)
+    }
+}
+
 impl Debug for Location {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f,
@@ -154,6 +173,25 @@ impl Location {
         self.last_line_number() != other.first_line_number()
     }
 
+    /// Returns the locations of the include directives through which
+    /// this location is included, from the innermost to the outermost.
+    pub fn include_stack(&self) -> Vec<Location> {
+        let mut stack = vec![];
+        let mut compilation_unit = self.compilation_unit.clone();
+        while let Some(location) = compilation_unit.include_location() {
+            compilation_unit = location.compilation_unit();
+            stack.push(location);
+        }
+        stack
+    }
+
+    /// Maps this location to the outermost compilation unit, returning
+    /// the location of the outermost include directive through which it
+    /// is included, or this location if it is not included.
+    pub fn root_location(&self) -> Location {
+        self.include_stack().pop().unwrap_or_else(|| self.clone())
+    }
+
     /// Returns the source text comprising the source location.
     pub fn text(&self) -> String {
         self.compilation_unit.text()[self.first_offset..self.last_offset].to_owned()
@@ -0,0 +1,58 @@
+use crate::ns::*;
+use std::sync::OnceLock;
+
+/// Source text with the interface of `hydroperfox_sourcetext::SourceText`,
+/// used when the `sync` feature is enabled. Line offsets are computed
+/// once on first use.
+pub(crate) struct SourceText {
+    pub contents: String,
+    line_offsets: OnceLock<Vec<usize>>,
+}
+
+impl SourceText {
+    pub fn new(contents: String) -> Self {
+        Self { contents, line_offsets: OnceLock::new() }
+    }
+
+    fn line_offsets(&self) -> &[usize] {
+        self.line_offsets.get_or_init(|| {
+            let mut offsets = vec![0];
+            let mut chars = self.contents.char_indices().peekable();
+            while let Some((i, ch)) = chars.next() {
+                if CharacterValidator::is_line_terminator(ch) {
+                    if ch == '\r' && chars.next_if(|(_, ch)| *ch == '\n').is_some() {
+                        offsets.push(i + 2);
+                    } else {
+                        offsets.push(i + ch.len_utf8());
+                    }
+                }
+            }
+            offsets
+        })
+    }
+
+    /// Retrieves line number from an offset. The resulting line number
+    /// is counted from one.
+    pub fn get_line_number(&self, offset: usize) -> usize {
+        // An offset between CR and LF belongs to the next line.
+        let offset = if offset > 0 && self.contents.get(offset - 1..offset + 1) == Some("\r\n") { offset + 1 } else { offset };
+        self.line_offsets().partition_point(|line_offset| *line_offset <= offset)
+    }
+
+    /// Retrieves offset from line number (counted from one).
+    pub fn get_line_offset(&self, line: usize) -> Option<usize> {
+        self.line_offsets().get(line.checked_sub(1)?).copied()
+    }
+
+    pub fn get_line_offset_from_offset(&self, offset: usize) -> usize {
+        self.line_offsets()[self.get_line_number(offset) - 1]
+    }
+
+    pub fn get_column(&self, offset: usize) -> usize {
+        let line_offset = self.get_line_offset_from_offset(offset);
+        if line_offset > offset {
+            return 0;
+        }
+        self.contents[line_offset..offset].chars().count()
+    }
+}
@@ -0,0 +1,139 @@
+use crate::ns::*;
+
+/// A token recorded by a parser running in lossless mode,
+/// along with the trivia that precedes it.
+///
+/// Concatenating the full text of every token of a compilation unit
+/// reproduces the parsed source text exactly.
+pub struct SyntaxToken {
+    pub(crate) token: Token,
+    pub(crate) location: Location,
+    pub(crate) leading_trivia: Vec<Trivia>,
+}
+
+/// A piece of source text that does not form a token.
+#[derive(Clone)]
+pub enum Trivia {
+    Whitespace(Location),
+    LineTerminator(Location),
+    Comment(Rc<Comment>),
+    /// Characters the parser did not turn into tokens, such as
+    /// the arguments of a CSS media query or invalid characters.
+    Skipped(Location),
+}
+
+impl SyntaxToken {
+    /// Constructs a token whose leading trivia spans from `trivia_offset`
+    /// until the token.
+    pub(crate) fn new(token: Token, location: Location, trivia_offset: usize) -> Self {
+        let compilation_unit = location.compilation_unit();
+        // Comments are contributed while scanning the token,
+        // thus they are at the end of the collection.
+        let mut comments: Vec<Rc<Comment>> = compilation_unit.comments.borrow().iter().rev()
+            .take_while(|comment| comment.location().first_offset() >= trivia_offset)
+            .filter(|comment| comment.location().last_offset() <= location.first_offset())
+            .cloned().collect();
+        comments.reverse();
+        let leading_trivia = Trivia::scan(&compilation_unit, trivia_offset, location.first_offset(), comments);
+        Self {
+            token,
+            location,
+            leading_trivia,
+        }
+    }
+
+    pub fn token(&self) -> Token {
+        self.token.clone()
+    }
+
+    pub fn location(&self) -> Location {
+        self.location.clone()
+    }
+
+    pub fn leading_trivia(&self) -> Vec<Trivia> {
+        self.leading_trivia.clone()
+    }
+
+    /// The source text of the token, excluding trivia.
+    pub fn text(&self) -> String {
+        self.location.text()
+    }
+
+    /// The source text of the leading trivia followed by the token.
+    pub fn full_text(&self) -> String {
+        let first_offset = self.leading_trivia.first().map_or(self.location.first_offset(), |trivia| trivia.location().first_offset());
+        self.location.compilation_unit().text()[first_offset..self.location.last_offset()].to_owned()
+    }
+}
+
+impl Trivia {
+    pub fn location(&self) -> Location {
+        match self {
+            Self::Whitespace(location) |
+            Self::LineTerminator(location) |
+            Self::Skipped(location) => location.clone(),
+            Self::Comment(comment) => comment.location(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.location().text()
+    }
+
+    /// Splits the source text between two offsets into trivia, given
+    /// the comments within that span in source order.
+    pub(crate) fn scan(compilation_unit: &Rc<CompilationUnit>, first_offset: usize, last_offset: usize, mut comments: Vec<Rc<Comment>>) -> Vec<Trivia> {
+        comments.reverse();
+        let text = &compilation_unit.text()[..last_offset];
+        let mut trivia = vec![];
+        let mut i = first_offset;
+        while i < last_offset {
+            if let Some(comment) = comments.pop_if(|comment| comment.location().first_offset() == i) {
+                i = comment.location().last_offset();
+                trivia.push(Trivia::Comment(comment));
+                continue;
+            }
+            let ch = text[i..].chars().next().unwrap();
+            let j = if ch == '\r' && text[i..].starts_with("\r\n") { i + 2 } else { i + ch.len_utf8() };
+            let kind = if CharacterValidator::is_line_terminator(ch) {
+                Self::LineTerminator
+            } else if CharacterValidator::is_whitespace(ch) {
+                Self::Whitespace
+            } else {
+                Self::Skipped
+            };
+            // Join consecutive whitespace and skipped characters.
+            match (trivia.last_mut(), kind(Location::with_offsets(compilation_unit, i, j))) {
+                (Some(Self::Whitespace(location)), Self::Whitespace(_)) |
+                (Some(Self::Skipped(location)), Self::Skipped(_)) => {
+                    *location = Location::with_offsets(compilation_unit, location.first_offset(), j);
+                },
+                (_, new_trivia) => trivia.push(new_trivia),
+            }
+            i = j;
+        }
+        trivia
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_round_trip() {
+        let text = "package  {\r\n    // Line\r\n    var x =/* a */ ( 10 ) ;\n}\nx = /ab+c/gi\t; y = <a k='v'>{x} </a>\n/** Doc */ function f() {}\n";
+        let cu = CompilationUnit::new(None, text.into());
+        let program = ParserFacade(&cu, ParserOptions { lossless: true, ..default() }).parse_program();
+        assert!(!cu.invalidated());
+        assert_eq!(cu.tokens().iter().map(|t| t.full_text()).collect::<String>(), text);
+
+        let Directive::VariableDefinition(defn) = program.packages[0].block.directives[0].as_ref() else {
+            panic!();
+        };
+        let tokens = cu.tokens_within(&defn.location);
+        let texts: Vec<String> = tokens.iter().map(|t| t.text()).collect();
+        assert_eq!(texts, ["var", "x", "=", "(", "10", ")", ";"]);
+        assert!(matches!(&tokens[3].leading_trivia()[..], [Trivia::Comment(_), Trivia::Whitespace(_)]));
+    }
+}
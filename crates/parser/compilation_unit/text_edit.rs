@@ -0,0 +1,126 @@
+use crate::ns::*;
+use std::ops::Range;
+
+/// Replacement of a range of source text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Byte range of the replaced text.
+    pub range: Range<usize>,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(range: Range<usize>, new_text: &str) -> Self {
+        Self {
+            range,
+            new_text: new_text.into(),
+        }
+    }
+
+    /// Applies a sequence of non-overlapping edits to `text`.
+    /// The edits may be given in any order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if two edits overlap.
+    pub fn apply_all(text: &str, edits: &[TextEdit]) -> String {
+        let edits = Self::sorted(edits);
+        let mut result = String::new();
+        let mut i = 0;
+        for edit in &edits {
+            result.push_str(&text[i..edit.range.start]);
+            result.push_str(&edit.new_text);
+            i = edit.range.end;
+        }
+        result.push_str(&text[i..]);
+        result
+    }
+
+    /// Sorts edits by their range, panicking if two edits overlap.
+    pub(crate) fn sorted(edits: &[TextEdit]) -> Vec<TextEdit> {
+        let mut edits = edits.to_vec();
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        for pair in edits.windows(2) {
+            assert!(pair[0].range.end <= pair[1].range.start, "Overlapping text edits.");
+        }
+        edits
+    }
+}
+
+/// Mapping of offsets of a text to offsets of the text that results
+/// from applying a sequence of non-overlapping edits to it.
+#[derive(Clone, Debug)]
+pub struct OffsetMap {
+    /// The replaced ranges in order, with the length of their replacement.
+    edits: Vec<(Range<usize>, usize)>,
+    /// The change in length before each edit, followed by the total change.
+    deltas: Vec<isize>,
+}
+
+impl OffsetMap {
+    /// # Panics
+    ///
+    /// Panics if two edits overlap.
+    pub fn new(edits: &[TextEdit]) -> Self {
+        let edits: Vec<(Range<usize>, usize)> = TextEdit::sorted(edits).into_iter().map(|edit| (edit.range, edit.new_text.len())).collect();
+        let mut deltas = vec![0];
+        for (range, length) in &edits {
+            deltas.push(deltas.last().unwrap() + *length as isize - range.len() as isize);
+        }
+        Self { edits, deltas }
+    }
+
+    /// Maps an offset of the old text. Offsets within a replaced range map
+    /// to the start of its replacement, and offsets where text is inserted
+    /// map to the end of the inserted text.
+    pub fn map(&self, offset: usize) -> usize {
+        self.map_with(offset, true)
+    }
+
+    /// Maps a location of the old text to `compilation_unit`, such that it
+    /// covers the replacement of any range it overlaps, but not text inserted
+    /// at its boundaries.
+    pub fn map_location(&self, location: &Location, compilation_unit: &Rc<CompilationUnit>) -> Location {
+        let first_offset = self.map_with(location.first_offset(), true);
+        let last_offset = self.map_with(location.last_offset(), false).max(first_offset);
+        Location::with_offsets(compilation_unit, first_offset, last_offset)
+    }
+
+    /// Maps an offset, where `leading` indicates whether text inserted at
+    /// the offset precedes it, and whether an offset within a replaced range
+    /// maps to the start rather than the end of its replacement.
+    fn map_with(&self, offset: usize, leading: bool) -> usize {
+        let i = self.edits.partition_point(|(range, _)| range.start < offset || (leading && range.start == offset));
+        if i > 0 {
+            let (range, length) = &self.edits[i - 1];
+            if offset < range.end {
+                let start = range.start.wrapping_add_signed(self.deltas[i - 1]);
+                return if leading { start } else { start + length };
+            }
+        }
+        offset.wrapping_add_signed(self.deltas[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_offset_map() {
+        let cu = CompilationUnit::new(Some("A.as".into()), "foo(a, bb, c);".into());
+        let edits = [TextEdit::new(4..4, "x, "), TextEdit::new(7..9, "b"), TextEdit::new(13..14, "")];
+        let (new_cu, offsets) = cu.apply_edits(&edits);
+        assert_eq!(new_cu.text(), "foo(x, a, b, c)");
+        assert_eq!(new_cu.file_path().as_deref(), Some("A.as"));
+        assert!(!new_cu.invalidated() && new_cu.comments().is_empty());
+
+        assert_eq!([0, 4, 5, 7, 8, 9, 11, 13, 14].map(|offset| offsets.map(offset)), [0, 7, 8, 10, 10, 11, 13, 15, 15]);
+        let map_text = |first, last| offsets.map_location(&Location::with_offsets(&cu, first, last), &new_cu).text();
+        // Text inserted at the start of a location precedes it.
+        assert_eq!(map_text(4, 5), "a");
+        assert_eq!(map_text(7, 8), "b");
+        assert_eq!(map_text(0, 14), "foo(x, a, b, c)");
+        assert_eq!(map_text(4, 4), "");
+    }
+}
@@ -0,0 +1,286 @@
+use crate::ns::*;
+use serde::{Serialize, Deserialize};
+use std::fmt::{Display, Formatter};
+
+/// Versioned binary encoding of parsed trees, for caching them on disk
+/// between builds.
+///
+/// An encoded tree starts with a header holding the format version, the kind
+/// of tree, and the hash of the source text it was parsed from, followed by a
+/// string table and the tree, where locations are stored as offsets.
+/// Included compilation units, comments and diagnostics are stored as well;
+/// syntax tokens of lossless parsing are not.
+///
+/// Decoding binds the tree to a freshly created compilation unit and fails
+/// with [`TreeCacheError::SourceChanged`] unless its text, and the text of
+/// every included file, is unchanged.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let cu = CompilationUnit::new(None, "x = 10;".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let bytes = TreeCache::encode(&program).unwrap();
+///
+/// let cu = CompilationUnit::new(None, "x = 10;".into());
+/// let program: Rc<Program> = TreeCache::decode(&cu, &bytes).unwrap();
+/// assert_eq!(program.directives[0].location().text(), "x = 10;");
+///
+/// let cu = CompilationUnit::new(None, "x = 11;".into());
+/// assert_eq!(TreeCache::decode::<Rc<Program>>(&cu, &bytes).err(), Some(TreeCacheError::SourceChanged));
+/// ```
+pub struct TreeCache;
+
+/// A tree that can be encoded by [`TreeCache`].
+pub trait CachedTree: TreeRoot {
+    const KIND: u8;
+}
+
+impl CachedTree for Rc<Program> {
+    const KIND: u8 = 0;
+}
+
+impl CachedTree for Rc<Mxml> {
+    const KIND: u8 = 1;
+}
+
+impl CachedTree for Rc<CssDocument> {
+    const KIND: u8 = 2;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeCacheError {
+    /// The data is not an encoded tree.
+    InvalidFormat,
+    /// The data was encoded by another version of the format.
+    UnsupportedVersion(u32),
+    /// The data encodes another kind of tree.
+    KindMismatch,
+    /// The source text differs from the one the tree was parsed from.
+    SourceChanged,
+    /// The data is corrupt or the tree could not be encoded.
+    Malformed(String),
+}
+
+impl Display for TreeCacheError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFormat => f.write_str("not an encoded tree"),
+            Self::UnsupportedVersion(version) => write!(f, "unsupported tree cache version {version}"),
+            Self::KindMismatch => f.write_str("the encoded tree is of another kind"),
+            Self::SourceChanged => f.write_str("the source text has changed"),
+            Self::Malformed(message) => write!(f, "malformed tree cache: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for TreeCacheError {}
+
+impl From<BinaryFormatError> for TreeCacheError {
+    fn from(error: BinaryFormatError) -> Self {
+        Self::Malformed(error.0)
+    }
+}
+
+const MAGIC: &[u8; 4] = b"AS3T";
+const HEADER_LENGTH: usize = 17;
+
+#[derive(Serialize, Deserialize)]
+struct UnitEntry {
+    file_path: Option<String>,
+    source_hash: u64,
+    /// Index of the compilation unit that includes this one.
+    included_from: Option<usize>,
+    invalidated: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UnitState {
+    include_location: Option<Location>,
+    comments: Vec<CommentEntry>,
+    diagnostics: Vec<DiagnosticEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommentEntry {
+    multiline: bool,
+    content: String,
+    location: Location,
+}
+
+/// A diagnostic, whose arguments are stored as the strings they format to.
+#[derive(Serialize, Deserialize)]
+struct DiagnosticEntry {
+    location: Location,
+    kind: i32,
+    is_warning: bool,
+    is_verify_error: bool,
+    arguments: Vec<String>,
+}
+
+impl TreeCache {
+    /// The version of the format, changed whenever
+    /// the format or the trees change.
+    pub const VERSION: u32 = 4;
+
+    /// Hash of a source text, as stored in an encoded tree.
+    pub fn source_hash(text: &str) -> u64 {
+        xxhash_rust::xxh3::xxh3_64(text.as_bytes())
+    }
+
+    pub fn encode<T: CachedTree>(tree: &T) -> Result<Vec<u8>, TreeCacheError> {
+        let compilation_unit = tree.root_location().compilation_unit();
+        let mut units = vec![compilation_unit.clone()];
+        let mut i = 0;
+        while i < units.len() {
+            units.extend(units[i].nested_compilation_units());
+            i += 1;
+        }
+        let entries: Vec<UnitEntry> = units.iter().map(|unit| UnitEntry {
+            file_path: unit.file_path(),
+            source_hash: Self::source_hash(unit.text()),
+            included_from: unit.included_from().and_then(|parent| units.iter().position(|unit| Rc::ptr_eq(unit, &parent))),
+            invalidated: unit.invalidated(),
+        }).collect();
+        let states: Vec<UnitState> = units.iter().map(|unit| UnitState {
+            include_location: unit.include_location(),
+            comments: unit.comments().iter().map(|comment| CommentEntry {
+                multiline: comment.multiline(),
+                content: comment.content(),
+                location: comment.location(),
+            }).collect(),
+            diagnostics: unit.diagnostics.borrow().iter().map(|diagnostic| DiagnosticEntry {
+                location: diagnostic.location(),
+                kind: diagnostic.id(),
+                is_warning: diagnostic.is_warning,
+                is_verify_error: diagnostic.is_verify_error,
+                arguments: diagnostic.arguments.iter().map(|argument| argument.to_string()).collect(),
+            }).collect(),
+        }).collect();
+
+        let mut serializer = BinarySerializer::new();
+        {
+            let _guard = LocatedUnitsGuard::new(units);
+            (&entries, &states, tree).serialize(&mut serializer)?;
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH);
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&Self::VERSION.to_le_bytes());
+        bytes.push(T::KIND);
+        bytes.extend_from_slice(&Self::source_hash(compilation_unit.text()).to_le_bytes());
+        bytes.extend_from_slice(&serializer.finish());
+        Ok(bytes)
+    }
+
+    /// Decodes a tree, binding it to `compilation_unit`, which should not
+    /// have been parsed. Included files are read from the file system.
+    pub fn decode<T: CachedTree>(compilation_unit: &Rc<CompilationUnit>, bytes: &[u8]) -> Result<T, TreeCacheError> {
+        Self::decode_with(compilation_unit, bytes, &FileSystemSourceProvider)
+    }
+
+    /// Decodes a tree like [`TreeCache::decode`], loading included
+    /// files from a source provider.
+    pub fn decode_with<T: CachedTree>(compilation_unit: &Rc<CompilationUnit>, bytes: &[u8], source_provider: &dyn SourceProvider) -> Result<T, TreeCacheError> {
+        if bytes.len() < HEADER_LENGTH || &bytes[0..4] != MAGIC {
+            return Err(TreeCacheError::InvalidFormat);
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        if version != Self::VERSION {
+            return Err(TreeCacheError::UnsupportedVersion(version));
+        }
+        if bytes[8] != T::KIND {
+            return Err(TreeCacheError::KindMismatch);
+        }
+        if u64::from_le_bytes(bytes[9..17].try_into().unwrap()) != Self::source_hash(compilation_unit.text()) {
+            return Err(TreeCacheError::SourceChanged);
+        }
+
+        let mut deserializer = BinaryDeserializer::new(&bytes[HEADER_LENGTH..])?;
+        let entries = Vec::<UnitEntry>::deserialize(&mut deserializer)?;
+        let units = Self::create_units(compilation_unit, &entries, source_provider)?;
+        let _guard = LocatedUnitsGuard::new(units.clone());
+
+        let states = Vec::<UnitState>::deserialize(&mut deserializer)?;
+        let tree = T::deserialize(&mut deserializer)?;
+        if !deserializer.is_at_end() || states.len() != units.len() {
+            return Err(TreeCacheError::Malformed("unexpected trailing data".into()));
+        }
+        for ((unit, entry), state) in units.iter().zip(&entries).zip(states) {
+            unit.set_include_location(state.include_location);
+            for comment in state.comments {
+                unit.comments_mut().push(Rc::new(Comment::new(comment.multiline, comment.content, comment.location)));
+            }
+            for diagnostic in state.diagnostics {
+                let kind = DiagnosticKind::from_id(diagnostic.kind)
+                    .ok_or_else(|| TreeCacheError::Malformed(format!("unknown diagnostic {}", diagnostic.kind)))?;
+                unit.add_diagnostic(Diagnostic {
+                    location: diagnostic.location,
+                    kind,
+                    is_warning: diagnostic.is_warning,
+                    is_verify_error: diagnostic.is_verify_error,
+                    arguments: diagnostic.arguments.into_iter().map(|argument| Rc::new(argument) as Rc<dyn DiagnosticArgument>).collect(),
+                    custom_kind: RefCell::new(None),
+                });
+            }
+            if entry.invalidated {
+                unit.invalidated.set(true);
+            }
+        }
+        Ok(tree.complete())
+    }
+
+    fn create_units(compilation_unit: &Rc<CompilationUnit>, entries: &[UnitEntry], source_provider: &dyn SourceProvider) -> Result<Vec<Rc<CompilationUnit>>, TreeCacheError> {
+        if entries.first().is_none_or(|entry| entry.included_from.is_some()) {
+            return Err(TreeCacheError::Malformed("missing compilation unit".into()));
+        }
+        let mut units = vec![compilation_unit.clone()];
+        for entry in &entries[1..] {
+            let text = match &entry.file_path {
+                Some(file_path) => source_provider.load(file_path).ok_or(TreeCacheError::SourceChanged)?,
+                None => String::new(),
+            };
+            if Self::source_hash(&text) != entry.source_hash {
+                return Err(TreeCacheError::SourceChanged);
+            }
+            let unit = CompilationUnit::new(entry.file_path.clone(), text);
+            unit.set_compiler_options(compilation_unit.compiler_options());
+            units.get(entry.included_from.unwrap_or(usize::MAX))
+                .ok_or_else(|| TreeCacheError::Malformed("unknown compilation unit".into()))?
+                .add_nested_compilation_unit(unit.clone());
+            units.push(unit);
+        }
+        Ok(units)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_tree_cache() {
+        let text = "package {\n    /** Doc. */\n    public class C {\n        // Note.\n        function f() { return 1 + ; }\n    }\n}";
+        let cu = CompilationUnit::new(Some("C.as".into()), text.into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        let bytes = TreeCache::encode(&program).unwrap();
+
+        let cached_cu = CompilationUnit::new(Some("C.as".into()), text.into());
+        let cached: Rc<Program> = TreeCache::decode(&cached_cu, &bytes).unwrap();
+        assert_eq!(serde_json::to_string(&LocatedTree::new(cached.clone())).unwrap(), serde_json::to_string(&LocatedTree::new(program)).unwrap());
+        assert!(cached_cu.invalidated());
+        assert_eq!(
+            cached_cu.diagnostics().iter().map(|d| d.format_english()).collect::<Vec<_>>(),
+            cu.diagnostics().iter().map(|d| d.format_english()).collect::<Vec<_>>());
+        assert_eq!(cached_cu.comments()[0].location().text(), "// Note.");
+        assert!(Rc::ptr_eq(&cached_cu.node::<Program>(NodeId(0)).unwrap(), &cached));
+
+        let cu = CompilationUnit::new(None, "@namespace s \"library://ns.adobe.com/flex/spark\";".into());
+        let document = CssParserFacade(&cu, default()).parse_document();
+        assert_eq!(TreeCache::decode::<Rc<Program>>(&cu, &TreeCache::encode(&document).unwrap()).err(), Some(TreeCacheError::KindMismatch));
+        assert_eq!(TreeCache::decode::<Rc<Program>>(&cu, b"AS3T").err(), Some(TreeCacheError::InvalidFormat));
+        assert!(matches!(TreeCache::decode::<Rc<Program>>(&cached_cu, &bytes[..bytes.len() - 1]), Err(TreeCacheError::Malformed(_))));
+    }
+}
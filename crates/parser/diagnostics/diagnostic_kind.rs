@@ -1,5 +1,8 @@
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+
 #[repr(i32)]
-#[derive(Eq, PartialEq, Clone, Copy)]
+#[derive(Eq, PartialEq, Clone, Copy, FromPrimitive)]
 pub enum DiagnosticKind {
     InvalidEscapeValue = 1024,
     UnexpectedEnd = 1025,
@@ -70,10 +73,15 @@ pub enum DiagnosticKind {
     XmlClosingTagNameMustBeEquals = 1090,
     UnexpectedIncludeExtension = 1091,
     UnallowedExpression = 1092,
+    UnexpectedIncludeExtensions = 1093,
 }
 
 impl DiagnosticKind {
     pub fn id(&self) -> i32 {
         *self as i32
     }
+
+    pub fn from_id(id: i32) -> Option<Self> {
+        FromPrimitive::from_i32(id)
+    }
 }
\ No newline at end of file
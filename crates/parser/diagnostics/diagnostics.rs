@@ -1,5 +1,3 @@
-use std::any::Any;
-
 use hydroperfox_filepaths::FlexPath;
 use maplit::hashmap;
 use crate::ns::*;
@@ -17,7 +15,7 @@ pub struct Diagnostic {
     pub(crate) is_warning: bool,
     pub(crate) is_verify_error: bool,
     pub(crate) arguments: Vec<Rc<dyn DiagnosticArgument>>,
-    pub(crate) custom_kind: RefCell<Option<Rc<dyn Any>>>,
+    pub(crate) custom_kind: RefCell<Option<Rc<DynAny>>>,
 }
 
 impl Eq for Diagnostic {}
@@ -79,6 +77,12 @@ impl Diagnostic {
         self.location.clone()
     }
 
+    /// Returns the locations of the include directives through which the
+    /// diagnostic's source is included, from the innermost to the outermost.
+    pub fn include_stack(&self) -> Vec<Location> {
+        self.location.include_stack()
+    }
+
     pub fn kind(&self) -> DiagnosticKind {
         self.kind.clone()
     }
@@ -107,11 +111,11 @@ impl Diagnostic {
         self.kind.id()
     }
 
-    pub fn custom_kind(&self) -> Option<Rc<dyn Any>> {
+    pub fn custom_kind(&self) -> Option<Rc<DynAny>> {
         self.custom_kind.borrow().clone()
     }
 
-    pub fn set_custom_kind(&self, id: Option<Rc<dyn Any>>) {
+    pub fn set_custom_kind(&self, id: Option<Rc<DynAny>>) {
         self.custom_kind.replace(id);
     }
 
@@ -122,6 +126,9 @@ impl Diagnostic {
 
     /// Formats the diagnostic by overriding the message text and providing a base Whack package's path
     /// (to relativize the source path).
+    ///
+    /// If the source of the diagnostic is included, a line follows for
+    /// every include directive of the include stack.
     pub fn format_with_message_and_base_path(&self, message: &str, id: Option<i32>, base_path: Option<&str>) -> String {
         let category = (if self.is_verify_error {
             "Verify error"
@@ -131,20 +138,30 @@ impl Diagnostic {
             "Syntax error"
         }).to_owned();
 
-        let mut file_path = self.location.compilation_unit.file_path.clone().map_or("".to_owned(), |s| format!("{s}:"));
-        if let Some(base_path) = base_path {
-            file_path = FlexPath::new_native(base_path).relative(&file_path).to_owned();
-        }
-        if file_path.starts_with(r"\\?\") {
-            file_path = file_path[4..].to_owned();
-        }
+        let file_path = Self::format_file_path(&self.location, base_path);
         let line = self.location.first_line_number();
         let column = self.location.first_column() + 1;
-        if let Some(id) = id {
+        let mut result = if let Some(id) = id {
             format!("{file_path}{line}:{column}: {category} #{}: {message}", id.to_string())
         } else {
             format!("{file_path}{line}:{column}: {category}: {message}")
+        };
+        for location in self.include_stack() {
+            let file_path = Self::format_file_path(&location, base_path);
+            result.push_str(&format!("\n    included from {file_path}{}:{}", location.first_line_number(), location.first_column() + 1));
         }
+        result
+    }
+
+    fn format_file_path(location: &Location, base_path: Option<&str>) -> String {
+        let mut file_path = location.compilation_unit.file_path.clone().map_or("".to_owned(), |s| format!("{s}:"));
+        if let Some(base_path) = base_path {
+            file_path = FlexPath::new_native(base_path).relative(&file_path).to_owned();
+        }
+        if file_path.starts_with(r"\\?\") {
+            file_path = file_path[4..].to_owned();
+        }
+        file_path
     }
 
     /// Formats the diagnostic in English.
@@ -185,9 +202,34 @@ pub macro diagarg {
     ($($value:expr),*) => { vec![ $(Rc::new($value)),* ] },
 }
 
-pub trait DiagnosticArgument: Any + ToString + 'static {
+pub trait DiagnosticArgument: std::any::Any + ToString + MaybeSync + 'static {
 }
 
 impl DiagnosticArgument for String {}
 
-impl DiagnosticArgument for Token {}
\ No newline at end of file
+impl DiagnosticArgument for Token {}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_include_stack() {
+        let sources = InMemorySourceProvider::new();
+        sources.insert("/src/a.include.as", "\ninclude \"b.include.as\";");
+        sources.insert("/src/b.include.as", "x = ;");
+        let cu = CompilationUnit::new(Some("/src/Main.as".into()), "var y;\n  include \"a.include.as\";".into());
+        ParserFacade(&cu, ParserOptions { source_provider: Rc::new(sources), ..default() }).parse_program();
+
+        let diagnostics = cu.nested_diagnostics();
+        assert_eq!(diagnostics[0].format_english(), [
+            "/src/b.include.as:1:5: Syntax error #1030: Expecting expression before semicolon.",
+            "    included from /src/a.include.as:2:1",
+            "    included from /src/Main.as:2:3",
+        ].join("\n"));
+        let root_location = diagnostics[0].location().root_location();
+        assert!(Rc::ptr_eq(&root_location.compilation_unit(), &cu));
+        assert_eq!(root_location.text(), "include \"a.include.as\";");
+    }
+}
+
@@ -45,7 +45,7 @@ lazy_static! {
         DiagnosticKind::UnrecognizedProxy.id() => "Unrecognized proxy: '{1}'.".into(),
         DiagnosticKind::EnumMembersMustBeConst.id() => "Enumeration members must be 'const'.".into(),
         DiagnosticKind::UnrecognizedMetadataSyntax.id() => "Unrecognized meta-data syntax.".into(),
-        DiagnosticKind::FailedToIncludeFile.id() => "Failed to include file.".into(),
+        DiagnosticKind::FailedToIncludeFile.id() => "Failed to include file. Tried {1}.".into(),
         DiagnosticKind::ParentSourceIsNotAFile.id() => "Parent source is not a file.".into(),
         DiagnosticKind::CircularIncludeDirective.id() => "Circular include directive.".into(),
         DiagnosticKind::MalformedDestructuring.id() => "Malformed destructuring.".into(),
@@ -71,8 +71,9 @@ lazy_static! {
         DiagnosticKind::ExpectingStatement.id() => "Expecting statement before {1}.".into(),
         DiagnosticKind::Unexpected.id() => "Unexpected {1}.".into(),
         DiagnosticKind::XmlClosingTagNameMustBeEquals.id() => "Closing tag name must be equals '{1}'.".into(),
-        DiagnosticKind::UnexpectedIncludeExtension.id() => "Unexpected include extension. Expected the file extension '.include.as'.".into(),
+        DiagnosticKind::UnexpectedIncludeExtension.id() => "Unexpected include extension. Expected the file extension {1}.".into(),
         DiagnosticKind::UnallowedExpression.id() => "Unallowed expression.".into(),
+        DiagnosticKind::UnexpectedIncludeExtensions.id() => "Unexpected include extension. Expected one of the file extensions {1}.".into(),
         // DiagnosticKind::K.id() => ".".into(),
     };
 }
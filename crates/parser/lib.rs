@@ -6,6 +6,8 @@ pub mod compilation_unit;
 pub mod diagnostics;
 pub mod operator;
 pub mod parser;
+pub mod printer;
+pub mod services;
 pub mod util;
 
 pub mod ns;
\ No newline at end of file
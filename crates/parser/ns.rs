@@ -5,4 +5,6 @@ pub use crate::compilation_unit::*;
 pub use crate::diagnostics::*;
 pub use crate::operator::*;
 pub use crate::parser::*;
+pub use crate::printer::*;
+pub use crate::services::*;
 pub use crate::util::*;
\ No newline at end of file
@@ -11,6 +11,9 @@ mod reserved_word;
 pub use reserved_word::*;
 mod parser;
 pub use parser::*;
+mod reparser;
+mod source_provider;
+pub use source_provider::*;
 mod css_parser;
 pub use css_parser::*;
 mod css_tokenizer;
@@ -20,4 +23,6 @@ pub use parser_error::*;
 mod token;
 pub use token::*;
 mod tokenizer;
-pub use tokenizer::*;
\ No newline at end of file
+pub use tokenizer::*;
+mod lexer;
+pub use lexer::*;
\ No newline at end of file
@@ -7,6 +7,8 @@ pub struct CssParser<'input> {
     token: (Token, Location),
     locations: Vec<Location>,
     expecting_token_error: bool,
+    lossless: bool,
+    first_offset: usize,
 }
 
 impl<'input> CssParser<'input> {
@@ -18,6 +20,8 @@ impl<'input> CssParser<'input> {
             token: (Token::Eof, Location::with_offset(&compilation_unit, 0)),
             locations: vec![],
             expecting_token_error: false,
+            lossless: options.lossless,
+            first_offset: options.byte_range.map_or(0, |range| range.0),
         }
     }
 
@@ -70,6 +74,21 @@ impl<'input> CssParser<'input> {
     fn next(&mut self) {
         self.previous_token = self.token.clone();
         self.token = self.tokenizer.scan();
+        self.record_token();
+    }
+
+    /// Contributes the current token to the compilation unit
+    /// in lossless mode.
+    fn record_token(&self) {
+        if !self.lossless {
+            return;
+        }
+        let mut tokens = self.compilation_unit().tokens.borrow_mut();
+        if tokens.last().is_some_and(|t| t.location.first_offset() == self.token.1.first_offset()) {
+            tokens.pop();
+        }
+        let trivia_offset = tokens.last().map_or(self.first_offset, |t| t.location.last_offset());
+        tokens.push(Rc::new(SyntaxToken::new(self.token.0.clone(), self.token.1.clone(), trivia_offset)));
     }
 
     fn peek(&self, token: Token) -> bool {
@@ -368,6 +387,7 @@ impl<'input> CssParser<'input> {
         let (byte_range, token) = self.tokenizer.scan_arguments();
         self.previous_token = self.token.clone();
         self.token = token;
+        self.record_token();
         self.next();
         Ok(CssParserFacade(self.compilation_unit(), ParserOptions {
             byte_range: Some(byte_range),
@@ -758,7 +778,9 @@ impl<'input> CssParserFacade<'input> {
     pub fn parse_document(&self) -> Rc<CssDocument> {
         let mut parser = self.create_parser();
         parser.next();
-        parser.parse_document()
+        let document = parser.parse_document();
+        NodeIdAssigner::assign_css(&document);
+        document
     }
 
     /// Parses either a string or return source text as is.
@@ -0,0 +1,309 @@
+use crate::ns::*;
+use std::collections::VecDeque;
+
+/// A lexer that drives a [`Tokenizer`] over an ActionScript 3 compilation unit
+/// without parsing it.
+///
+/// The lexer chooses the goal symbol of each token the same way the parser does,
+/// distinguishing regular expressions from division and scanning E4X literals,
+/// and yields comments, whitespace and line terminators as [`Token::Comment`],
+/// [`Token::Whitespace`] and [`Token::LineTerminator`]. Iteration ends before
+/// [`Token::Eof`]; characters that do not form tokens are not yielded.
+///
+/// Lexing contributes no comments and no diagnostics to the compilation unit.
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+/// let cu = CompilationUnit::new(None, "x = /a/g; // Comment".into());
+/// let tokens: Vec<Token> = Lexer::new(&cu, &default()).map(|(token, _)| token).collect();
+/// assert!(matches!(tokens[4], Token::RegExp { .. }));
+/// assert!(matches!(tokens.last(), Some(Token::Comment { multiline: false, .. })));
+/// ```
+pub struct Lexer<'input> {
+    tokenizer: Tokenizer<'input>,
+    pending: VecDeque<(Token, Location)>,
+    trivia_offset: usize,
+    goal: LexerGoal,
+    /// Whether an operand is expected, in which case `/` starts
+    /// a regular expression and `<` starts a XML literal.
+    operand: bool,
+    /// Whether the next token is a name following a `.`-like punctuator.
+    name_expected: bool,
+    /// Whether the next `(` opens the condition of a control statement.
+    control_condition: bool,
+    /// The numbers of frames enclosing function expressions
+    /// whose body has not been opened yet.
+    function_expressions: Vec<usize>,
+    frames: Vec<LexerFrame>,
+    previous: Option<Token>,
+    finished: bool,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum LexerGoal {
+    Div,
+    XmlTag,
+    XmlContent,
+}
+
+enum LexerFrame {
+    Paren { control: bool },
+    Brace { kind: BraceKind },
+    /// Type arguments opened by `.<` or `new <`.
+    TypeArguments,
+    XmlBrace { goal: LexerGoal },
+    XmlElement { closing: bool },
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum BraceKind {
+    Block,
+    ObjectLiteral,
+    /// The body of a function expression, which ends an operand
+    /// unlike the body of a function definition.
+    FunctionExpressionBody,
+}
+
+impl<'input> Lexer<'input> {
+    /// Constructs a lexer.
+    pub fn new(compilation_unit: &'input Rc<CompilationUnit>, options: &ParserOptions) -> Self {
+        let mut tokenizer = Tokenizer::new(compilation_unit, options);
+        tokenizer.detach();
+        let trivia_offset = tokenizer.characters().index();
+        Self {
+            tokenizer,
+            pending: VecDeque::new(),
+            trivia_offset,
+            goal: LexerGoal::Div,
+            operand: true,
+            name_expected: false,
+            control_condition: false,
+            function_expressions: vec![],
+            frames: vec![],
+            previous: None,
+            finished: false,
+        }
+    }
+
+    fn advance(&mut self) {
+        let (mut token, mut location) = match self.goal {
+            LexerGoal::Div => self.tokenizer.scan_ie_div(),
+            LexerGoal::XmlTag => self.tokenizer.scan_ie_xml_tag(),
+            LexerGoal::XmlContent => self.tokenizer.scan_ie_xml_content(),
+        };
+
+        if self.goal == LexerGoal::Div && self.operand && !self.name_expected {
+            if matches!(token, Token::Div | Token::DivideAssign) {
+                (token, location) = self.tokenizer.scan_regexp_literal(location.clone(), if token == Token::DivideAssign { "=".into() } else { "".into() });
+            } else if token == Token::Lt && self.previous != Some(Token::New) {
+                if let Some(markup) = self.tokenizer.scan_xml_markup(location.clone()) {
+                    (token, location) = markup;
+                } else {
+                    self.frames.push(LexerFrame::XmlElement { closing: false });
+                    self.goal = LexerGoal::XmlTag;
+                    self.push(token, location);
+                    return;
+                }
+            }
+        }
+
+        if token == Token::Eof {
+            self.push_trivia(location.first_offset());
+            self.finished = true;
+            return;
+        }
+
+        match self.goal {
+            LexerGoal::Div => self.update_div_state(&token),
+            LexerGoal::XmlTag => match token {
+                Token::BlockOpen => {
+                    self.frames.push(LexerFrame::XmlBrace { goal: LexerGoal::XmlTag });
+                    self.enter_div();
+                },
+                Token::Gt => {
+                    if matches!(self.frames.last(), Some(LexerFrame::XmlElement { closing: true })) {
+                        self.finish_xml_element();
+                    } else {
+                        self.goal = LexerGoal::XmlContent;
+                    }
+                },
+                Token::XmlSlashGt => self.finish_xml_element(),
+                _ => {},
+            },
+            LexerGoal::XmlContent => match token {
+                Token::BlockOpen => {
+                    self.frames.push(LexerFrame::XmlBrace { goal: LexerGoal::XmlContent });
+                    self.enter_div();
+                },
+                Token::Lt => {
+                    self.frames.push(LexerFrame::XmlElement { closing: false });
+                    self.goal = LexerGoal::XmlTag;
+                },
+                Token::XmlLtSlash => {
+                    if let Some(LexerFrame::XmlElement { closing }) = self.frames.last_mut() {
+                        *closing = true;
+                    }
+                    self.goal = LexerGoal::XmlTag;
+                },
+                _ => {},
+            },
+        }
+
+        self.push(token, location);
+    }
+
+    fn update_div_state(&mut self, token: &Token) {
+        let name = self.name_expected && token.is_identifier_name();
+        self.name_expected = matches!(token, Token::Dot | Token::OptionalChaining | Token::ColonColon | Token::Descendants | Token::Attribute);
+
+        let control_condition = self.control_condition;
+        self.control_condition = matches!(token, Token::If | Token::While | Token::For | Token::With)
+            || (control_condition && matches!(token, Token::Identifier(name) if name == "each"));
+
+        self.operand = match token {
+            _ if name => false,
+            Token::ParenOpen => {
+                self.frames.push(LexerFrame::Paren { control: control_condition });
+                true
+            },
+            Token::ParenClose => matches!(self.frames.pop_if(|frame| matches!(frame, LexerFrame::Paren { .. })), Some(LexerFrame::Paren { control: true })),
+            Token::Function => {
+                if self.opens_object_literal() {
+                    self.function_expressions.push(self.frames.len());
+                }
+                true
+            },
+            Token::BlockOpen => {
+                let kind = if self.function_expressions.last() == Some(&self.frames.len()) {
+                    self.function_expressions.pop();
+                    BraceKind::FunctionExpressionBody
+                } else if self.opens_object_literal() {
+                    BraceKind::ObjectLiteral
+                } else {
+                    BraceKind::Block
+                };
+                self.frames.push(LexerFrame::Brace { kind });
+                true
+            },
+            Token::BlockClose => match self.frames.pop_if(|frame| matches!(frame, LexerFrame::Brace { .. } | LexerFrame::XmlBrace { .. })) {
+                Some(LexerFrame::Brace { kind }) => kind == BraceKind::Block,
+                Some(LexerFrame::XmlBrace { goal }) => {
+                    self.goal = goal;
+                    false
+                },
+                _ => true,
+            },
+            Token::Lt if matches!(self.previous, Some(Token::Dot | Token::New)) => {
+                self.frames.push(LexerFrame::TypeArguments);
+                true
+            },
+            // The `>` characters of a token may close type arguments, as in `Vector.<Vector.<T>>`.
+            Token::Gt | Token::RightShift | Token::UnsignedRightShift => {
+                let count = match token {
+                    Token::Gt => 1,
+                    Token::RightShift => 2,
+                    _ => 3,
+                };
+                (0..count).filter(|_| self.frames.pop_if(|frame| matches!(frame, LexerFrame::TypeArguments)).is_some()).count() < count
+            },
+            Token::Identifier(_) | Token::String(_) | Token::Number(..) | Token::RegExp { .. } | Token::XmlMarkup(_) |
+            Token::This | Token::Super | Token::Null | Token::True | Token::False |
+            Token::SquareClose | Token::Increment | Token::Decrement => false,
+            _ => true,
+        };
+        self.previous = Some(token.clone());
+    }
+
+    /// Determines whether a `{` in operand position is an object initializer
+    /// rather than a block.
+    fn opens_object_literal(&self) -> bool {
+        if !self.operand {
+            return false;
+        }
+        match self.previous {
+            // Start of input or of a XML embedded expression
+            None => matches!(self.frames.last(), Some(LexerFrame::XmlBrace { .. })),
+            Some(Token::Semicolon | Token::BlockOpen | Token::BlockClose | Token::ParenClose |
+                Token::Else | Token::Do | Token::Try | Token::Finally | Token::Package | Token::Void | Token::Times) => false,
+            Some(Token::Colon) => matches!(self.frames.last(), Some(LexerFrame::Brace { kind: BraceKind::ObjectLiteral })),
+            _ => true,
+        }
+    }
+
+    fn enter_div(&mut self) {
+        self.goal = LexerGoal::Div;
+        self.operand = true;
+        self.name_expected = false;
+        self.control_condition = false;
+        self.previous = None;
+    }
+
+    fn finish_xml_element(&mut self) {
+        self.frames.pop();
+        if matches!(self.frames.last(), Some(LexerFrame::XmlElement { .. })) {
+            self.goal = LexerGoal::XmlContent;
+        } else {
+            self.goal = LexerGoal::Div;
+            self.operand = false;
+            self.name_expected = false;
+            self.previous = None;
+        }
+    }
+
+    fn push(&mut self, token: Token, location: Location) {
+        self.push_trivia(location.first_offset());
+        self.trivia_offset = location.last_offset();
+        self.pending.push_back((token, location));
+    }
+
+    fn push_trivia(&mut self, last_offset: usize) {
+        let compilation_unit = self.tokenizer.compilation_unit().clone();
+        let comments = self.tokenizer.take_detached_comments();
+        for trivia in Trivia::scan(&compilation_unit, self.trivia_offset, last_offset, comments) {
+            let token = match &trivia {
+                Trivia::Whitespace(_) => Token::Whitespace,
+                Trivia::LineTerminator(_) => Token::LineTerminator,
+                Trivia::Comment(comment) => Token::Comment {
+                    multiline: comment.multiline(),
+                    content: comment.content(),
+                },
+                Trivia::Skipped(_) => continue,
+            };
+            self.pending.push_back((token, trivia.location()));
+        }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = (Token, Location);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.finished {
+            self.advance();
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_lexer() {
+        let text = "/** Doc */\r\nif (x) /a/.test(y);\nz = a / b / c;\nv = Vector.<T>([]);\nf(function() {} / 2, a.<Vector.<T>> / 2);\nfunction g() {}\n/r/.test(s);\nw = <a k={ {o: 1} }>t{ /r/ }<b/></a> / 2;";
+        let cu = CompilationUnit::new(None, text.into());
+        let tokens: Vec<(Token, Location)> = Lexer::new(&cu, &default()).collect();
+        assert_eq!(tokens.iter().map(|(_, location)| location.text()).collect::<String>(), text);
+        assert!(cu.comments().is_empty() && !cu.invalidated());
+
+        let tokens: Vec<Token> = tokens.into_iter().map(|(token, _)| token)
+            .filter(|token| !matches!(token, Token::Whitespace | Token::LineTerminator)).collect();
+        assert_eq!(tokens[0], Token::Comment { multiline: true, content: "* Doc ".into() });
+        assert_eq!(tokens.iter().filter(|token| matches!(token, Token::RegExp { .. })).count(), 3);
+        assert_eq!(tokens.iter().filter(|token| matches!(token, Token::Div)).count(), 5);
+        assert!(tokens.contains(&Token::XmlText("t".into())));
+        assert!(tokens.contains(&Token::XmlSlashGt));
+        assert_eq!(tokens[tokens.len() - 3], Token::Div);
+    }
+}
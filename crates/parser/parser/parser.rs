@@ -10,6 +10,14 @@ pub struct Parser<'input> {
     ignore_xml_whitespace: bool,
     documentable_metadata: Vec<String>,
     expecting_token_error: bool,
+    lossless: bool,
+    source_provider: Rc<dyn SourceProvider>,
+    include_paths: Vec<String>,
+    include_extensions: Vec<String>,
+    legacy_includes: bool,
+    declaration_only: bool,
+    first_offset: usize,
+    last_offset: usize,
 }
 
 impl<'input> Parser<'input> {
@@ -24,6 +32,14 @@ impl<'input> Parser<'input> {
             ignore_xml_whitespace: options.ignore_xml_whitespace,
             documentable_metadata: options.documentable_metadata.clone(),
             expecting_token_error: false,
+            lossless: options.lossless,
+            source_provider: options.source_provider.clone(),
+            include_paths: options.include_paths.clone(),
+            include_extensions: options.include_extensions.clone(),
+            legacy_includes: options.legacy_includes,
+            declaration_only: options.declaration_only,
+            first_offset: options.byte_range.map_or(0, |range| range.0),
+            last_offset: options.byte_range.map_or(compilation_unit.text().len(), |range| range.1),
         }
     }
 
@@ -31,10 +47,24 @@ impl<'input> Parser<'input> {
         ParserOptions {
             ignore_xml_whitespace: self.ignore_xml_whitespace,
             documentable_metadata: self.documentable_metadata.clone(),
+            source_provider: self.source_provider.clone(),
+            include_paths: self.include_paths.clone(),
+            include_extensions: self.include_extensions.clone(),
+            legacy_includes: self.legacy_includes,
+            declaration_only: self.declaration_only,
             ..default()
         }
     }
 
+    /// Returns the file extensions allowed in include directives.
+    fn include_extensions(&self) -> Vec<String> {
+        let mut extensions = self.include_extensions.clone();
+        if self.legacy_includes && !extensions.iter().any(|extension| extension == ".as") {
+            extensions.push(".as".into());
+        }
+        extensions
+    }
+
     fn compilation_unit(&self) -> &Rc<CompilationUnit> {
         self.tokenizer.compilation_unit()
     }
@@ -88,16 +118,34 @@ impl<'input> Parser<'input> {
     fn next(&mut self) {
         self.previous_token = self.token.clone();
         self.token = self.tokenizer.scan_ie_div();
+        self.record_token();
     }
 
     fn next_ie_xml_tag(&mut self) {
         self.previous_token = self.token.clone();
         self.token = self.tokenizer.scan_ie_xml_tag();
+        self.record_token();
     }
 
     fn next_ie_xml_content(&mut self) {
         self.previous_token = self.token.clone();
         self.token = self.tokenizer.scan_ie_xml_content();
+        self.record_token();
+    }
+
+    /// Contributes the current token to the compilation unit
+    /// in lossless mode.
+    fn record_token(&self) {
+        if !self.lossless {
+            return;
+        }
+        let mut tokens = self.compilation_unit().tokens.borrow_mut();
+        // A token scanned again with another goal symbol replaces the previous one.
+        if tokens.last().is_some_and(|t| t.location.first_offset() == self.token.1.first_offset()) {
+            tokens.pop();
+        }
+        let trivia_offset = tokens.last().map_or(self.first_offset, |t| t.location.last_offset());
+        tokens.push(Rc::new(SyntaxToken::new(self.token.0.clone(), self.token.1.clone(), trivia_offset)));
     }
 
     fn peek(&self, token: Token) -> bool {
@@ -896,6 +944,7 @@ impl<'input> Parser<'input> {
         } else if self.peek(Token::Div) || self.peek(Token::DivideAssign) {
             self.mark_location();
             self.token = self.tokenizer.scan_regexp_literal(self.token.1.clone(), if self.peek(Token::DivideAssign) { "=".into() } else { "".into() });
+            self.record_token();
             let Token::RegExp { ref body, ref flags } = self.token.0.clone() else {
                 panic!();
             };
@@ -916,6 +965,7 @@ impl<'input> Parser<'input> {
         } else if self.peek(Token::Lt) {
             if let Some(token) = self.tokenizer.scan_xml_markup(self.token_location()) {
                 self.token = token;
+                self.record_token();
             }
             let start = self.token_location();
             if let Token::XmlMarkup(content) = &self.token.0.clone() {
@@ -1116,7 +1166,7 @@ impl<'input> Parser<'input> {
 
         // Body
         let body = if self.peek(Token::BlockOpen) {
-            Some(FunctionBody::Block(Rc::new(self.parse_block(block_context))))
+            Some(self.skip_function_body(&block_context).unwrap_or_else(|| FunctionBody::Block(Rc::new(self.parse_block(block_context)))))
         } else if !(self.offending_token_is_inline_or_higher_indented() || self.peek(Token::ParenOpen)) {
             None
         } else {
@@ -1149,6 +1199,55 @@ impl<'input> Parser<'input> {
         })
     }
 
+    /// In declaration-only mode, skips a function body by matching its
+    /// braces, returning a placeholder for it. Bodies that use `await` or
+    /// `yield`, which affect their function, are not skipped, and neither
+    /// are unterminated bodies.
+    ///
+    /// Neither are bodies where a `/` or `<` follows a `}`, `)` or `>`,
+    /// since the lexer may choose the goal symbol differently than the
+    /// parser there, misplacing the closing brace.
+    fn skip_function_body(&mut self, block_context: &ParserDirectiveContext) -> Option<FunctionBody> {
+        if !self.declaration_only || self.lossless {
+            return None;
+        }
+        let first_offset = self.token.1.first_offset();
+        let mut depth = 0usize;
+        let mut block_close = None;
+        let mut previous = None;
+        for (token, location) in Lexer::new(self.compilation_unit(), &ParserOptions {
+            byte_range: Some((first_offset, self.last_offset)),
+            ..default()
+        }) {
+            match token {
+                Token::BlockOpen => depth += 1,
+                Token::BlockClose => {
+                    depth -= 1;
+                    if depth == 0 {
+                        block_close = Some(location);
+                        break;
+                    }
+                },
+                Token::Await | Token::Yield => return None,
+                Token::Div | Token::DivideAssign | Token::RegExp { .. } | Token::Lt | Token::XmlMarkup(_)
+                    if matches!(previous, Some(Token::BlockClose | Token::ParenClose | Token::Gt | Token::RightShift | Token::UnsignedRightShift)) => return None,
+                Token::Whitespace | Token::LineTerminator | Token::Comment { .. } => continue,
+                _ => {},
+            }
+            previous = Some(token);
+        }
+        let block_close = block_close?;
+        self.tokenizer.skip_to(block_close.last_offset());
+        self.token = (Token::BlockClose, block_close.clone());
+        self.next();
+        let location = Location::with_offsets(self.compilation_unit(), first_offset, block_close.last_offset());
+        Some(FunctionBody::Lazy(Rc::new(LazyFunctionBody {
+            source_hash: xxhash_rust::xxh3::xxh3_64(location.text().as_bytes()),
+            location,
+            constructor: matches!(block_context, ParserDirectiveContext::ConstructorBlock { .. }),
+        })))
+    }
+
     fn parse_parameter(&mut self) -> Rc<Parameter> {
         self.mark_location();
         let rest = self.consume(Token::Ellipsis);
@@ -1471,6 +1570,7 @@ impl<'input> Parser<'input> {
         } else if self.peek(Token::Div) || self.peek(Token::DivideAssign) {
             self.mark_location();
             self.token = self.tokenizer.scan_regexp_literal(self.token.1.clone(), if self.peek(Token::DivideAssign) { "=".into() } else { "".into() });
+            self.record_token();
             let Token::RegExp { ref body, ref flags } = self.token.0.clone() else {
                 panic!();
             };
@@ -1491,6 +1591,7 @@ impl<'input> Parser<'input> {
         } else if self.peek(Token::Lt) {
             if let Some(token) = self.tokenizer.scan_xml_markup(self.token_location()) {
                 self.token = token;
+                self.record_token();
             }
             let start = self.token_location();
             if let Token::XmlMarkup(content) = &self.token.0.clone() {
@@ -2454,6 +2555,7 @@ impl<'input> Parser<'input> {
 
         // Parse CONFIG::VAR_NAME
         if let Some(result) = self.parse_opt_config(&exp, asdoc.clone(), context.clone()) {
+            self.pop_location();
             return result;
         }
 
@@ -2469,9 +2571,16 @@ impl<'input> Parser<'input> {
             match exp.to_configuration_identifier(self) {
                 Ok(Some((q, constant_name, metadata))) => {
                     self.push_location(&exp.location());
+                    let asdoc = match (self.parse_asdoc(), asdoc) {
+                        (Some(asdoc), Some(superseded)) => {
+                            self.restore_asdoc_comment(&superseded);
+                            Some(asdoc)
+                        },
+                        (asdoc, superseded) => asdoc.or(superseded),
+                    };
                     let mut context = AnnotatableContext {
                         start_location: exp.location(),
-                        asdoc: self.parse_asdoc().or(asdoc),
+                        asdoc,
                         attributes: metadata,
                         context,
                         directive_context_keyword: None,
@@ -2528,6 +2637,26 @@ impl<'input> Parser<'input> {
         }
     }
 
+    /// Parses a block that spans the whole byte range of the parser, in the
+    /// same way it is parsed as part of a larger program. A function body
+    /// is parsed under a new activation, whose use of `await` and `yield`
+    /// is returned along with the block.
+    ///
+    /// Returns `None` if the block is not terminated by the last
+    /// character of the byte range.
+    pub(crate) fn parse_isolated_block(&mut self, context: ParserDirectiveContext, function_body: bool) -> Option<(Block, bool, bool)> {
+        self.next();
+        if function_body {
+            self.activations.push(ParserActivation::new());
+        }
+        let block = self.parse_block(context);
+        let activation = if function_body { self.activations.pop().unwrap() } else { ParserActivation::new() };
+        if !(self.previous_token.0 == Token::BlockClose && self.peek(Token::Eof) && block.location.last_offset() == self.token.1.first_offset()) {
+            return None;
+        }
+        Some((block, activation.uses_await, activation.uses_yield))
+    }
+
     fn parse_if_statement(&mut self, context: ParserDirectiveContext) -> (Rc<Directive>, bool) {
         let context = context.override_control_context(true, ParserControlFlowContext {
             breakable: true,
@@ -3255,6 +3384,7 @@ impl<'input> Parser<'input> {
 
                         // Parse CONFIG::VAR_NAME
                         if let Some(result) = self.parse_opt_config(&first_attr_expr, asdoc.clone(), context.clone()) {
+                            self.pop_location();
                             return result;
                         }
 
@@ -3686,40 +3816,41 @@ impl<'input> Parser<'input> {
 
         let nested_compilation_unit: Rc<CompilationUnit>;
 
-        // Select origin file path
-        let origin_file_path = if let Some(file_path) = self.tokenizer.compilation_unit().file_path.clone() {
-            Some(file_path)
-        } else {
-            std::env::current_dir().ok().map(|d| d.to_string_lossy().into_owned())
-        };
-
         // Resolve source
-        if let Some(origin_file_path) = origin_file_path {
-            let sub_flex_file_path = hydroperfox_filepaths::FlexPath::from_n_native([origin_file_path.as_ref(), "..", source.as_ref()]);
-            let sub_file_path = sub_flex_file_path.to_string_with_flex_separator();
+        let including_file_path = self.tokenizer.compilation_unit().file_path.clone();
+        let mut candidates: Vec<String> = self.source_provider.resolve(including_file_path.as_deref(), &source).into_iter().collect();
+        for include_path in &self.include_paths {
+            let candidate = hydroperfox_filepaths::FlexPath::from_n_native([include_path.as_ref(), source.as_ref()]).to_string_with_flex_separator();
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        let include_extensions = self.include_extensions();
 
-            if !sub_flex_file_path.has_extension(".include.as") {
-                self.add_syntax_error(&source_path_location.clone(), DiagnosticKind::UnexpectedIncludeExtension, vec![]);
+        if !hydroperfox_filepaths::FlexPath::new_native(&source).has_extensions(include_extensions.iter().map(|extension| extension.as_str())) {
+            let kind = if include_extensions.len() == 1 { DiagnosticKind::UnexpectedIncludeExtension } else { DiagnosticKind::UnexpectedIncludeExtensions };
+            let include_extensions = include_extensions.iter().map(|extension| format!("'{extension}'")).collect::<Vec<_>>().join(", ");
+            self.add_syntax_error(&source_path_location.clone(), kind, diagarg![include_extensions]);
 
-                // Use a placeholder compilation unit
-                nested_compilation_unit = CompilationUnit::new(None, "".into());
-            } else if self.tokenizer.compilation_unit().include_directive_is_circular(&sub_file_path) {
+            // Use a placeholder compilation unit
+            nested_compilation_unit = CompilationUnit::new(None, "".into());
+        } else if candidates.is_empty() {
+            self.add_syntax_error(&source_path_location.clone(), DiagnosticKind::ParentSourceIsNotAFile, vec![]);
+
+            // Use a placeholder compilation unit
+            nested_compilation_unit = CompilationUnit::new(None, "".into());
+        } else if let Some((sub_file_path, content)) = candidates.iter().find_map(|candidate| Some((candidate.clone(), self.source_provider.load(candidate)?))) {
+            if self.tokenizer.compilation_unit().include_directive_is_circular(&sub_file_path, self.source_provider.as_ref()) {
                 self.add_syntax_error(&source_path_location.clone(), DiagnosticKind::CircularIncludeDirective, vec![]);
 
                 // Use a placeholder compilation unit
                 nested_compilation_unit = CompilationUnit::new(None, "".into());
             } else {
-                if let Ok(content) = std::fs::read_to_string(&sub_file_path) {
-                    nested_compilation_unit = CompilationUnit::new(Some(sub_file_path.clone()), content);
-                } else {
-                    self.add_syntax_error(&source_path_location.clone(), DiagnosticKind::FailedToIncludeFile, vec![]);
-
-                    // Use a placeholder compilation unit
-                    nested_compilation_unit = CompilationUnit::new(None, "".into());
-                }
+                nested_compilation_unit = CompilationUnit::new(Some(sub_file_path), content);
             }
         } else {
-            self.add_syntax_error(&source_path_location.clone(), DiagnosticKind::ParentSourceIsNotAFile, vec![]);
+            let candidates = candidates.iter().map(|candidate| format!("'{candidate}'")).collect::<Vec<_>>().join(", ");
+            self.add_syntax_error(&source_path_location.clone(), DiagnosticKind::FailedToIncludeFile, diagarg![candidates]);
 
             // Use a placeholder compilation unit
             nested_compilation_unit = CompilationUnit::new(None, "".into());
@@ -3732,15 +3863,21 @@ impl<'input> Parser<'input> {
         self.tokenizer.compilation_unit().add_nested_compilation_unit(nested_compilation_unit.clone());
 
         // Parse directives from replacement source
-        let (nested_packages, nested_directives) = parse_include_directive_source(nested_compilation_unit.clone(), context);
+        let (nested_packages, nested_directives) = parse_include_directive_source(nested_compilation_unit.clone(), context, &ParserOptions {
+            lossless: self.lossless,
+            ..self.options()
+        });
 
         // Delegate sub compilation unit errors to super compilation unit
         if nested_compilation_unit.invalidated() {
             self.tokenizer.compilation_unit().invalidated.set(true);
         }
 
+        let location = self.pop_location();
+        nested_compilation_unit.set_include_location(Some(location.clone()));
+
         let node = Rc::new(Directive::IncludeDirective(IncludeDirective {
-            location: self.pop_location(),
+            location,
             source,
             nested_packages,
             nested_directives,
@@ -4383,7 +4520,9 @@ impl<'input> Parser<'input> {
                         name: metadata.name.clone(),
                         entries: metadata.entries.clone(),
                     })));
-                    context.asdoc = metadata.asdoc.clone();
+                    if let Some(superseded) = context.asdoc.replace(metadata.asdoc.clone().unwrap()) {
+                        self.restore_asdoc_comment(&superseded);
+                    }
                 } else {
                     new_attributes.push(attr.clone());
                 }
@@ -4394,6 +4533,16 @@ impl<'input> Parser<'input> {
         context.attributes = new_attributes;
     }
 
+    /// Returns an ASDoc comment that is not attached to the tree
+    /// to the comments of the compilation unit.
+    fn restore_asdoc_comment(&self, asdoc: &Asdoc) {
+        let location = asdoc.location.clone();
+        let content = self.compilation_unit().text()[(location.first_offset() + 2)..(location.last_offset() - 2)].to_owned();
+        let mut comments = self.compilation_unit().comments_mut();
+        let i = comments.partition_point(|c| c.location().first_offset() < location.first_offset());
+        comments.insert(i, Rc::new(Comment::new(true, content, location)));
+    }
+
     pub fn parse_package_definition(&mut self) -> Rc<PackageDefinition> {
         self.mark_location();
         let asdoc = self.parse_asdoc();
@@ -5162,10 +5311,8 @@ impl<'input> Parser<'input> {
     }
 }
 
-fn parse_include_directive_source(nested_compilation_unit: Rc<CompilationUnit>, context: ParserDirectiveContext) -> (Vec<Rc<PackageDefinition>>, Vec<Rc<Directive>>) {
-    let mut parser = Parser::new(&nested_compilation_unit, &ParserOptions {
-        ..default()
-    });
+fn parse_include_directive_source(nested_compilation_unit: Rc<CompilationUnit>, context: ParserDirectiveContext, options: &ParserOptions) -> (Vec<Rc<PackageDefinition>>, Vec<Rc<Directive>>) {
+    let mut parser = Parser::new(&nested_compilation_unit, options);
     parser.next();
     let mut packages = vec![];
     if matches!(context, ParserDirectiveContext::TopLevel) {
@@ -5309,6 +5456,7 @@ struct PlainMxmlAttribute {
 /// A simplified interface for executing the parser.
 pub struct ParserFacade<'input>(pub &'input Rc<CompilationUnit>, pub ParserOptions);
 
+#[derive(Clone)]
 pub struct ParserOptions {
     /// For MXML, indicates whether to ignore XML whitespace chunks when at
     /// least one element appears. Default: true.
@@ -5319,6 +5467,34 @@ pub struct ParserOptions {
     /// Indicates the set of meta-data that are documentable through ASDoc comments.
     /// Defaults to \[`Event`, `SkinState`\].
     pub documentable_metadata: Vec<String>,
+    /// Indicates whether to record every token, along with its leading
+    /// whitespace and comments, into the compilation unit, allowing to
+    /// reproduce the source text exactly. Default: false.
+    ///
+    /// The tokens of a node are obtained through
+    /// `CompilationUnit::tokens_within(&node.location())`.
+    pub lossless: bool,
+    /// Resolves and loads the sources of include directives.
+    /// Default: a `FileSystemSourceProvider`.
+    pub source_provider: Rc<dyn SourceProvider>,
+    /// Indicates directories where include directives search for sources
+    /// not found relative to the including source, in order. Default: none.
+    pub include_paths: Vec<String>,
+    /// Indicates the file extensions allowed in include directives.
+    /// Defaults to \[`.include.as`\].
+    pub include_extensions: Vec<String>,
+    /// Indicates whether include directives may refer to any `.as`
+    /// source, as in legacy Flex code. Default: false.
+    pub legacy_includes: bool,
+    /// Indicates whether to skip the directives of function bodies,
+    /// leaving `FunctionBody::Lazy` placeholders, for fast scanning of
+    /// declarations. Ignored in lossless mode. Default: false.
+    ///
+    /// The directives of a skipped body are parsed on demand through
+    /// `ParserFacade::parse_lazy_function_body`. The comments and syntax
+    /// errors of a skipped body are deferred until then, thus the compilation
+    /// unit may be valid even if a body contains errors.
+    pub declaration_only: bool,
 }
 
 impl Default for ParserOptions {
@@ -5327,6 +5503,12 @@ impl Default for ParserOptions {
             ignore_xml_whitespace: true,
             byte_range: None,
             documentable_metadata: vec!["Event".into(), "SkinState".into()],
+            lossless: false,
+            source_provider: Rc::new(FileSystemSourceProvider),
+            include_paths: vec![],
+            include_extensions: vec![".include.as".into()],
+            legacy_includes: false,
+            declaration_only: false,
         }
     }
 }
@@ -5340,7 +5522,9 @@ impl<'input> ParserFacade<'input> {
     pub fn parse_program(&self) -> Rc<Program> {
         let mut parser = self.create_parser();
         parser.next();
-        parser.parse_program()
+        let program = parser.parse_program();
+        NodeIdAssigner.visit_program(&program);
+        program
     }
 
     /// Parses `ListExpression^allowIn` and expects end-of-file.
@@ -5351,6 +5535,7 @@ impl<'input> ParserFacade<'input> {
             ..default()
         });
         parser.expect_eof();
+        NodeIdAssigner.visit_expression(&exp);
         exp
     }
 
@@ -5369,6 +5554,7 @@ impl<'input> ParserFacade<'input> {
         parser.next();
         let exp = parser.parse_type_expression();
         parser.expect_eof();
+        NodeIdAssigner.visit_expression(&exp);
         exp
     }
 
@@ -5376,27 +5562,158 @@ impl<'input> ParserFacade<'input> {
     pub fn parse_directives(&self, context: ParserDirectiveContext) -> Vec<Rc<Directive>> {
         let mut parser = self.create_parser();
         parser.next();
-        parser.parse_directives(context)
+        let directives = parser.parse_directives(context);
+        for directive in &directives {
+            NodeIdAssigner.visit_directive(directive);
+        }
+        directives
+    }
+
+    /// Parses the directives of a function body skipped by declaration-only
+    /// parsing, where the compilation unit is the one the body belongs to.
+    /// The comments and diagnostics of the body are contributed to
+    /// the compilation unit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    ///
+    /// let cu = CompilationUnit::new(None, "function f() { return 1; }".into());
+    /// let program = ParserFacade(&cu, ParserOptions { declaration_only: true, ..default() }).parse_program();
+    /// let Directive::FunctionDefinition(defn) = program.directives[0].as_ref() else { panic!() };
+    /// let Some(FunctionBody::Lazy(body)) = &defn.common.body else { panic!() };
+    /// let block = ParserFacade(&cu, default()).parse_lazy_function_body(body);
+    /// assert_eq!(block.directives.len(), 1);
+    /// ```
+    pub fn parse_lazy_function_body(&self, body: &LazyFunctionBody) -> Rc<Block> {
+        // Set aside the comments from the body onwards, so that the parser
+        // finds the comments of the body as the last ones.
+        let following_comments = {
+            let mut comments = self.0.comments_mut();
+            let i = comments.partition_point(|comment| comment.location().first_offset() < body.location.first_offset());
+            comments.split_off(i)
+        };
+        let mut parser = Parser::new(self.0, &ParserOptions {
+            byte_range: Some((body.location.first_offset(), body.location.last_offset())),
+            declaration_only: false,
+            ..self.1.clone()
+        });
+        parser.next();
+        parser.activations.push(ParserActivation::new());
+        let block = Rc::new(parser.parse_block(if body.constructor {
+            ParserDirectiveContext::ConstructorBlock { super_statement_found: Rc::new(Cell::new(false)) }
+        } else {
+            ParserDirectiveContext::Default
+        }));
+        let mut comments = self.0.comments_mut();
+        comments.extend(following_comments);
+        comments.sort_by_key(|comment| comment.location().first_offset());
+        comments.dedup_by_key(|comment| comment.location().first_offset());
+        drop(comments);
+        NodeIdAssigner.visit_block(&block);
+        block
     }
 
     /// Parses `Mxml` until end-of-file.
     pub fn parse_mxml(&self) -> Rc<Mxml> {
         let mut parser = self.create_parser();
         parser.next_ie_xml_content();
-        parser.parse_mxml()
+        let mxml = parser.parse_mxml();
+        NodeIdAssigner::assign_mxml(&mxml);
+        mxml
     }
 
     /// Parses a sequence of zero or meta data and an ASDoc comment.
     pub fn parse_metadata(&self) -> (Vec<Attribute>, Option<Rc<Asdoc>>) {
         let mut parser = self.create_parser();
         parser.next();
-        parser.parse_metadata()
+        let (attributes, asdoc) = parser.parse_metadata();
+        for attribute in &attributes {
+            NodeIdAssigner.visit_attribute(attribute);
+        }
+        (attributes, asdoc)
     }
 
     /// Parses the content inside the square brackets (`[ ... ]`) of a meta data.
     pub fn parse_metadata_content(&self) -> Rc<Metadata> {
         let mut parser = self.create_parser();
         parser.next();
-        parser.parse_metadata_content()
+        let metadata = parser.parse_metadata_content();
+        NodeIdAssigner.visit_metadata(&metadata);
+        metadata
+    }
+
+    /// Parses `Program` from each compilation unit, returning the programs
+    /// in the same order. When the `sync` feature is enabled, the compilation
+    /// units are parsed in parallel over the available threads.
+    pub fn parse_many(compilation_units: &[Rc<CompilationUnit>], options: &ParserOptions) -> Vec<Rc<Program>> {
+        #[cfg(feature = "sync")]
+        {
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            let chunk_size = compilation_units.len().div_ceil(threads).max(1);
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = compilation_units.chunks(chunk_size).map(|chunk| scope.spawn(move || {
+                    chunk.iter().map(|cu| ParserFacade(cu, options.clone()).parse_program()).collect::<Vec<_>>()
+                })).collect();
+                handles.into_iter().flat_map(|handle| handle.join().unwrap_or_else(|error| std::panic::resume_unwind(error))).collect()
+            })
+        }
+        #[cfg(not(feature = "sync"))]
+        compilation_units.iter().map(|cu| ParserFacade(cu, options.clone()).parse_program()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_declaration_only() {
+        let text = "package p {\n    public class C {\n        /** Constructs. */\n        public function C() { super(); var o = {a: \"}\"}; }\n        /** Matches. */\n        public function m(s: String): Boolean { return /}/.test(s) /* inner */ && <a>{ {b: '{'} }</a> != null; }\n        function g() { yield 1; }\n        /** Last. */\n        public var v: int; // after\n    }\n}";
+        let summarize = |options: ParserOptions| {
+            let cu = CompilationUnit::new(None, text.into());
+            let program = ParserFacade(&cu, options).parse_program();
+            assert!(!cu.invalidated());
+            let summary: Vec<(String, String, Option<String>)> = OutlineSymbol::outline(&program)[0].children[0].children.iter()
+                .map(|symbol| (symbol.name.clone(), symbol.signature.clone(), symbol.asdoc.as_ref().and_then(|asdoc| asdoc.main_body.as_ref().map(|(body, _)| body.clone()))))
+                .collect();
+            (cu, program, summary)
+        };
+        let (_, _, summary) = summarize(default());
+        let (cu, program, lazy_summary) = summarize(ParserOptions { declaration_only: true, ..default() });
+        assert_eq!(lazy_summary, summary);
+        assert_eq!(lazy_summary[3].2.as_deref(), Some("Last."));
+
+        let Directive::ClassDefinition(class) = program.packages[0].block.directives[0].as_ref() else { panic!() };
+        let bodies: Vec<&FunctionBody> = class.block.directives.iter().filter_map(|directive| match directive.as_ref() {
+            Directive::FunctionDefinition(defn) => defn.common.body.as_ref(),
+            _ => None,
+        }).collect();
+        assert!(matches!(bodies[2], FunctionBody::Block(_)));
+        let FunctionBody::Lazy(body) = bodies[1] else { panic!() };
+        assert!(body.location.text().ends_with("!= null; }"));
+        let block = ParserFacade(&cu, default()).parse_lazy_function_body(body);
+        assert!(matches!(block.directives[0].as_ref(), Directive::ReturnStatement(_)));
+        assert!(!cu.invalidated());
+        let comments: Vec<String> = cu.comments().iter().map(|comment| comment.content()).collect();
+        assert_eq!(comments, [" inner ", " after"]);
+
+        // Bodies where the goal of `/` depends on the preceding operand.
+        let names = |text: &str, options: ParserOptions| -> Vec<String> {
+            let cu = CompilationUnit::new(None, text.into());
+            let program = ParserFacade(&cu, options).parse_program();
+            OutlineSymbol::outline(&program)[0].children[0].children.iter().map(|symbol| symbol.name.clone()).collect()
+        };
+        for body in ["var v = function(){} / 2;", "var v = a.<int> / 2;", "var v = function() {\n}\n/ 2;", "var v = f(x) / 2 / (y) / 3;"] {
+            let text = format!("package {{ public class C {{ function f() {{ {body} }} function g(): int {{ return 1 / 3; }} public var after: int; }} }}");
+            assert_eq!(names(&text, ParserOptions { declaration_only: true, ..default() }), names(&text, default()), "{body}");
+            assert_eq!(names(&text, default()), ["f", "g", "after"]);
+        }
+
+        // Skipped bodies with different text are structurally distinct.
+        let parse = |text: &str| ParserFacade(&CompilationUnit::new(None, text.into()), ParserOptions { declaration_only: true, ..default() }).parse_program();
+        assert!(!parse("function f() { a() }").directives[0].structural_eq(&parse("function f() { b() }").directives[0]));
+        assert!(parse("function f() { a() }").directives[0].structural_eq(&parse("\nfunction f() { a() }").directives[0]));
     }
 }
@@ -0,0 +1,310 @@
+use crate::ns::*;
+
+impl<'input> ParserFacade<'input> {
+    /// Parses `Program` from the result of applying `edits` to the compilation unit,
+    /// given the `Program` previously parsed from it with the same options.
+    /// Returns the new compilation unit along with the new program.
+    ///
+    /// When all edits fall inside a single function body or class block, only
+    /// that block is parsed again, and the rest of the previous program is reused
+    /// with its locations moved into the new compilation unit. Otherwise, the
+    /// whole program is parsed again. In both cases the resulting tree and
+    /// diagnostics are the same as those of a full parse.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    ///
+    /// let cu = CompilationUnit::new(None, "function f() { x = 1 }".into());
+    /// let program = ParserFacade(&cu, default()).parse_program();
+    /// let (cu, program) = ParserFacade(&cu, default()).reparse_program(&program, &[TextEdit::new(19..20, "y")]);
+    /// assert_eq!(cu.text(), "function f() { x = y }");
+    /// ```
+    pub fn reparse_program(&self, program: &Rc<Program>, edits: &[TextEdit]) -> (Rc<CompilationUnit>, Rc<Program>) {
+        let (compilation_unit, _) = self.0.apply_edits(edits);
+        if let Some(program) = self.reparse_block(program, edits, &compilation_unit) {
+            return (compilation_unit, program);
+        }
+        // Discard any results of the attempt.
+        let (compilation_unit, _) = self.0.apply_edits(edits);
+        let program = ParserFacade(&compilation_unit, self.1.clone()).parse_program();
+        (compilation_unit, program)
+    }
+
+    /// Attempts to parse only the function body or class block
+    /// that encloses the edits.
+    fn reparse_block(&self, program: &Rc<Program>, edits: &[TextEdit], compilation_unit: &Rc<CompilationUnit>) -> Option<Rc<Program>> {
+        let old_compilation_unit = self.0;
+        let edits = TextEdit::sorted(edits);
+        if edits.is_empty() || self.1.lossless || self.1.byte_range.is_some() {
+            return None;
+        }
+        let mut finder = ReparseTargetFinder {
+            compilation_unit: old_compilation_unit.clone(),
+            range: (edits.first().unwrap().range.start, edits.last().unwrap().range.end),
+            constructor: false,
+            function_depth: 0,
+            target: None,
+        };
+        finder.visit_program(program);
+        let target = finder.target?;
+        let delta: isize = edits.iter().map(|edit| edit.new_text.len() as isize - edit.range.len() as isize).sum();
+
+        let old_first_offset = target.location.first_offset();
+        let old_last_offset = target.location.last_offset();
+        let first_offset = old_first_offset;
+        let last_offset = old_last_offset.checked_add_signed(delta)?;
+        let relocate = |offset: usize| if offset <= old_first_offset { offset } else { offset.wrapping_add_signed(delta) };
+
+        // Contribute the comments and diagnostics that precede the block
+        // before parsing it.
+        let relocate_comment = |comment: &Rc<Comment>| {
+            let location = comment.location();
+            Rc::new(Comment::new(comment.multiline(), comment.content(), Location::with_offsets(compilation_unit, relocate(location.first_offset()), relocate(location.last_offset()))))
+        };
+        let relocate_diagnostic = |diagnostic: &Diagnostic| {
+            let location = diagnostic.location();
+            let mut diagnostic = diagnostic.clone();
+            diagnostic.location = Location::with_offsets(compilation_unit, relocate(location.first_offset()), relocate(location.last_offset()));
+            diagnostic
+        };
+        let old_comments = old_compilation_unit.comments();
+        let old_diagnostics = old_compilation_unit.diagnostics();
+        for comment in old_comments.iter().filter(|c| c.location().first_offset() < old_first_offset) {
+            compilation_unit.comments_mut().push(relocate_comment(comment));
+        }
+        for diagnostic in old_diagnostics.iter().filter(|d| d.location().first_offset() < old_first_offset) {
+            compilation_unit.add_diagnostic(relocate_diagnostic(diagnostic));
+        }
+        let preceding_diagnostic_count = compilation_unit.diagnostics.borrow().len();
+
+        let mut parser = Parser::new(compilation_unit, &ParserOptions {
+            byte_range: Some((first_offset, last_offset)),
+            ..self.1.clone()
+        });
+        let (block, contains_await, contains_yield) = parser.parse_isolated_block(target.context.clone(), target.function.is_some())?;
+
+        // A change to the function kind affects the diagnostics of its definition.
+        if let Some(common) = &target.function {
+            if common.contains_await != contains_await || common.contains_yield != contains_yield {
+                return None;
+            }
+        }
+
+        // Errors at the end of the range indicate that the
+        // block is not terminated by its original brace.
+        if compilation_unit.diagnostics.borrow()[preceding_diagnostic_count..].iter().any(|d| d.location().first_offset() >= last_offset) {
+            return None;
+        }
+
+        for comment in old_comments.iter().filter(|c| c.location().first_offset() >= old_last_offset) {
+            compilation_unit.comments_mut().push(relocate_comment(comment));
+        }
+        compilation_unit.comments_mut().sort_by_key(|c| c.location().first_offset());
+        for diagnostic in old_diagnostics.iter().filter(|d| d.location().first_offset() >= old_last_offset) {
+            compilation_unit.add_diagnostic(relocate_diagnostic(diagnostic));
+        }
+
+        let mut relocator = Relocator {
+            old_compilation_unit: old_compilation_unit.clone(),
+            compilation_unit: compilation_unit.clone(),
+            first_offset: old_first_offset,
+            delta,
+            target: target.location.clone(),
+            block: Some(block),
+        };
+        let program = relocator.fold_program(program);
+
+        // Include directives contribute nested compilation units in source order.
+        let mut includes = IncludeCollector {
+            compilation_unit: compilation_unit.clone(),
+            nested_compilation_units: vec![],
+        };
+        includes.visit_program(&program);
+        compilation_unit.nested_compilation_units.borrow_mut().clear();
+        for nested_compilation_unit in includes.nested_compilation_units {
+            if nested_compilation_unit.invalidated() {
+                compilation_unit.invalidated.set(true);
+            }
+            compilation_unit.add_nested_compilation_unit(nested_compilation_unit);
+        }
+        NodeIdAssigner.visit_program(&program);
+
+        Some(program)
+    }
+}
+
+struct ReparseTarget {
+    location: Location,
+    context: ParserDirectiveContext,
+    function: Option<Rc<FunctionCommon>>,
+}
+
+/// Finds the innermost function body or class block
+/// whose braces enclose a range.
+struct ReparseTargetFinder {
+    compilation_unit: Rc<CompilationUnit>,
+    range: (usize, usize),
+    constructor: bool,
+    function_depth: usize,
+    target: Option<ReparseTarget>,
+}
+
+impl ReparseTargetFinder {
+    fn encloses_range(&self, location: &Location) -> bool {
+        Rc::ptr_eq(&location.compilation_unit(), &self.compilation_unit)
+        && location.first_offset() < self.range.0
+        && self.range.1 < location.last_offset()
+    }
+}
+
+impl Visitor for ReparseTargetFinder {
+    fn visit_function_definition(&mut self, node: &FunctionDefinition) {
+        self.constructor = matches!(node.name, FunctionName::Constructor(_));
+        walk_function_definition(self, node);
+    }
+
+    fn visit_function_common(&mut self, node: &Rc<FunctionCommon>) {
+        let constructor = std::mem::take(&mut self.constructor);
+        if let Some(FunctionBody::Block(block)) = &node.body {
+            if self.encloses_range(&block.location) {
+                self.target = Some(ReparseTarget {
+                    location: block.location.clone(),
+                    context: if constructor {
+                        ParserDirectiveContext::ConstructorBlock { super_statement_found: Rc::new(Cell::new(false)) }
+                    } else {
+                        ParserDirectiveContext::Default
+                    },
+                    function: Some(node.clone()),
+                });
+            }
+        }
+        self.function_depth += 1;
+        walk_function_common(self, node);
+        self.function_depth -= 1;
+    }
+
+    fn visit_class_definition(&mut self, node: &ClassDefinition) {
+        // Nested classes are parsed under the activation of the enclosing function.
+        if self.function_depth == 0 && self.encloses_range(&node.block.location) {
+            self.target = Some(ReparseTarget {
+                location: node.block.location.clone(),
+                context: ParserDirectiveContext::ClassBlock { name: node.name.0.clone() },
+                function: None,
+            });
+        }
+        walk_class_definition(self, node);
+    }
+}
+
+/// Moves a tree into a new compilation unit, replacing the reparsed block.
+struct Relocator {
+    old_compilation_unit: Rc<CompilationUnit>,
+    compilation_unit: Rc<CompilationUnit>,
+    first_offset: usize,
+    delta: isize,
+    target: Location,
+    block: Option<Block>,
+}
+
+impl Folder for Relocator {
+    fn fold_location(&mut self, location: &Location) -> Location {
+        if !Rc::ptr_eq(&location.compilation_unit(), &self.old_compilation_unit) {
+            return location.clone();
+        }
+        let relocate = |offset: usize| if offset <= self.first_offset { offset } else { offset.wrapping_add_signed(self.delta) };
+        Location::with_offsets(&self.compilation_unit, relocate(location.first_offset()), relocate(location.last_offset()))
+    }
+
+    fn fold_block(&mut self, node: &Block) -> Block {
+        if node.location == self.target {
+            if let Some(block) = self.block.take() {
+                return block;
+            }
+        }
+        rebuild_block(self, node)
+    }
+}
+
+struct IncludeCollector {
+    compilation_unit: Rc<CompilationUnit>,
+    nested_compilation_units: Vec<Rc<CompilationUnit>>,
+}
+
+impl Visitor for IncludeCollector {
+    fn visit_include_directive(&mut self, node: &IncludeDirective) {
+        if Rc::ptr_eq(&node.location.compilation_unit(), &self.compilation_unit) {
+            self.nested_compilation_units.push(node.nested_compilation_unit.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    fn summary(compilation_unit: &Rc<CompilationUnit>, program: &Rc<Program>) -> (String, Vec<String>, Vec<String>) {
+        compilation_unit.sort_diagnostics();
+        let diagnostics = compilation_unit.diagnostics().iter().map(|d| {
+            let location = d.location();
+            format!("{}:{}:{}", d.id(), location.first_offset(), location.last_offset())
+        }).collect();
+        let comments = compilation_unit.comments().iter().map(|c| {
+            let location = c.location();
+            format!("{}:{}", location.first_offset(), c.content())
+        }).collect();
+        (PrinterFacade(default()).print_program(program), diagnostics, comments)
+    }
+
+    #[test]
+    fn test_reparse() {
+        let text = r#"
+package p {
+    public class C {
+        // Field
+        var x = 1;
+        public function C() { super(); }
+        public function f(): void {
+            trace(x) // Trace
+        }
+        /** Doc */
+        public function g(): void { return; }
+    }
+}
+var y = {;
+"#;
+        let cu = CompilationUnit::new(None, text.into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        let body = text.find("trace").unwrap();
+        let ctor = text.find("super").unwrap();
+        let field = text.find("var x").unwrap();
+        let cases: Vec<(Vec<TextEdit>, bool)> = vec![
+            // Reparses the body of `f`.
+            (vec![TextEdit::new(body..body + 5, "log"), TextEdit::new(body + 6..body + 7, "x + 10 /* Sum */")], true),
+            // Reparses the constructor body.
+            (vec![TextEdit::new(ctor..ctor + 8, "super(); super();")], true),
+            // Reparses the class block.
+            (vec![TextEdit::new(field..field + 10, "var x = 2; var z;")], true),
+            // Yielding changes the kind of function.
+            (vec![TextEdit::new(body..body + 5, "yield")], false),
+            // Unbalanced braces.
+            (vec![TextEdit::new(body..body, "{")], false),
+            (vec![TextEdit::new(body..body, "}")], false),
+            (vec![TextEdit::new(body..body, "/*")], false),
+            // Outside of any block.
+            (vec![TextEdit::new(1..1, "import q.*;")], false),
+        ];
+        for (edits, incremental) in cases {
+            let (new_cu, new_program) = ParserFacade(&cu, default()).reparse_program(&program, &edits);
+            let full_cu = CompilationUnit::new(None, TextEdit::apply_all(text, &edits));
+            let full_program = ParserFacade(&full_cu, default()).parse_program();
+            assert_eq!(summary(&new_cu, &new_program), summary(&full_cu, &full_program), "{}", full_cu.text());
+            assert_eq!(new_cu.invalidated(), full_cu.invalidated());
+
+            let attempt_cu = CompilationUnit::new(None, full_cu.text().clone());
+            let reparsed = ParserFacade(&cu, default()).reparse_block(&program, &edits, &attempt_cu);
+            assert_eq!(reparsed.is_some(), incremental, "{}", full_cu.text());
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::ns::*;
+use hydroperfox_filepaths::FlexPath;
+
+/// Resolves and loads the sources of include directives.
+///
+/// The parser obtains the provider from [`ParserOptions::source_provider`],
+/// which defaults to a [`FileSystemSourceProvider`].
+pub trait SourceProvider: MaybeSync {
+    /// Resolves the path of an included source, given the file path of the
+    /// including source, if any. Returns `None` if the path cannot be resolved.
+    fn resolve(&self, including_file_path: Option<&str>, path: &str) -> Option<String>;
+
+    /// Loads the text of a resolved source.
+    fn load(&self, file_path: &str) -> Option<String>;
+
+    /// Returns a path that is the same for every path of a source,
+    /// used for detecting circular include directives.
+    fn canonicalize(&self, file_path: &str) -> String {
+        file_path.to_owned()
+    }
+}
+
+/// Loads sources from the file system, resolving included paths relative
+/// to the including source, or to the current directory if the including
+/// source is not a file.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FileSystemSourceProvider;
+
+impl SourceProvider for FileSystemSourceProvider {
+    fn resolve(&self, including_file_path: Option<&str>, path: &str) -> Option<String> {
+        let origin = match including_file_path {
+            Some(file_path) => file_path.to_owned(),
+            None => std::env::current_dir().ok()?.to_string_lossy().into_owned(),
+        };
+        Some(FlexPath::from_n_native([origin.as_ref(), "..", path]).to_string_with_flex_separator())
+    }
+
+    fn load(&self, file_path: &str) -> Option<String> {
+        std::fs::read_to_string(file_path).ok()
+    }
+
+    fn canonicalize(&self, file_path: &str) -> String {
+        std::path::Path::new(file_path).canonicalize().map_or_else(|_| file_path.to_owned(), |path| path.to_string_lossy().into_owned())
+    }
+}
+
+/// Sources held in memory, such as unsaved editor buffers or the files of
+/// a virtual file system, keyed by resolved path. They take precedence over
+/// the sources of an optional fallback provider, which also resolves paths.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let sources = InMemorySourceProvider::new();
+/// sources.insert("src/log.include.as", "trace(1);");
+/// let cu = CompilationUnit::new(Some("src/Main.as".into()), "include \"log.include.as\";".into());
+/// let options = ParserOptions { source_provider: Rc::new(sources), ..default() };
+/// let program = ParserFacade(&cu, options).parse_program();
+/// assert!(!cu.invalidated());
+/// assert_eq!(cu.nested_compilation_units()[0].text(), "trace(1);");
+/// ```
+#[derive(Default)]
+pub struct InMemorySourceProvider {
+    sources: RefCell<HashMap<String, String>>,
+    fallback: Option<Rc<dyn SourceProvider>>,
+}
+
+impl InMemorySourceProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fallback(fallback: Rc<dyn SourceProvider>) -> Self {
+        Self { sources: default(), fallback: Some(fallback) }
+    }
+
+    /// Sets the text of a source, replacing any previous text.
+    pub fn insert(&self, file_path: &str, text: &str) {
+        self.sources.borrow_mut().insert(file_path.to_owned(), text.to_owned());
+    }
+
+    pub fn remove(&self, file_path: &str) -> Option<String> {
+        self.sources.borrow_mut().remove(file_path)
+    }
+}
+
+impl SourceProvider for InMemorySourceProvider {
+    fn resolve(&self, including_file_path: Option<&str>, path: &str) -> Option<String> {
+        match &self.fallback {
+            Some(fallback) => fallback.resolve(including_file_path, path),
+            None => Some(FlexPath::from_n_native([including_file_path?, "..", path]).to_string_with_flex_separator()),
+        }
+    }
+
+    fn load(&self, file_path: &str) -> Option<String> {
+        if let Some(text) = self.sources.borrow().get(file_path) {
+            return Some(text.clone());
+        }
+        self.fallback.as_ref()?.load(file_path)
+    }
+
+    fn canonicalize(&self, file_path: &str) -> String {
+        match &self.fallback {
+            Some(fallback) if !self.sources.borrow().contains_key(file_path) => fallback.canonicalize(file_path),
+            _ => file_path.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_in_memory_source_provider() {
+        let disk = InMemorySourceProvider::new();
+        disk.insert("/src/a.include.as", "var x;");
+        disk.insert("/src/b.include.as", "include \"b.include.as\";");
+        let buffers = InMemorySourceProvider::with_fallback(Rc::new(disk));
+        buffers.insert("/src/a.include.as", "var y;");
+        let options = ParserOptions { source_provider: Rc::new(buffers), ..default() };
+
+        let cu = CompilationUnit::new(Some("/src/Main.as".into()), "include \"a.include.as\";\ninclude \"b.include.as\";\ninclude \"c.include.as\";".into());
+        ParserFacade(&cu, options).parse_program();
+        let nested = cu.nested_compilation_units();
+        assert_eq!(nested[0].file_path(), Some("/src/a.include.as".into()));
+        assert_eq!(nested[0].text(), "var y;");
+        let ids: Vec<i32> = cu.nested_diagnostics().iter().map(|diagnostic| diagnostic.id()).collect();
+        assert_eq!(ids, [DiagnosticKind::FailedToIncludeFile.id(), DiagnosticKind::CircularIncludeDirective.id()]);
+    }
+
+    #[test]
+    fn test_include_paths() {
+        let sources = InMemorySourceProvider::new();
+        sources.insert("/lib/shared/log.as", "var log;");
+        let options = ParserOptions {
+            source_provider: Rc::new(sources),
+            include_paths: vec!["/lib".into(), "/lib/shared".into()],
+            legacy_includes: true,
+            ..default()
+        };
+        let cu = CompilationUnit::new(Some("/src/Main.as".into()), "include \"log.as\";\ninclude \"missing.as\";\ninclude \"log.txt\";".into());
+        ParserFacade(&cu, options).parse_program();
+        assert_eq!(cu.nested_compilation_units()[0].file_path(), Some("/lib/shared/log.as".into()));
+        let messages: Vec<String> = cu.nested_diagnostics().iter().map(|diagnostic| diagnostic.format_message_english()).collect();
+        assert_eq!(messages, [
+            "Failed to include file. Tried '/src/missing.as', '/lib/missing.as', '/lib/shared/missing.as'.",
+            "Unexpected include extension. Expected one of the file extensions '.include.as', '.as'.",
+        ]);
+
+        let cu = CompilationUnit::new(Some("/src/Main.as".into()), "include \"log.as\";".into());
+        ParserFacade(&cu, default()).parse_program();
+        assert_eq!(cu.nested_diagnostics()[0].format_message_english(), "Unexpected include extension. Expected the file extension '.include.as'.");
+    }
+}
@@ -159,6 +159,18 @@ pub enum Token {
     XmlName(String),
     XmlMarkup(String),
     XmlAttributeValue(String),
+
+    /// Comment token, produced only by [`Lexer`]. The content excludes
+    /// the `//`, `/*` and `*/` delimiters; ASDoc comments are multi-line
+    /// comments whose content starts with `*`.
+    Comment {
+        multiline: bool,
+        content: String,
+    },
+    /// Whitespace token, produced only by [`Lexer`].
+    Whitespace,
+    /// Line terminator token, produced only by [`Lexer`].
+    LineTerminator,
 }
 
 impl ToString for Token {
@@ -181,6 +193,9 @@ impl ToString for Token {
     /// * `"XML name"`
     /// * `"XML markup"`
     /// * `"XML attribute value"`
+    /// * `"comment"`
+    /// * `"whitespace"`
+    /// * `"line terminator"`
     fn to_string(&self) -> String {
         (match self {
             Token::Eof => "end-of-file",
@@ -320,6 +335,9 @@ impl ToString for Token {
             Token::XmlName(_) => "XML name",
             Token::XmlMarkup(_) => "XML markup",
             Token::XmlAttributeValue(_) => "XML attribute value",
+            Token::Comment { .. } => "comment",
+            Token::Whitespace => "whitespace",
+            Token::LineTerminator => "line terminator",
         }).into()
     }
 }
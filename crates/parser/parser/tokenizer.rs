@@ -3,6 +3,8 @@ use crate::ns::*;
 pub struct Tokenizer<'input> {
     compilation_unit: Rc<CompilationUnit>,
     characters: CharacterReader<'input>,
+    /// Comments collected by a detached tokenizer.
+    detached_comments: Option<Vec<Rc<Comment>>>,
 }
 
 impl<'input> Tokenizer<'input> {
@@ -19,9 +21,21 @@ impl<'input> Tokenizer<'input> {
         Self {
             compilation_unit,
             characters,
+            detached_comments: None,
         }
     }
 
+    /// Detaches the tokenizer from its compilation unit: comments are collected
+    /// by the tokenizer instead and syntax errors are discarded.
+    pub(crate) fn detach(&mut self) {
+        self.detached_comments.get_or_insert_with(Vec::new);
+    }
+
+    /// Takes the comments collected by a detached tokenizer.
+    pub(crate) fn take_detached_comments(&mut self) -> Vec<Rc<Comment>> {
+        self.detached_comments.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
     pub fn compilation_unit(&self) -> &Rc<CompilationUnit> {
         &self.compilation_unit
     }
@@ -30,8 +44,13 @@ impl<'input> Tokenizer<'input> {
         &self.characters
     }
 
+    /// Moves the cursor forward to a byte offset without scanning tokens.
+    pub(crate) fn skip_to(&mut self, offset: usize) {
+        while self.characters.index() < offset && self.characters.next().is_some() {}
+    }
+
     fn add_syntax_error(&self, location: &Location, kind: DiagnosticKind, arguments: Vec<Rc<dyn DiagnosticArgument>>) {
-        if self.compilation_unit.prevent_equal_offset_error(location) {
+        if self.detached_comments.is_some() || self.compilation_unit.prevent_equal_offset_error(location) {
             return;
         }
         self.compilation_unit.add_diagnostic(Diagnostic::new_syntax_error(location, kind, arguments));
@@ -523,6 +542,14 @@ impl<'input> Tokenizer<'input> {
         false
     }
 
+    fn add_comment(&mut self, comment: Rc<Comment>) {
+        if let Some(comments) = self.detached_comments.as_mut() {
+            comments.push(comment);
+        } else {
+            self.compilation_unit.add_comment(comment);
+        }
+    }
+
     fn consume_comment(&mut self) -> bool {
         let ch = self.characters.peek_or_zero();
         if ch != '/' {
@@ -538,7 +565,7 @@ impl<'input> Tokenizer<'input> {
             let location = start.combine_with(self.cursor_location());
             self.consume_line_terminator();
 
-            self.compilation_unit.add_comment(Rc::new(Comment {
+            self.add_comment(Rc::new(Comment {
                 multiline: false,
                 content: RefCell::new(self.compilation_unit.text()[(location.first_offset() + 2)..location.last_offset()].to_owned()),
                 location: RefCell::new(location),
@@ -569,7 +596,7 @@ impl<'input> Tokenizer<'input> {
             let i = location.first_offset() + 2;
             let j = decrease_last_offset(i, location.last_offset(), 2);
 
-            self.compilation_unit.add_comment(Rc::new(Comment {
+            self.add_comment(Rc::new(Comment {
                 multiline: true,
                 content: RefCell::new(self.compilation_unit.text()[i..j].to_owned()),
                 location: RefCell::new(location),
@@ -0,0 +1,9 @@
+//! Defines the ActionScript 3 printer.
+//!
+//! Using the methods of the `PrinterFacade` structure is the most common way of
+//! turning syntax trees back into source text.
+
+mod source_printer;
+pub use source_printer::*;
+mod formatter;
+pub use formatter::*;
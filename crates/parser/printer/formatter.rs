@@ -0,0 +1,142 @@
+use crate::ns::*;
+
+/// A simplified interface for formatting ActionScript 3 compilation units.
+///
+/// The formatter reprints the program through `Printer`, reinserting
+/// every comment of the compilation unit, including ASDoc comments and
+/// trailing line comments, and preserving single blank lines between
+/// directives. Formatting its own output yields the same text.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let cu = CompilationUnit::new(None, "if(x){trace( 1 ) // one\n}".into());
+/// let text = FormatterFacade(&cu, default()).format_program().unwrap();
+/// assert_eq!(text, "if (x) {\n    trace(1); // one\n}\n");
+/// ```
+pub struct FormatterFacade<'input>(pub &'input Rc<CompilationUnit>, pub PrinterOptions);
+
+impl<'input> FormatterFacade<'input> {
+    /// Parses the compilation unit as a `Program` and formats it.
+    /// Returns `None` if the compilation unit contains errors.
+    pub fn format_program(&self) -> Option<String> {
+        let program = ParserFacade(self.0, default()).parse_program();
+        if self.0.invalidated() {
+            return None;
+        }
+        Some(self.format_parsed_program(&program))
+    }
+
+    /// Formats a `Program` previously parsed from the compilation unit.
+    pub fn format_parsed_program(&self, program: &Rc<Program>) -> String {
+        // The parser removes ASDoc comments from the compilation unit
+        // once they are attached to the tree.
+        let mut collector = AsdocCollector {
+            compilation_unit: self.0.clone(),
+            comments: self.0.comments(),
+        };
+        collector.visit_program(program);
+        let mut printer = Printer::with_comments(self.1.clone(), self.0, collector.comments);
+        printer.print_program(program);
+        printer.into_output()
+    }
+}
+
+struct AsdocCollector {
+    compilation_unit: Rc<CompilationUnit>,
+    comments: Vec<Rc<Comment>>,
+}
+
+impl Visitor for AsdocCollector {
+    fn visit_asdoc(&mut self, node: &Rc<Asdoc>) {
+        let location = &node.location;
+        if !Rc::ptr_eq(&location.compilation_unit(), &self.compilation_unit) {
+            return;
+        }
+        let content = self.compilation_unit.text()[(location.first_offset() + 2)..(location.last_offset() - 2)].to_owned();
+        self.comments.push(Rc::new(Comment::new(true, content, location.clone())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    fn format(text: &str, options: PrinterOptions) -> String {
+        let cu = CompilationUnit::new(None, text.into());
+        FormatterFacade(&cu, options).format_program().expect(text)
+    }
+
+    fn parse(text: &str) -> Rc<Program> {
+        ParserFacade(&CompilationUnit::new(None, text.into()), default()).parse_program()
+    }
+
+    #[test]
+    fn test_idempotence() {
+        let source = r#"
+// License header
+
+package com.example
+{
+    import flash.events.Event; // events
+
+    /**
+     * Example class.
+     *
+     * @private
+     */
+    [Bindable]
+    /** Misplaced ASDoc */
+    public class Example
+    {
+        /* Block
+           comment */
+        private var x: int = 0; /* trailing */ // second
+
+
+        public function f(a: int, /* b */ b: int): void
+        {
+            // Leading
+            trace(a, // first
+                b);
+            switch (a) {
+                // Before case
+                case 1:
+                    break;
+                // Before default
+                default:
+                    // Inside default
+            }
+            var o: Object = { alpha: 1, beta: "a very long string that does not fit", gamma: [1, 2, 3], delta: function(): void {} };
+            // End of body
+        }
+    }
+
+    CONFIG::DEBUG
+    /** Debug. */
+    [Event(name="a")]
+    /** Between. */
+    [Event(name="b")]
+    internal class Debug {}
+}
+// End of file
+"#;
+        for options in [
+            PrinterOptions::default(),
+            PrinterOptions { indent_width: 2, brace_style: BraceStyle::NextLine, max_line_width: 40 },
+        ] {
+            let formatted = format(source, options.clone());
+            assert_eq!(format(&formatted, options.clone()), formatted);
+            let count = |text: &str| text.matches("//").count() + text.matches("/*").count();
+            assert_eq!(count(source), count(&formatted));
+
+            // Comments keep their owners, such as the meta-data an ASDoc comment precedes.
+            let (original, reparsed) = (parse(source), parse(&formatted));
+            let directives = |program: &Rc<Program>| program.packages[0].block.directives.clone();
+            assert_eq!(directives(&original).len(), directives(&reparsed).len());
+            assert!(directives(&original).iter().zip(directives(&reparsed).iter()).all(|(a, b)| a.structural_eq(b)), "{formatted}");
+        }
+    }
+}
@@ -0,0 +1,1661 @@
+use crate::ns::*;
+use std::collections::VecDeque;
+
+/// Options for printing ActionScript 3 source text.
+#[derive(Clone)]
+pub struct PrinterOptions {
+    /// Number of spaces per indentation level. Default: 4.
+    pub indent_width: usize,
+    /// Placement of the opening brace of blocks. Default: `BraceStyle::SameLine`.
+    pub brace_style: BraceStyle,
+    /// Width beyond which argument lists, array literals and object
+    /// initializers are split into one item per line. Default: 100.
+    pub max_line_width: usize,
+}
+
+impl Default for PrinterOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: 4,
+            brace_style: BraceStyle::SameLine,
+            max_line_width: 100,
+        }
+    }
+}
+
+/// Placement of the opening brace of blocks.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BraceStyle {
+    /// `if (x) {`
+    SameLine,
+    /// `if (x)` followed by `{` in the next line.
+    NextLine,
+}
+
+/// A simplified interface for executing the printer.
+///
+/// The facade prints no comments, including ASDoc comments; use
+/// `FormatterFacade` or `Printer::with_comments()` to print them.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let cu = CompilationUnit::new(None, "x = (a + b) * c ?? d".into());
+/// let exp = ParserFacade(&cu, default()).parse_expression();
+/// assert_eq!(PrinterFacade(default()).print_expression(&exp), "x = (a + b) * c ?? d");
+/// ```
+pub struct PrinterFacade(pub PrinterOptions);
+
+impl PrinterFacade {
+    /// Prints a `Program`.
+    pub fn print_program(&self, program: &Rc<Program>) -> String {
+        let mut printer = Printer::new(self.0.clone());
+        printer.print_program(program);
+        printer.into_output()
+    }
+
+    /// Prints a directive, ending with a line break.
+    pub fn print_directive(&self, directive: &Rc<Directive>) -> String {
+        let mut printer = Printer::new(self.0.clone());
+        printer.print_directive(directive);
+        printer.into_output()
+    }
+
+    /// Prints an expression or type expression.
+    pub fn print_expression(&self, exp: &Rc<Expression>) -> String {
+        let mut printer = Printer::new(self.0.clone());
+        printer.print_expression(exp);
+        printer.into_output()
+    }
+
+    /// Prints the parameter list and result type of a function.
+    pub fn print_function_signature(&self, signature: &FunctionSignature) -> String {
+        let mut printer = Printer::new(self.0.clone());
+        printer.print_function_signature(signature);
+        printer.into_output()
+    }
+}
+
+/// Emits ActionScript 3 source text from syntax trees.
+///
+/// Parentheses are only inserted where the operator precedence of a
+/// subexpression requires them; parenthesized expressions of the
+/// tree are always kept. Comments, including ASDoc comments, are only
+/// emitted by printers created through `Printer::with_comments()`.
+pub struct Printer {
+    options: PrinterOptions,
+    output: String,
+    indent: usize,
+    /// Whether the `in` operator must be parenthesized, as in
+    /// the initializer of a `for` statement.
+    no_in: bool,
+    /// The compilation unit whose comments are reinserted.
+    source: Option<Rc<CompilationUnit>>,
+    /// Comments not yet emitted, sorted by offset.
+    comments: VecDeque<Rc<Comment>>,
+    /// Last source line emitted in the current block, used
+    /// for preserving blank lines.
+    last_line: Option<usize>,
+    /// Whether lists are always written in a single line,
+    /// as when measuring their width.
+    measuring: bool,
+}
+
+impl Printer {
+    pub fn new(options: PrinterOptions) -> Self {
+        Self {
+            options,
+            output: String::new(),
+            indent: 0,
+            no_in: false,
+            source: None,
+            comments: VecDeque::new(),
+            last_line: None,
+            measuring: false,
+        }
+    }
+
+    /// Creates a printer that reinserts comments of a compilation unit
+    /// and preserves blank lines between directives parsed from it.
+    pub fn with_comments(options: PrinterOptions, compilation_unit: &Rc<CompilationUnit>, mut comments: Vec<Rc<Comment>>) -> Self {
+        comments.sort_by_key(|c| c.location().first_offset());
+        Self {
+            source: Some(compilation_unit.clone()),
+            comments: comments.into(),
+            ..Self::new(options)
+        }
+    }
+
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    pub fn into_output(self) -> String {
+        self.output
+    }
+
+    pub fn print_program(&mut self, program: &Rc<Program>) {
+        for pckg in &program.packages {
+            self.print_package_definition(pckg);
+        }
+        for directive in &program.directives {
+            self.print_directive(directive);
+        }
+        self.write_comments_before(usize::MAX);
+    }
+
+    pub fn print_package_definition(&mut self, pckg: &Rc<PackageDefinition>) {
+        let in_source = self.begin_line(&pckg.location);
+        self.write("package");
+        if !pckg.name.is_empty() {
+            self.write(" ");
+            self.write_names(&pckg.name);
+        }
+        self.write_braced_block(&pckg.block);
+        self.end_line(&pckg.location, in_source);
+    }
+
+    /// Prints a directive at the current indentation, ending with a line break.
+    pub fn print_directive(&mut self, directive: &Rc<Directive>) {
+        if let Directive::DirectiveInjection(inj) = directive.as_ref() {
+            for directive in inj.directives.borrow().iter() {
+                self.print_directive(directive);
+            }
+            return;
+        }
+        let location = directive.location();
+        let in_source = self.begin_line(&location);
+        self.write_directive(directive);
+        self.end_line(&location, in_source);
+    }
+
+    pub fn print_expression(&mut self, exp: &Rc<Expression>) {
+        self.write_expression(exp, OperatorPrecedence::List);
+    }
+
+    /// Prints the parameter list and result type of a function.
+    pub fn print_function_signature(&mut self, signature: &FunctionSignature) {
+        self.write("(");
+        let mut first = true;
+        if let Some(this_param) = &signature.this_parameter {
+            self.write("this: ");
+            self.print_expression(&this_param.type_annotation);
+            first = false;
+        }
+        for param in &signature.parameters {
+            if !first {
+                self.write(", ");
+            }
+            first = false;
+            if matches!(param.kind, ParameterKind::Rest) {
+                self.write("...");
+            }
+            self.write_typed_destructuring(&param.destructuring);
+            if let Some(default_value) = &param.default_value {
+                self.write(" = ");
+                self.write_expression(default_value, OperatorPrecedence::AssignmentAndOther);
+            }
+        }
+        self.write(")");
+        if let Some(result_type) = &signature.result_type {
+            self.write(": ");
+            self.print_expression(result_type);
+        }
+    }
+
+    fn write(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn write_indent(&mut self) {
+        let width = self.indent * self.options.indent_width;
+        self.output.extend(std::iter::repeat_n(' ', width));
+    }
+
+    fn column(&self) -> usize {
+        self.output.len() - self.output.rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Whether a location belongs to the compilation unit
+    /// whose comments are reinserted.
+    fn in_source(&self, location: &Location) -> bool {
+        self.source.as_ref().is_some_and(|cu| Rc::ptr_eq(cu, &location.compilation_unit()))
+    }
+
+    /// Writes the leading comments and indentation of a line
+    /// holding the node at `location`.
+    fn begin_line(&mut self, location: &Location) -> bool {
+        let in_source = self.in_source(location);
+        if in_source {
+            self.write_comments_before(location.first_offset());
+            self.write_blank_line_before(location.first_line_number());
+        }
+        self.write_indent();
+        in_source
+    }
+
+    /// Writes the trailing comments of the node at `location`
+    /// and terminates the line.
+    fn end_line(&mut self, location: &Location, in_source: bool) {
+        if !in_source {
+            self.write("\n");
+            return;
+        }
+        let compilation_unit = location.compilation_unit();
+        let text = compilation_unit.text();
+        let mut last_line = location.last_line_number();
+        let mut last_offset = location.last_offset();
+        let mut line_open = true;
+        while let Some(comment) = self.comments.front().cloned() {
+            let comment_location = comment.location();
+            // Comments inside the node that were not emitted by nested
+            // directives, and comments that follow the node in its last line.
+            let inside = comment_location.first_offset() < location.last_offset();
+            let same_line = comment_location.first_line_number() == location.last_line_number()
+                && text[last_offset.min(comment_location.first_offset())..comment_location.first_offset()].chars().all(|ch| ch == ';' || ch.is_whitespace());
+            if !(inside || same_line) {
+                break;
+            }
+            self.comments.pop_front();
+            if line_open && !comment.content().contains('\n') {
+                self.write(" ");
+            } else {
+                if line_open {
+                    self.write("\n");
+                }
+                self.write_indent();
+            }
+            self.write_comment(&comment);
+            self.write("\n");
+            line_open = false;
+            last_line = last_line.max(comment_location.last_line_number());
+            last_offset = last_offset.max(comment_location.last_offset());
+        }
+        if line_open {
+            self.write("\n");
+        }
+        self.last_line = Some(last_line);
+    }
+
+    fn has_comments_before(&self, offset: usize) -> bool {
+        self.comments.front().is_some_and(|c| c.location().first_offset() < offset)
+    }
+
+    /// Writes the comments before `offset`, each in its own line.
+    fn write_comments_before(&mut self, offset: usize) {
+        while self.has_comments_before(offset) {
+            let comment = self.comments.pop_front().unwrap();
+            let location = comment.location();
+            self.write_blank_line_before(location.first_line_number());
+            self.write_indent();
+            self.write_comment(&comment);
+            self.write("\n");
+            self.last_line = Some(location.last_line_number());
+        }
+    }
+
+    /// Writes the comments before `offset` in the middle of a directive
+    /// header, such as between meta-data and a definition.
+    fn write_header_comments_before(&mut self, location: &Location) {
+        if !self.in_source(location) {
+            return;
+        }
+        if self.has_comments_before(location.first_offset()) && self.column() > self.indent * self.options.indent_width {
+            // Start the comments in their own line, as after `CONFIG::DEBUG`.
+            self.output.truncate(self.output.trim_end_matches(' ').len());
+            self.write("\n");
+            self.write_indent();
+        }
+        while self.has_comments_before(location.first_offset()) {
+            let comment = self.comments.pop_front().unwrap();
+            self.write_comment(&comment);
+            self.write("\n");
+            self.write_indent();
+        }
+    }
+
+    /// Preserves a single blank line from the source.
+    fn write_blank_line_before(&mut self, line: usize) {
+        if self.last_line.is_some_and(|last_line| line > last_line + 1) {
+            self.write("\n");
+        }
+    }
+
+    fn write_comment(&mut self, comment: &Comment) {
+        let content = comment.content();
+        if !comment.multiline() {
+            self.write("//");
+            self.write(content.trim_end());
+            return;
+        }
+        if !content.contains('\n') {
+            self.write("/*");
+            self.write(&content);
+            self.write("*/");
+            return;
+        }
+        let lines = content.split('\n').map(|line| line.trim_end()).collect::<Vec<_>>();
+        self.write("/*");
+        self.write(lines[0]);
+        let rest = &lines[1..];
+        let starred = rest.iter().enumerate().all(|(i, line)| {
+            let line = line.trim_start();
+            line.starts_with('*') || (line.is_empty() && i + 1 == rest.len())
+        });
+        let common_indent = rest.iter()
+            .filter(|line| !line.trim_start().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min().unwrap_or(0);
+        for line in rest {
+            self.write("\n");
+            if starred {
+                self.write_indent();
+                self.write(" ");
+                self.write(line.trim_start());
+            } else if !line.trim_start().is_empty() {
+                self.write_indent();
+                self.write(&line[common_indent..]);
+            } else if std::ptr::eq(line, rest.last().unwrap()) {
+                self.write_indent();
+            }
+        }
+        if !rest.last().unwrap().is_empty() && content.ends_with(char::is_whitespace) {
+            self.write(" ");
+        }
+        self.write("*/");
+    }
+
+    fn write_names(&mut self, names: &[(String, Location)]) {
+        let names = names.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        self.write(&names.join("."));
+    }
+
+    fn write_block(&mut self, block: &Block) {
+        let in_source = self.in_source(&block.location);
+        let end_offset = block.location.last_offset();
+        if block.directives.is_empty() && !(in_source && self.has_comments_before(end_offset)) {
+            self.write("{}");
+            return;
+        }
+        self.write("{\n");
+        self.indent += 1;
+        self.last_line = None;
+        for directive in &block.directives {
+            self.print_directive(directive);
+        }
+        if in_source {
+            self.write_comments_before(end_offset);
+        }
+        self.indent -= 1;
+        self.write_indent();
+        self.write("}");
+    }
+
+    /// Writes a block after a header such as `function f()`,
+    /// placing the opening brace according to the brace style.
+    fn write_braced_block(&mut self, block: &Block) {
+        self.write_brace_separator();
+        self.write_block(block);
+    }
+
+    fn write_brace_separator(&mut self) {
+        match self.options.brace_style {
+            BraceStyle::SameLine => self.write(" "),
+            BraceStyle::NextLine => {
+                self.write("\n");
+                self.write_indent();
+            },
+        }
+    }
+
+    /// Writes the comments, blank line and indentation preceding
+    /// a line that is not a directive, such as a `case` label.
+    fn write_leading_line(&mut self, location: &Location) {
+        if self.in_source(location) {
+            self.write_comments_before(location.first_offset());
+            self.write_blank_line_before(location.first_line_number());
+            self.last_line = Some(location.first_line_number());
+        }
+        self.write_indent();
+    }
+
+    /// Writes the remaining comments and the closing brace
+    /// of a statement whose body is not a `Block`, such as `switch`.
+    fn write_closing_brace(&mut self, location: &Location) {
+        if self.in_source(location) {
+            self.write_comments_before(location.last_offset());
+        }
+        self.indent -= 1;
+        self.write_indent();
+        self.write("}");
+    }
+
+    /// Writes the body of a statement; blocks stay on the same line
+    /// and other statements are moved into an indented line.
+    fn write_substatement(&mut self, directive: &Rc<Directive>) {
+        if let Directive::Block(block) = directive.as_ref() {
+            self.write_braced_block(block);
+        } else {
+            self.write("\n");
+            self.indent += 1;
+            self.write_indent();
+            self.write_directive(directive);
+            self.indent -= 1;
+        }
+    }
+
+    /// Writes the keyword following a substatement, such as `else`.
+    fn write_after_substatement(&mut self, directive: &Rc<Directive>, keyword: &str) {
+        if matches!(directive.as_ref(), Directive::Block(_)) {
+            self.write_brace_separator();
+        } else {
+            self.write("\n");
+            self.write_indent();
+        }
+        self.write(keyword);
+    }
+
+    fn write_directive(&mut self, directive: &Rc<Directive>) {
+        match directive.as_ref() {
+            Directive::EmptyStatement(_) => self.write(";"),
+            Directive::ExpressionStatement(stmt) => {
+                if Self::starts_like_directive(&stmt.expression) {
+                    self.write("(");
+                    self.print_expression(&stmt.expression);
+                    self.write(")");
+                } else {
+                    self.print_expression(&stmt.expression);
+                }
+                self.write(";");
+            },
+            Directive::SuperStatement(stmt) => {
+                self.write("super");
+                self.write_arguments(&stmt.arguments);
+                self.write(";");
+            },
+            Directive::Block(block) => self.write_block(block),
+            Directive::LabeledStatement(stmt) => {
+                self.write(&stmt.label.0);
+                self.write(": ");
+                self.write_directive(&stmt.substatement);
+            },
+            Directive::IfStatement(stmt) => self.write_if_statement(stmt),
+            Directive::SwitchStatement(stmt) => {
+                self.write("switch (");
+                self.print_expression(&stmt.discriminant);
+                self.write(")");
+                self.write_brace_separator();
+                self.write("{\n");
+                self.indent += 1;
+                self.last_line = None;
+                for case in &stmt.cases {
+                    for label in &case.labels {
+                        self.write_leading_line(&label.location());
+                        match label {
+                            CaseLabel::Case((exp, _)) => {
+                                self.write("case ");
+                                self.print_expression(exp);
+                                self.write(":\n");
+                            },
+                            CaseLabel::Default(_) => self.write("default:\n"),
+                        }
+                    }
+                    self.indent += 1;
+                    for directive in &case.directives {
+                        self.print_directive(directive);
+                    }
+                    self.indent -= 1;
+                }
+                self.write_closing_brace(&stmt.location);
+            },
+            Directive::SwitchTypeStatement(stmt) => {
+                self.write("switch type (");
+                self.print_expression(&stmt.discriminant);
+                self.write(")");
+                self.write_brace_separator();
+                self.write("{\n");
+                self.indent += 1;
+                self.last_line = None;
+                for case in &stmt.cases {
+                    self.write_leading_line(&case.location);
+                    if let Some(param) = &case.parameter {
+                        self.write("case (");
+                        self.write_typed_destructuring(param);
+                        self.write(")");
+                    } else {
+                        self.write("default");
+                    }
+                    self.write_braced_block(&case.block);
+                    self.write("\n");
+                    self.last_line = Some(case.location.last_line_number());
+                }
+                self.write_closing_brace(&stmt.location);
+            },
+            Directive::DoStatement(stmt) => {
+                self.write("do");
+                self.write_substatement(&stmt.body);
+                self.write_after_substatement(&stmt.body, "while (");
+                self.print_expression(&stmt.test);
+                self.write(");");
+            },
+            Directive::WhileStatement(stmt) => {
+                self.write("while (");
+                self.print_expression(&stmt.test);
+                self.write(")");
+                self.write_substatement(&stmt.body);
+            },
+            Directive::ForStatement(stmt) => {
+                self.write("for (");
+                if let Some(init) = &stmt.init {
+                    let no_in = self.no_in;
+                    self.no_in = true;
+                    match init {
+                        ForInitializer::Expression(exp) => self.print_expression(exp),
+                        ForInitializer::VariableDefinition(defn) => self.write_simple_variable_definition(defn),
+                    }
+                    self.no_in = no_in;
+                }
+                self.write(";");
+                if let Some(test) = &stmt.test {
+                    self.write(" ");
+                    self.print_expression(test);
+                }
+                self.write(";");
+                if let Some(update) = &stmt.update {
+                    self.write(" ");
+                    self.print_expression(update);
+                }
+                self.write(")");
+                self.write_substatement(&stmt.body);
+            },
+            Directive::ForInStatement(stmt) => {
+                self.write(if stmt.each { "for each (" } else { "for (" });
+                let no_in = self.no_in;
+                self.no_in = true;
+                match &stmt.left {
+                    ForInBinding::Expression(exp) => self.write_expression(exp, OperatorPrecedence::Postfix),
+                    ForInBinding::VariableDefinition(defn) => self.write_simple_variable_definition(defn),
+                }
+                self.no_in = no_in;
+                self.write(" in ");
+                self.print_expression(&stmt.right);
+                self.write(")");
+                self.write_substatement(&stmt.body);
+            },
+            Directive::BreakStatement(stmt) => {
+                self.write("break");
+                if let Some(label) = &stmt.label {
+                    self.write(" ");
+                    self.write(&label.0);
+                }
+                self.write(";");
+            },
+            Directive::ContinueStatement(stmt) => {
+                self.write("continue");
+                if let Some(label) = &stmt.label {
+                    self.write(" ");
+                    self.write(&label.0);
+                }
+                self.write(";");
+            },
+            Directive::WithStatement(stmt) => {
+                self.write("with (");
+                self.print_expression(&stmt.object);
+                self.write(")");
+                self.write_substatement(&stmt.body);
+            },
+            Directive::ReturnStatement(stmt) => {
+                self.write("return");
+                if let Some(exp) = &stmt.expression {
+                    self.write(" ");
+                    self.print_expression(exp);
+                }
+                self.write(";");
+            },
+            Directive::ThrowStatement(stmt) => {
+                self.write("throw ");
+                self.print_expression(&stmt.expression);
+                self.write(";");
+            },
+            Directive::DefaultXmlNamespaceStatement(stmt) => {
+                self.write("default xml namespace = ");
+                self.write_expression(&stmt.right, OperatorPrecedence::AssignmentAndOther);
+                self.write(";");
+            },
+            Directive::TryStatement(stmt) => {
+                self.write("try");
+                self.write_braced_block(&stmt.block);
+                for catch_clause in &stmt.catch_clauses {
+                    self.write_brace_separator();
+                    self.write("catch (");
+                    self.write_typed_destructuring(&catch_clause.parameter);
+                    self.write(")");
+                    self.write_braced_block(&catch_clause.block);
+                }
+                if let Some(finally_clause) = &stmt.finally_clause {
+                    self.write_brace_separator();
+                    self.write("finally");
+                    self.write_braced_block(&finally_clause.block);
+                }
+            },
+            Directive::Invalidated(_) => {},
+            Directive::ConfigurationDirective(cfg) => {
+                self.write(&cfg.namespace.0);
+                self.write("::");
+                self.write(&cfg.constant_name.0);
+                self.write(" ");
+                self.write_directive(&cfg.directive);
+            },
+            Directive::ImportDirective(imp) => {
+                self.write("import ");
+                if let Some(alias) = &imp.alias {
+                    self.write(&alias.0);
+                    self.write(" = ");
+                }
+                self.write_import_name(&imp.package_name, &imp.import_specifier);
+                self.write(";");
+            },
+            Directive::UseNamespaceDirective(directive) => {
+                self.write("use namespace ");
+                self.print_expression(&directive.expression);
+                self.write(";");
+            },
+            Directive::IncludeDirective(directive) => {
+                self.write("include ");
+                self.write_string_literal(&directive.source);
+                self.write(";");
+            },
+            Directive::PackageConcatDirective(directive) => {
+                self.write("public += ");
+                self.write_import_name(&directive.package_name, &directive.import_specifier);
+                self.write(";");
+            },
+            Directive::DirectiveInjection(inj) => {
+                let directives = inj.directives.borrow();
+                for (i, directive) in directives.iter().enumerate() {
+                    if i != 0 {
+                        self.write("\n");
+                        self.write_indent();
+                    }
+                    self.write_directive(directive);
+                }
+            },
+            Directive::VariableDefinition(defn) => {
+                self.write_attributes(&defn.attributes, &defn.kind.1);
+                self.write_variable_definition(&defn.kind.0, &defn.bindings);
+                self.write(";");
+            },
+            Directive::FunctionDefinition(defn) => {
+                self.write_attributes(&defn.attributes, &defn.name.location());
+                self.write("function ");
+                match &defn.name {
+                    FunctionName::Getter(name) => {
+                        self.write("get ");
+                        self.write(&name.0);
+                    },
+                    FunctionName::Setter(name) => {
+                        self.write("set ");
+                        self.write(&name.0);
+                    },
+                    FunctionName::Identifier(name) |
+                    FunctionName::Constructor(name) => self.write(&name.0),
+                }
+                self.write_function_common(&defn.common);
+                if !defn.common.has_block_body() {
+                    self.write(";");
+                }
+            },
+            Directive::ClassDefinition(defn) => {
+                self.write_attributes(&defn.attributes, &defn.name.1);
+                self.write("class ");
+                self.write(&defn.name.0);
+                self.write_type_parameters(&defn.type_parameters);
+                if let Some(extends_clause) = &defn.extends_clause {
+                    self.write(" extends ");
+                    self.print_expression(extends_clause);
+                }
+                if let Some(implements_clause) = &defn.implements_clause {
+                    self.write(" implements ");
+                    self.write_expression_list(implements_clause, OperatorPrecedence::AssignmentAndOther);
+                }
+                self.write_braced_block(&defn.block);
+            },
+            Directive::EnumDefinition(defn) => {
+                if defn.is_set {
+                    self.write("[Set]\n");
+                    self.write_indent();
+                }
+                self.write_attributes(&defn.attributes, &defn.name.1);
+                self.write("enum ");
+                self.write(&defn.name.0);
+                if let Some(as_clause) = &defn.as_clause {
+                    self.write(" as ");
+                    self.print_expression(as_clause);
+                }
+                self.write_braced_block(&defn.block);
+            },
+            Directive::InterfaceDefinition(defn) => {
+                self.write_attributes(&defn.attributes, &defn.name.1);
+                self.write("interface ");
+                self.write(&defn.name.0);
+                self.write_type_parameters(&defn.type_parameters);
+                if let Some(extends_clause) = &defn.extends_clause {
+                    self.write(" extends ");
+                    self.write_expression_list(extends_clause, OperatorPrecedence::AssignmentAndOther);
+                }
+                self.write_braced_block(&defn.block);
+            },
+            Directive::TypeDefinition(defn) => {
+                self.write_attributes(&defn.attributes, &defn.left.1);
+                self.write("type ");
+                self.write(&defn.left.0);
+                self.write(" = ");
+                self.print_expression(&defn.right);
+                self.write(";");
+            },
+            Directive::NamespaceDefinition(defn) => {
+                self.write_attributes(&defn.attributes, &defn.left.1);
+                self.write("namespace ");
+                self.write(&defn.left.0);
+                if let Some(right) = &defn.right {
+                    self.write(" = ");
+                    self.write_expression(right, OperatorPrecedence::AssignmentAndOther);
+                }
+                self.write(";");
+            },
+        }
+    }
+
+    fn write_if_statement(&mut self, stmt: &IfStatement) {
+        self.write("if (");
+        self.print_expression(&stmt.test);
+        self.write(")");
+        let Some(alternative) = &stmt.alternative else {
+            self.write_substatement(&stmt.consequent);
+            return;
+        };
+        // Prevent the `else` clause from binding to a nested `if` statement.
+        if Self::ends_with_open_if(&stmt.consequent) {
+            self.write(" {\n");
+            self.indent += 1;
+            self.print_directive(&stmt.consequent);
+            self.indent -= 1;
+            self.write_indent();
+            self.write("} else");
+        } else {
+            self.write_substatement(&stmt.consequent);
+            self.write_after_substatement(&stmt.consequent, "else");
+        }
+        if let Directive::IfStatement(alternative) = alternative.as_ref() {
+            self.write(" ");
+            self.write_if_statement(alternative);
+        } else {
+            self.write_substatement(alternative);
+        }
+    }
+
+    fn ends_with_open_if(directive: &Rc<Directive>) -> bool {
+        match directive.as_ref() {
+            Directive::IfStatement(stmt) => {
+                stmt.alternative.as_ref().is_none_or(Self::ends_with_open_if)
+            },
+            Directive::LabeledStatement(stmt) => Self::ends_with_open_if(&stmt.substatement),
+            Directive::WhileStatement(stmt) => Self::ends_with_open_if(&stmt.body),
+            Directive::ForStatement(stmt) => Self::ends_with_open_if(&stmt.body),
+            Directive::ForInStatement(stmt) => Self::ends_with_open_if(&stmt.body),
+            Directive::WithStatement(stmt) => Self::ends_with_open_if(&stmt.body),
+            Directive::ConfigurationDirective(cfg) => Self::ends_with_open_if(&cfg.directive),
+            _ => false,
+        }
+    }
+
+    /// Determines whether an expression statement would be
+    /// ambiguous with a block or a function definition.
+    fn starts_like_directive(exp: &Rc<Expression>) -> bool {
+        match exp.as_ref() {
+            Expression::ObjectInitializer(_) |
+            Expression::Function(_) => true,
+            Expression::Member(e) => Self::starts_like_directive(&e.base),
+            Expression::ComputedMember(e) => Self::starts_like_directive(&e.base),
+            Expression::Descendants(e) => Self::starts_like_directive(&e.base),
+            Expression::Filter(e) => Self::starts_like_directive(&e.base),
+            Expression::Call(e) => Self::starts_like_directive(&e.base),
+            Expression::WithTypeArguments(e) => Self::starts_like_directive(&e.base),
+            Expression::OptionalChaining(e) => Self::starts_like_directive(&e.base),
+            Expression::Unary(e) => Self::unary_is_postfix(e.operator) && Self::starts_like_directive(&e.expression),
+            Expression::Binary(e) => Self::starts_like_directive(&e.left),
+            Expression::Conditional(e) => Self::starts_like_directive(&e.test),
+            Expression::Assignment(e) => Self::starts_like_directive(&e.left),
+            Expression::Sequence(e) => Self::starts_like_directive(&e.left),
+            _ => false,
+        }
+    }
+
+    fn write_import_name(&mut self, package_name: &[(String, Location)], specifier: &ImportSpecifier) {
+        self.write_names(package_name);
+        if !package_name.is_empty() {
+            self.write(".");
+        }
+        match specifier {
+            ImportSpecifier::Wildcard(_) => self.write("*"),
+            ImportSpecifier::Recursive(_) => self.write("**"),
+            ImportSpecifier::Identifier(name) => self.write(&name.0),
+        }
+    }
+
+    /// Writes the attributes of a definition, followed by any comments
+    /// between them and the definition name.
+    fn write_attributes(&mut self, attributes: &[Attribute], name_location: &Location) {
+        for attribute in attributes.iter().filter(|a| a.is_metadata()) {
+            let Attribute::Metadata(metadata) = attribute else { unreachable!() };
+            self.write_header_comments_before(&metadata.location);
+            self.write_metadata(metadata);
+            self.write("\n");
+            self.write_indent();
+        }
+        self.write_header_comments_before(name_location);
+        for attribute in attributes {
+            match attribute {
+                Attribute::Metadata(_) => continue,
+                Attribute::Expression(exp) => self.write_expression(exp, OperatorPrecedence::Postfix),
+                Attribute::Public(_) => self.write("public"),
+                Attribute::Private(_) => self.write("private"),
+                Attribute::Protected(_) => self.write("protected"),
+                Attribute::Internal(_) => self.write("internal"),
+                Attribute::Final(_) => self.write("final"),
+                Attribute::Native(_) => self.write("native"),
+                Attribute::Static(_) => self.write("static"),
+                Attribute::Abstract(_) => self.write("abstract"),
+                Attribute::Override(_) => self.write("override"),
+                Attribute::Dynamic(_) => self.write("dynamic"),
+            }
+            self.write(" ");
+        }
+    }
+
+    fn write_metadata(&mut self, metadata: &Metadata) {
+        self.write("[");
+        self.write(&metadata.name.0);
+        if let Some(entries) = &metadata.entries {
+            self.write("(");
+            for (i, entry) in entries.iter().enumerate() {
+                if i != 0 {
+                    self.write(", ");
+                }
+                if let Some(key) = &entry.key {
+                    self.write(&key.0);
+                    self.write("=");
+                }
+                match entry.value.as_ref() {
+                    MetadataValue::IdentifierString((value, _)) => self.write(value),
+                    MetadataValue::String((value, _)) => self.write_string_literal(value),
+                }
+            }
+            self.write(")");
+        }
+        self.write("]");
+    }
+
+    fn write_variable_definition(&mut self, kind: &VariableDefinitionKind, bindings: &[Rc<VariableBinding>]) {
+        self.write(match kind {
+            VariableDefinitionKind::Var => "var ",
+            VariableDefinitionKind::Const => "const ",
+        });
+        for (i, binding) in bindings.iter().enumerate() {
+            if i != 0 {
+                self.write(", ");
+            }
+            self.write_typed_destructuring(&binding.destructuring);
+            if let Some(init) = &binding.initializer {
+                self.write(" = ");
+                self.write_expression(init, OperatorPrecedence::AssignmentAndOther);
+            }
+        }
+    }
+
+    fn write_simple_variable_definition(&mut self, defn: &SimpleVariableDefinition) {
+        self.write_variable_definition(&defn.kind.0, &defn.bindings);
+    }
+
+    fn write_typed_destructuring(&mut self, destructuring: &TypedDestructuring) {
+        self.write_expression(&destructuring.destructuring, OperatorPrecedence::Postfix);
+        if let Some(t) = &destructuring.type_annotation {
+            self.write(": ");
+            self.print_expression(t);
+        }
+    }
+
+    fn write_type_parameters(&mut self, type_parameters: &Option<Vec<Rc<TypeParameter>>>) {
+        if let Some(type_parameters) = type_parameters {
+            self.write(".<");
+            let names = type_parameters.iter().map(|p| p.name.0.as_str()).collect::<Vec<_>>();
+            self.write(&names.join(", "));
+            self.write(">");
+        }
+    }
+
+    fn write_function_common(&mut self, common: &FunctionCommon) {
+        self.print_function_signature(&common.signature);
+        match &common.body {
+            Some(FunctionBody::Block(block)) => self.write_braced_block(block),
+            Some(FunctionBody::Lazy(body)) => {
+                self.write_brace_separator();
+                self.write(&body.location.text());
+            },
+            Some(FunctionBody::Expression(exp)) => {
+                self.write(" ");
+                if matches!(exp.as_ref(), Expression::ObjectInitializer(_)) {
+                    self.write("(");
+                    self.print_expression(exp);
+                    self.write(")");
+                } else {
+                    self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+                }
+            },
+            None => {},
+        }
+    }
+
+    fn write_arguments(&mut self, arguments: &[Rc<Expression>]) {
+        self.write_list(("(", ")"), false, arguments, |p, exp| p.write_expression(exp, OperatorPrecedence::AssignmentAndOther));
+    }
+
+    /// Writes a delimited list in a single line if it fits in
+    /// the maximum line width, otherwise one item per line.
+    fn write_list<T>(&mut self, (open, close): (&str, &str), padded: bool, items: &[T], write_item: impl Fn(&mut Self, &T)) {
+        let write_inline = |p: &mut Self| {
+            p.write(open);
+            if padded {
+                p.write(" ");
+            }
+            for (i, item) in items.iter().enumerate() {
+                if i != 0 {
+                    p.write(", ");
+                }
+                write_item(p, item);
+            }
+            if padded {
+                p.write(" ");
+            }
+            p.write(close);
+        };
+        if self.measuring {
+            write_inline(self);
+            return;
+        }
+        let mut inline = Printer::new(self.options.clone());
+        inline.indent = self.indent;
+        inline.no_in = self.no_in;
+        inline.measuring = true;
+        write_inline(&mut inline);
+        let width = inline.output.lines().next().map_or(0, |line| line.len());
+        if items.is_empty() || self.column() + width <= self.options.max_line_width {
+            write_inline(self);
+            return;
+        }
+        self.write(open);
+        self.write("\n");
+        self.indent += 1;
+        for (i, item) in items.iter().enumerate() {
+            self.write_indent();
+            write_item(self, item);
+            if i + 1 != items.len() {
+                self.write(",");
+            }
+            self.write("\n");
+        }
+        self.indent -= 1;
+        self.write_indent();
+        self.write(close);
+    }
+
+    fn write_expression_list(&mut self, list: &[Rc<Expression>], precedence: OperatorPrecedence) {
+        for (i, exp) in list.iter().enumerate() {
+            if i != 0 {
+                self.write(", ");
+            }
+            self.write_expression(exp, precedence);
+        }
+    }
+
+    fn write_elements(&mut self, elements: &[Element]) {
+        if !elements.iter().any(|e| matches!(e, Element::Elision)) {
+            self.write_list(("[", "]"), false, elements, |p, element| {
+                if let Element::Rest(_) = element {
+                    p.write("...");
+                }
+                let (Element::Expression(exp) | Element::Rest((exp, _))) = element else { unreachable!() };
+                p.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+            });
+            return;
+        }
+        self.write("[");
+        for (i, element) in elements.iter().enumerate() {
+            match element {
+                Element::Elision => {
+                    self.write(",");
+                    continue;
+                },
+                Element::Expression(exp) => self.write_expression(exp, OperatorPrecedence::AssignmentAndOther),
+                Element::Rest((exp, _)) => {
+                    self.write("...");
+                    self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+                },
+            }
+            if i + 1 != elements.len() {
+                self.write(", ");
+            }
+        }
+        self.write("]");
+    }
+
+    /// Writes an expression, parenthesizing it if its precedence
+    /// is lower than `precedence`.
+    fn write_expression(&mut self, exp: &Rc<Expression>, precedence: OperatorPrecedence) {
+        let parenthesize = !precedence.includes(&self.precedence_of(exp));
+        if parenthesize {
+            let no_in = self.no_in;
+            self.no_in = false;
+            self.write("(");
+            self.write_expression_unparenthesized(exp);
+            self.write(")");
+            self.no_in = no_in;
+        } else {
+            self.write_expression_unparenthesized(exp);
+        }
+    }
+
+    /// Writes the base of a postfix operation.
+    fn write_postfix_base(&mut self, base: &Rc<Expression>) {
+        match base.as_ref() {
+            // `new C()` may be followed by postfix operations,
+            // but `new C` would absorb them.
+            Expression::New(e) if e.arguments.is_some() => self.write_expression_unparenthesized(base),
+            // `1.x` would be scanned as a numeric literal, and
+            // `a?.b.c` would extend the optional chain.
+            Expression::NumericLiteral(_) |
+            Expression::OptionalChaining(_) => {
+                self.write("(");
+                self.write_expression_unparenthesized(base);
+                self.write(")");
+            },
+            _ => self.write_expression(base, OperatorPrecedence::Postfix),
+        }
+    }
+
+    fn precedence_of(&self, exp: &Rc<Expression>) -> OperatorPrecedence {
+        match exp.as_ref() {
+            Expression::New(_) => OperatorPrecedence::Unary,
+            Expression::Function(_) => OperatorPrecedence::AssignmentAndOther,
+            Expression::Unary(e) => {
+                if Self::unary_is_postfix(e.operator) {
+                    OperatorPrecedence::Postfix
+                } else if e.operator == Operator::Yield {
+                    OperatorPrecedence::AssignmentAndOther
+                } else {
+                    OperatorPrecedence::Unary
+                }
+            },
+            Expression::Binary(e) => {
+                if self.no_in && matches!(e.operator, Operator::In | Operator::NotIn) {
+                    // Forces parentheses
+                    return OperatorPrecedence::List;
+                }
+                BinaryOperator::try_from(e.operator).unwrap().precedence()
+            },
+            Expression::Conditional(_) |
+            Expression::Assignment(_) => OperatorPrecedence::AssignmentAndOther,
+            Expression::Sequence(_) => OperatorPrecedence::List,
+            _ => OperatorPrecedence::Postfix,
+        }
+    }
+
+    fn unary_is_postfix(operator: Operator) -> bool {
+        matches!(operator, Operator::PostIncrement | Operator::PostDecrement | Operator::NonNull)
+    }
+
+    fn write_expression_unparenthesized(&mut self, exp: &Rc<Expression>) {
+        match exp.as_ref() {
+            Expression::QualifiedIdentifier(id) => self.write_qualified_identifier(id),
+            Expression::Paren(e) => {
+                let no_in = self.no_in;
+                self.no_in = false;
+                self.write("(");
+                self.print_expression(&e.expression);
+                self.write(")");
+                self.no_in = no_in;
+            },
+            Expression::NullLiteral(_) => self.write("null"),
+            Expression::BooleanLiteral(e) => self.write(if e.value { "true" } else { "false" }),
+            Expression::NumericLiteral(e) => {
+                self.write(&e.value);
+                if matches!(e.suffix, NumberSuffix::F) {
+                    self.write("f");
+                }
+            },
+            Expression::StringLiteral(e) => self.write_string_literal(&e.value),
+            Expression::ThisLiteral(_) => self.write("this"),
+            Expression::RegExpLiteral(e) => {
+                self.write("/");
+                self.write(&e.body);
+                self.write("/");
+                self.write(&e.flags);
+            },
+            Expression::Xml(e) => self.write_xml_element(&e.element),
+            Expression::XmlMarkup(e) => self.write(&e.markup),
+            Expression::XmlList(e) => {
+                self.write("<>");
+                self.write_xml_content(&e.content);
+                self.write("</>");
+            },
+            Expression::ArrayLiteral(e) => self.write_elements(&e.elements),
+            Expression::VectorLiteral(e) => {
+                self.write("new <");
+                self.print_expression(&e.element_type);
+                self.write(">");
+                self.write_elements(&e.elements);
+            },
+            Expression::ObjectInitializer(e) => {
+                if e.fields.is_empty() {
+                    self.write("{}");
+                    return;
+                }
+                self.write_list(("{", "}"), true, &e.fields, |p, field| p.write_initializer_field(field));
+            },
+            Expression::Function(e) => {
+                self.write("function");
+                if let Some(name) = &e.name {
+                    self.write(" ");
+                    self.write(&name.0);
+                }
+                self.write_function_common(&e.common);
+            },
+            Expression::ImportMeta(_) => self.write("import.meta"),
+            Expression::New(e) => {
+                self.write("new ");
+                self.write_new_base(&e.base);
+                if let Some(arguments) = &e.arguments {
+                    self.write_arguments(arguments);
+                }
+            },
+            Expression::Member(e) => {
+                if !matches!(e.base.as_ref(), Expression::OptionalChainingPlaceholder(_)) {
+                    self.write_postfix_base(&e.base);
+                    self.write(".");
+                }
+                self.write_qualified_identifier(&e.identifier);
+            },
+            Expression::ComputedMember(e) => {
+                self.write_postfix_base(&e.base);
+                self.write("[");
+                self.print_expression(&e.key);
+                self.write("]");
+            },
+            Expression::Descendants(e) => {
+                self.write_postfix_base(&e.base);
+                self.write("..");
+                self.write_qualified_identifier(&e.identifier);
+            },
+            Expression::Filter(e) => {
+                self.write_postfix_base(&e.base);
+                self.write(".(");
+                self.print_expression(&e.test);
+                self.write(")");
+            },
+            Expression::Super(e) => {
+                self.write("super");
+                if let Some(object) = &e.object {
+                    self.write_arguments(object);
+                }
+            },
+            Expression::Call(e) => {
+                self.write_postfix_base(&e.base);
+                self.write_arguments(&e.arguments);
+            },
+            Expression::WithTypeArguments(e) => {
+                self.write_postfix_base(&e.base);
+                self.write(".<");
+                self.write_expression_list(&e.arguments, OperatorPrecedence::AssignmentAndOther);
+                self.write(">");
+            },
+            Expression::Unary(e) => self.write_unary_expression(e),
+            Expression::OptionalChaining(e) => {
+                self.write_postfix_base(&e.base);
+                self.write("?.");
+                self.write_expression(&e.expression, OperatorPrecedence::Postfix);
+            },
+            Expression::OptionalChainingPlaceholder(_) => {},
+            Expression::Binary(e) => {
+                let operator = BinaryOperator::try_from(e.operator).unwrap();
+                let left_precedence = if operator.associativity() == BinaryAssociativity::LeftToRight {
+                    operator.precedence()
+                } else {
+                    operator.precedence().add(1).unwrap()
+                };
+                self.write_expression(&e.left, left_precedence);
+                self.write(" ");
+                self.write(Self::binary_operator_symbol(e.operator));
+                self.write(" ");
+                self.write_expression(&e.right, operator.right_precedence());
+            },
+            Expression::Conditional(e) => {
+                self.write_expression(&e.test, OperatorPrecedence::LogicalOrAndOther);
+                self.write(" ? ");
+                self.write_expression(&e.consequent, OperatorPrecedence::AssignmentAndOther);
+                self.write(" : ");
+                self.write_expression(&e.alternative, OperatorPrecedence::AssignmentAndOther);
+            },
+            Expression::Assignment(e) => {
+                self.write_expression(&e.left, OperatorPrecedence::Postfix);
+                self.write(" ");
+                if let Some(compound) = e.compound {
+                    self.write(Self::binary_operator_symbol(compound));
+                }
+                self.write("= ");
+                self.write_expression(&e.right, OperatorPrecedence::AssignmentAndOther);
+            },
+            Expression::Sequence(e) => {
+                self.write_expression(&e.left, OperatorPrecedence::List);
+                self.write(", ");
+                self.write_expression(&e.right, OperatorPrecedence::AssignmentAndOther);
+            },
+            Expression::NullableType(e) => {
+                self.write_type_base(&e.base);
+                self.write("?");
+            },
+            Expression::NonNullableType(e) => {
+                self.write_type_base(&e.base);
+                self.write("!");
+            },
+            Expression::AnyType(_) => self.write("*"),
+            Expression::VoidType(_) => self.write("void"),
+            Expression::ArrayType(e) => {
+                self.write("[");
+                self.print_expression(&e.expression);
+                self.write("]");
+            },
+            Expression::TupleType(e) => {
+                self.write("[");
+                self.write_expression_list(&e.expressions, OperatorPrecedence::AssignmentAndOther);
+                self.write("]");
+            },
+            Expression::FunctionType(e) => {
+                self.write("function(");
+                for (i, param) in e.parameters.iter().enumerate() {
+                    if i != 0 {
+                        self.write(", ");
+                    }
+                    if matches!(param.kind, ParameterKind::Rest) {
+                        self.write("...");
+                    }
+                    if let Some(t) = &param.type_expression {
+                        self.print_expression(t);
+                    }
+                    if matches!(param.kind, ParameterKind::Optional) {
+                        self.write("=");
+                    }
+                }
+                self.write(")");
+                if let Some(result_type) = &e.result_type {
+                    self.write(": ");
+                    self.print_expression(result_type);
+                }
+            },
+            Expression::Invalidated(_) => {},
+            Expression::ReservedNamespace(e) => self.write(match e {
+                ReservedNamespaceExpression::Public(_) => "public",
+                ReservedNamespaceExpression::Private(_) => "private",
+                ReservedNamespaceExpression::Protected(_) => "protected",
+                ReservedNamespaceExpression::Internal(_) => "internal",
+            }),
+        }
+    }
+
+    /// Writes the base of a `new` expression, which is
+    /// restricted to member accesses and type arguments.
+    fn write_new_base(&mut self, base: &Rc<Expression>) {
+        fn is_new_base(exp: &Expression) -> bool {
+            match exp {
+                Expression::Member(e) => is_new_base(&e.base),
+                Expression::ComputedMember(e) => is_new_base(&e.base),
+                Expression::WithTypeArguments(e) => is_new_base(&e.base),
+                Expression::Super(e) => e.object.is_none(),
+                Expression::New(e) => e.arguments.is_some() && is_new_base(&e.base),
+                Expression::Call(_) |
+                Expression::Descendants(_) |
+                Expression::Filter(_) |
+                Expression::OptionalChaining(_) |
+                Expression::Unary(_) |
+                Expression::Binary(_) |
+                Expression::Conditional(_) |
+                Expression::Assignment(_) |
+                Expression::Sequence(_) |
+                Expression::Function(_) |
+                Expression::NumericLiteral(_) => false,
+                _ => true,
+            }
+        }
+        if is_new_base(base) {
+            self.write_expression_unparenthesized(base);
+        } else {
+            self.write("(");
+            self.print_expression(base);
+            self.write(")");
+        }
+    }
+
+    fn write_type_base(&mut self, base: &Rc<Expression>) {
+        if matches!(base.as_ref(), Expression::FunctionType(_)) {
+            self.write("(");
+            self.print_expression(base);
+            self.write(")");
+        } else {
+            self.print_expression(base);
+        }
+    }
+
+    fn write_unary_expression(&mut self, e: &UnaryExpression) {
+        if Self::unary_is_postfix(e.operator) {
+            self.write_expression(&e.expression, OperatorPrecedence::Postfix);
+            self.write(match e.operator {
+                Operator::PostIncrement => "++",
+                Operator::PostDecrement => "--",
+                _ => "!",
+            });
+            return;
+        }
+        let (symbol, operand_precedence) = match e.operator {
+            Operator::Delete => ("delete ", OperatorPrecedence::Postfix),
+            Operator::Void => ("void ", OperatorPrecedence::Unary),
+            Operator::Typeof => ("typeof ", OperatorPrecedence::Unary),
+            Operator::Await => ("await ", OperatorPrecedence::Unary),
+            Operator::Yield => ("yield ", OperatorPrecedence::AssignmentAndOther),
+            Operator::PreIncrement => ("++", OperatorPrecedence::Postfix),
+            Operator::PreDecrement => ("--", OperatorPrecedence::Postfix),
+            Operator::Positive => ("+", OperatorPrecedence::Unary),
+            Operator::Negative => ("-", OperatorPrecedence::Unary),
+            Operator::BitwiseNot => ("~", OperatorPrecedence::Unary),
+            Operator::LogicalNot => ("!", OperatorPrecedence::Unary),
+            _ => unreachable!(),
+        };
+        self.write(symbol);
+        // Avoid forming `++` or `--` out of two operators.
+        if let Expression::Unary(operand) = e.expression.as_ref() {
+            let plus = matches!(e.operator, Operator::Positive | Operator::PreIncrement)
+                && matches!(operand.operator, Operator::Positive | Operator::PreIncrement);
+            let minus = matches!(e.operator, Operator::Negative | Operator::PreDecrement)
+                && matches!(operand.operator, Operator::Negative | Operator::PreDecrement);
+            if plus || minus {
+                self.write(" ");
+            }
+        }
+        self.write_expression(&e.expression, operand_precedence);
+    }
+
+    fn binary_operator_symbol(operator: Operator) -> &'static str {
+        match operator {
+            Operator::Power => "**",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Remainder => "%",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::ShiftLeft => "<<",
+            Operator::ShiftRight => ">>",
+            Operator::ShiftRightUnsigned => ">>>",
+            Operator::Lt => "<",
+            Operator::Gt => ">",
+            Operator::Le => "<=",
+            Operator::Ge => ">=",
+            Operator::Instanceof => "instanceof",
+            Operator::In => "in",
+            Operator::NotIn => "not in",
+            Operator::Is => "is",
+            Operator::IsNot => "is not",
+            Operator::As => "as",
+            Operator::Equals => "==",
+            Operator::NotEquals => "!=",
+            Operator::StrictEquals => "===",
+            Operator::StrictNotEquals => "!==",
+            Operator::BitwiseAnd => "&",
+            Operator::BitwiseXor => "^",
+            Operator::BitwiseOr => "|",
+            Operator::LogicalAnd => "&&",
+            Operator::LogicalXor => "^^",
+            Operator::LogicalOr => "||",
+            Operator::NullCoalescing => "??",
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_qualified_identifier(&mut self, id: &QualifiedIdentifier) {
+        if id.attribute {
+            self.write("@");
+        }
+        if let Some(qualifier) = &id.qualifier {
+            self.write_expression(qualifier, OperatorPrecedence::Postfix);
+            self.write("::");
+        }
+        match &id.id {
+            QualifiedIdentifierIdentifier::Id((name, _)) => self.write(name),
+            QualifiedIdentifierIdentifier::Brackets(exp) => {
+                self.write("[");
+                self.print_expression(exp);
+                self.write("]");
+            },
+        }
+    }
+
+    fn write_initializer_field(&mut self, field: &InitializerField) {
+        match field {
+            InitializerField::Field { name, non_null, value } => {
+                match &name.0 {
+                    FieldName::Identifier(id) => self.write_qualified_identifier(id),
+                    FieldName::Brackets(exp) => {
+                        self.write("[");
+                        self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+                        self.write("]");
+                    },
+                    FieldName::StringLiteral(exp) |
+                    FieldName::NumericLiteral(exp) => self.print_expression(exp),
+                }
+                if *non_null {
+                    self.write("!");
+                }
+                if let Some(value) = value {
+                    self.write(": ");
+                    self.write_expression(value, OperatorPrecedence::AssignmentAndOther);
+                }
+            },
+            InitializerField::Rest((exp, _)) => {
+                self.write("...");
+                self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+            },
+        }
+    }
+
+    fn write_string_literal(&mut self, value: &str) {
+        self.output.push('"');
+        for ch in value.chars() {
+            match ch {
+                '"' => self.write("\\\""),
+                '\\' => self.write("\\\\"),
+                '\n' => self.write("\\n"),
+                '\r' => self.write("\\r"),
+                '\t' => self.write("\\t"),
+                '\u{8}' => self.write("\\b"),
+                '\u{C}' => self.write("\\f"),
+                '\u{B}' => self.write("\\v"),
+                '\u{2028}' | '\u{2029}' => self.write(&format!("\\u{:04X}", ch as u32)),
+                _ if (ch as u32) < 0x20 => self.write(&format!("\\x{:02X}", ch as u32)),
+                _ => self.output.push(ch),
+            }
+        }
+        self.output.push('"');
+    }
+
+    fn write_xml_element(&mut self, element: &XmlElement) {
+        self.write("<");
+        self.write_xml_tag_name(&element.name);
+        for attribute in &element.attributes {
+            self.write(" ");
+            self.write(&attribute.name.0);
+            self.write("=");
+            match &attribute.value {
+                XmlAttributeValue::Value((value, _)) => {
+                    let delimiter = if value.contains('"') { "'" } else { "\"" };
+                    self.write(delimiter);
+                    self.write(value);
+                    self.write(delimiter);
+                },
+                XmlAttributeValue::Expression(exp) => {
+                    self.write("{");
+                    self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+                    self.write("}");
+                },
+            }
+        }
+        if let Some(exp) = &element.attribute_expression {
+            self.write(" {");
+            self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+            self.write("}");
+        }
+        let Some(content) = &element.content else {
+            self.write("/>");
+            return;
+        };
+        self.write(">");
+        self.write_xml_content(content);
+        self.write("</");
+        if let Some(closing_name) = &element.closing_name {
+            self.write_xml_tag_name(closing_name);
+        } else {
+            self.write_xml_tag_name(&element.name);
+        }
+        self.write(">");
+    }
+
+    fn write_xml_tag_name(&mut self, name: &XmlTagName) {
+        match name {
+            XmlTagName::Name((name, _)) => self.write(name),
+            XmlTagName::Expression(exp) => {
+                self.write("{");
+                self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+                self.write("}");
+            },
+        }
+    }
+
+    fn write_xml_content(&mut self, content: &[Rc<XmlContent>]) {
+        for node in content {
+            match node.as_ref() {
+                XmlContent::Characters((text, _)) |
+                XmlContent::Markup((text, _)) => self.write(text),
+                XmlContent::Element(element) => self.write_xml_element(element),
+                XmlContent::Expression(exp) => {
+                    self.write("{");
+                    self.write_expression(exp, OperatorPrecedence::AssignmentAndOther);
+                    self.write("}");
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    fn print(text: &str) -> String {
+        let cu = CompilationUnit::new(None, text.into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        let diagnostics = cu.nested_diagnostics().iter().map(|d| d.format_english()).collect::<Vec<_>>();
+        assert!(diagnostics.is_empty(), "{diagnostics:?}\n{text}");
+        PrinterFacade(default()).print_program(&program)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let source = r#"
+            package com.example {
+                import flash.display.*;
+                import X = com.other.Y;
+                [Event(name="change", type="flash.events.Event")]
+                public final class C.<T> extends B implements I, J {
+                    private static const K: Number = 1e3;
+                    protected var m: Vector.<T>? = new <T>[];
+                    public function C(a: int, b: String = "x\n", ...rest) {
+                        super(a);
+                        var { x, y: [z, ...w] }: Object = o;
+                        for (var i: int = 0, j = ("k" in o); i < 10; i++) continue;
+                        for each (var v in list) trace(v);
+                        if (a) if (b) f(); else g(); else h();
+                        switch type (v) {
+                            case (s: String) { trace(s) }
+                            default { trace("other") }
+                        }
+                        switch (a) { case 1: case 2: break; default: return; }
+                        try { throw new Error("e") } catch (e: Error) {} finally {}
+                        do x--; while (x > 0)
+                        ({}).toString();
+                        (function() {})();
+                        outer: while (true) break outer;
+                    }
+                    public function get value(): * { return a?.b.c?.[0]?.(1) ?? -(-x) }
+                    override protected function set value(v): void {}
+                }
+            }
+            enum E { const A = "a"; const B }
+            type F = function(int, String=, ...): ?Array;
+            namespace ns = "http://example.com";
+            use namespace ns;
+            x = (a, b);
+            y = (a + b) * c - (d - e) ** (f ** g) ** h;
+            z = a ? b : (c ? d : e), w = (new C).m, u = new C().m, v = new (f())();
+            q = ns::x.@y..z.(@id == 1).*[0] is not T;
+            xml = <a b="1" c={d}>{e} text <c/></a>;
+            list = <><x/></>;
+            re = /a+b/gi;
+            arr = [1, ...r];
+            delete o[k], typeof - +x, void ~!y;
+        "#;
+        let printed = print(source);
+        assert_eq!(print(&printed), printed);
+        assert!(printed.contains("y = (a + b) * c - (d - e) ** (f ** g) ** h;"));
+        assert!(printed.contains("(new C).m"));
+        assert!(printed.contains("a?.b.c?.[0]?.(1) ?? -(-x)"));
+    }
+
+    #[test]
+    fn test_required_parentheses() {
+        struct Unparenthesize;
+
+        impl Folder for Unparenthesize {
+            fn fold_expression(&mut self, node: &Rc<Expression>) -> Rc<Expression> {
+                match node.as_ref() {
+                    Expression::Paren(e) => self.fold_expression(&e.expression),
+                    _ => rebuild_expression(self, node),
+                }
+            }
+        }
+
+        for (source, expected) in [
+            ("(a + b) * (c * d)", "(a + b) * (c * d)"),
+            ("(a * b) * c", "a * b * c"),
+            ("(a - b) - (c - d)", "a - b - (c - d)"),
+            ("(a ** b) ** (c ** d)", "(a ** b) ** c ** d"),
+            ("(a || b) ?? (c || d)", "a || b ?? (c || d)"),
+            ("(a ? b : c) ? (d, e) : (f = g)", "(a ? b : c) ? (d, e) : f = g"),
+            ("(new C)(x), (new C(x))(y), new (f())", "(new C)(x), new C(x)(y), new (f())"),
+            ("-(-x), +(++x), !(!x), (x++).y, (1).z", "- -x, + ++x, !!x, x++.y, (1).z"),
+            ("(a?.b).c, (a?.b)!", "(a?.b).c, a?.b!"),
+        ] {
+            let cu = CompilationUnit::new(None, source.into());
+            let exp = ParserFacade(&cu, default()).parse_expression();
+            let exp = Unparenthesize.fold_expression(&exp);
+            assert_eq!(PrinterFacade(default()).print_expression(&exp), expected);
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Defines editor services over parsed ActionScript 3 programs.
+
+mod semantic_token;
+pub use semantic_token::*;
+mod outline;
+pub use outline::*;
+mod node_lookup;
+pub use node_lookup::*;
+mod definition_diff;
+pub use definition_diff::*;
+mod api_compatibility;
+pub use api_compatibility::*;
+mod structural_search;
+pub use structural_search::*;
+mod structural_rewrite;
+pub use structural_rewrite::*;
+mod project;
+pub use project::*;
\ No newline at end of file
@@ -0,0 +1,364 @@
+use crate::ns::*;
+use serde::Serialize;
+
+/// A change that breaks code using the public API of a library.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ApiBreakingChange {
+    pub kind: ApiBreakingChangeKind,
+    /// Qualified name of the definition, such as `p.C.f`.
+    pub name: String,
+    pub old_location: Option<Location>,
+    pub new_location: Option<Location>,
+    /// Whether the old definition was marked with `[Deprecated]`
+    /// metadata or the `@deprecated` ASDoc tag.
+    pub deprecated: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum ApiBreakingChangeKind {
+    /// A public or protected definition was removed.
+    Removed,
+    /// A public definition became protected or less visible, or
+    /// a protected definition became less visible.
+    VisibilityNarrowed,
+    /// The parameter list or result type of a function, the type of a
+    /// property, whether a property is writable, or the base types
+    /// of a type changed.
+    SignatureChanged,
+    /// A class or method became `final`.
+    BecameFinal,
+    /// A member was added to an interface.
+    InterfaceMemberAdded,
+    /// The value or position of an enum member changed.
+    EnumMemberChanged,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum ApiGroup {
+    Type,
+    Function,
+    Property,
+    Namespace,
+    EnumMember,
+}
+
+/// A definition, with location-insensitive digests of its signature.
+struct ApiEntry {
+    name: String,
+    group: ApiGroup,
+    is_static: bool,
+    location: Location,
+    /// 2 if public, 1 if protected, 0 otherwise.
+    rank: u8,
+    /// Whether the definition and its enclosing definitions are public
+    /// or protected, and not hidden with the `@private` ASDoc tag.
+    api: bool,
+    is_final: bool,
+    is_interface: bool,
+    deprecated: bool,
+    parent: Option<usize>,
+    signature: u64,
+    /// Digests of implemented or extended interfaces.
+    supertypes: Vec<u64>,
+    /// Types a property is read and written as, for properties.
+    readable: Option<u64>,
+    writable: Option<u64>,
+}
+
+type ApiKey = (String, ApiGroup, bool);
+
+#[derive(Default)]
+struct ApiIndex {
+    entries: Vec<ApiEntry>,
+    keys: HashMap<ApiKey, usize>,
+}
+
+impl ApiBreakingChange {
+    /// Reports the breaking changes to the public API between two versions
+    /// of the packages of a library, given every program of each version.
+    ///
+    /// Public definitions of packages and their public and protected members
+    /// form the API, excluding definitions hidden with the `@private` ASDoc tag.
+    /// Members of removed or narrowed definitions are not reported separately.
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    /// let old_cu = CompilationUnit::new(None, "package p { public class C { public function f(a: int): void {} } }".into());
+    /// let new_cu = CompilationUnit::new(None, "package p { public class C { public function f(a: int, b: int): void {} } }".into());
+    /// let old = ParserFacade(&old_cu, default()).parse_program();
+    /// let new = ParserFacade(&new_cu, default()).parse_program();
+    /// let changes = ApiBreakingChange::check(&[old], &[new]);
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!((changes[0].kind, changes[0].name.as_str()), (ApiBreakingChangeKind::SignatureChanged, "p.C.f"));
+    /// ```
+    pub fn check(old: &[Rc<Program>], new: &[Rc<Program>]) -> Vec<ApiBreakingChange> {
+        let old = ApiIndex::new(old);
+        let new = ApiIndex::new(new);
+        let mut changes = vec![];
+        // Definitions whose members are not reported.
+        let mut reported = vec![false; old.entries.len()];
+
+        for (i, entry) in old.entries.iter().enumerate() {
+            if !entry.api || entry.parent.is_some_and(|parent| reported[parent]) {
+                continue;
+            }
+            let key = (entry.name.clone(), entry.group, entry.is_static);
+            let Some(new_entry) = new.keys.get(&key).map(|j| &new.entries[*j]) else {
+                reported[i] = true;
+                changes.push(Self::change(ApiBreakingChangeKind::Removed, entry, None));
+                continue;
+            };
+            if !new_entry.api || new_entry.rank < entry.rank {
+                reported[i] = true;
+                changes.push(Self::change(ApiBreakingChangeKind::VisibilityNarrowed, entry, Some(new_entry)));
+                continue;
+            }
+            let signature_changed = entry.signature != new_entry.signature
+                || entry.supertypes.iter().any(|supertype| !new_entry.supertypes.contains(supertype))
+                || entry.readable.is_some_and(|readable| new_entry.readable != Some(readable))
+                || entry.writable.is_some_and(|writable| new_entry.writable != Some(writable));
+            if signature_changed {
+                let kind = if entry.group == ApiGroup::EnumMember { ApiBreakingChangeKind::EnumMemberChanged } else { ApiBreakingChangeKind::SignatureChanged };
+                changes.push(Self::change(kind, entry, Some(new_entry)));
+            }
+            if !entry.is_final && new_entry.is_final {
+                changes.push(Self::change(ApiBreakingChangeKind::BecameFinal, entry, Some(new_entry)));
+            }
+        }
+
+        // Members added to existing interfaces.
+        for entry in &new.entries {
+            let Some(parent) = entry.parent.map(|parent| &new.entries[parent]) else {
+                continue;
+            };
+            let old_parent = old.keys.get(&(parent.name.clone(), parent.group, parent.is_static)).map(|i| &old.entries[*i]);
+            if !parent.is_interface || !old_parent.is_some_and(|old_parent| old_parent.api && old_parent.is_interface) {
+                continue;
+            }
+            if !old.keys.contains_key(&(entry.name.clone(), entry.group, entry.is_static)) {
+                changes.push(ApiBreakingChange {
+                    kind: ApiBreakingChangeKind::InterfaceMemberAdded,
+                    name: entry.name.clone(),
+                    old_location: None,
+                    new_location: Some(entry.location.clone()),
+                    deprecated: false,
+                });
+            }
+        }
+        changes
+    }
+
+    fn change(kind: ApiBreakingChangeKind, old: &ApiEntry, new: Option<&ApiEntry>) -> ApiBreakingChange {
+        ApiBreakingChange {
+            kind,
+            name: old.name.clone(),
+            old_location: Some(old.location.clone()),
+            new_location: new.map(|entry| entry.location.clone()),
+            deprecated: old.deprecated,
+        }
+    }
+}
+
+impl ApiIndex {
+    fn new(programs: &[Rc<Program>]) -> Self {
+        let mut index = Self::default();
+        for program in programs {
+            for package in OutlineSymbol::outline(program).iter().filter(|symbol| symbol.kind == OutlineSymbolKind::Package) {
+                index.symbols(&package.children, None, &package.name, OutlineSymbolKind::Package);
+            }
+        }
+        index
+    }
+
+    /// Indexes the definitions of a block, where `container` is the kind
+    /// of the enclosing definition.
+    fn symbols(&mut self, symbols: &[OutlineSymbol], parent: Option<usize>, prefix: &str, container: OutlineSymbolKind) {
+        let qualify = |name: &str| if prefix.is_empty() { name.to_owned() } else { format!("{prefix}.{name}") };
+        let mut enum_member_position = 0;
+        for symbol in symbols {
+            let name = qualify(&symbol.name);
+            let attributes = symbol.node.attributes();
+            let asdoc = &symbol.asdoc;
+            let location = symbol.location.clone();
+            match &symbol.node {
+                OutlineNode::Package(_) => self.symbols(&symbol.children, None, &symbol.name, OutlineSymbolKind::Package),
+                OutlineNode::VariableBinding(directive, i) => {
+                    let Directive::VariableDefinition(defn) = directive.as_ref() else {
+                        continue;
+                    };
+                    let binding = &defn.bindings[*i];
+                    if container == OutlineSymbolKind::Enum {
+                        // Members without a value are numbered by position.
+                        let signature = match &binding.initializer {
+                            Some(initializer) => initializer.structural_hash(),
+                            None => enum_member_position,
+                        };
+                        enum_member_position += 1;
+                        self.add(name, ApiGroup::EnumMember, location, parent, container, attributes, asdoc, signature, vec![]);
+                        continue;
+                    }
+                    let type_annotation = structural_digest(&binding.destructuring.type_annotation);
+                    let i = self.add(name, ApiGroup::Property, location, parent, container, attributes, asdoc, 0, vec![]);
+                    if Self::rank(container, attributes) > 0 {
+                        self.entries[i].readable = Some(type_annotation);
+                        if defn.kind.0 == VariableDefinitionKind::Var {
+                            self.entries[i].writable = Some(type_annotation);
+                        }
+                    }
+                },
+                OutlineNode::Definition(directive) => match directive.as_ref() {
+                    Directive::FunctionDefinition(defn) => {
+                        let signature = &defn.common.signature;
+                        match &defn.name {
+                            FunctionName::Getter(_) => {
+                                let i = self.add(name, ApiGroup::Property, location, parent, container, attributes, asdoc, 0, vec![]);
+                                if Self::rank(container, attributes) > 0 {
+                                    self.entries[i].readable = Some(structural_digest(&signature.result_type));
+                                }
+                            },
+                            FunctionName::Setter(_) => {
+                                let i = self.add(name, ApiGroup::Property, location, parent, container, attributes, asdoc, 0, vec![]);
+                                if Self::rank(container, attributes) > 0 {
+                                    let parameter = signature.parameters.first().map(|parameter| &parameter.destructuring.type_annotation);
+                                    self.entries[i].writable = Some(structural_digest(&parameter));
+                                }
+                            },
+                            _ => {
+                                // Parameter names and default values do not affect callers.
+                                let parameters: Vec<_> = signature.parameters.iter().map(|parameter| (parameter.kind, &parameter.destructuring.type_annotation)).collect();
+                                let digest = structural_digest(&(parameters, &signature.this_parameter, &signature.result_type));
+                                self.add(name, ApiGroup::Function, location, parent, container, attributes, asdoc, digest, vec![]);
+                            },
+                        }
+                    },
+                    Directive::ClassDefinition(defn) => {
+                        let signature = structural_digest(&(defn.type_parameters.as_ref().map_or(0, Vec::len), &defn.extends_clause));
+                        let supertypes = defn.implements_clause.iter().flatten().map(|t| t.structural_hash()).collect();
+                        let i = self.add(name.clone(), ApiGroup::Type, location, parent, container, attributes, asdoc, signature, supertypes);
+                        self.symbols(&symbol.children, Some(i), &name, OutlineSymbolKind::Class);
+                    },
+                    Directive::InterfaceDefinition(defn) => {
+                        let signature = structural_digest(&defn.type_parameters.as_ref().map_or(0, Vec::len));
+                        let supertypes = defn.extends_clause.iter().flatten().map(|t| t.structural_hash()).collect();
+                        let i = self.add(name.clone(), ApiGroup::Type, location, parent, container, attributes, asdoc, signature, supertypes);
+                        self.entries[i].is_interface = true;
+                        self.symbols(&symbol.children, Some(i), &name, OutlineSymbolKind::Interface);
+                    },
+                    Directive::EnumDefinition(defn) => {
+                        let signature = structural_digest(&(defn.is_set, &defn.as_clause));
+                        let i = self.add(name.clone(), ApiGroup::Type, location, parent, container, attributes, asdoc, signature, vec![]);
+                        self.symbols(&symbol.children, Some(i), &name, OutlineSymbolKind::Enum);
+                    },
+                    Directive::TypeDefinition(defn) => {
+                        let signature = defn.right.structural_hash();
+                        self.add(name, ApiGroup::Type, location, parent, container, attributes, asdoc, signature, vec![]);
+                    },
+                    Directive::NamespaceDefinition(defn) => {
+                        let signature = structural_digest(&defn.right);
+                        self.add(name, ApiGroup::Namespace, location, parent, container, attributes, asdoc, signature, vec![]);
+                    },
+                    _ => {},
+                },
+            }
+        }
+    }
+
+    /// Adds a definition, or merges it with the other accessors
+    /// of a property, returning its index.
+    #[allow(clippy::too_many_arguments)]
+    fn add(&mut self, name: String, group: ApiGroup, location: Location, parent: Option<usize>, container: OutlineSymbolKind, attributes: &[Attribute], asdoc: &Option<Rc<Asdoc>>, signature: u64, supertypes: Vec<u64>) -> usize {
+        let is_static = Attribute::find_static(attributes).is_some();
+        let rank = Self::rank(container, attributes);
+        let hidden = asdoc.as_ref().is_some_and(|asdoc| asdoc.tags.iter().any(|(tag, _)| matches!(tag, AsdocTag::Private)));
+        let deprecated = Attribute::find_metadata(attributes).iter().any(|metadata| metadata.name.0 == "Deprecated")
+            || asdoc.as_ref().is_some_and(|asdoc| asdoc.tags.iter().any(|(tag, _)| matches!(tag, AsdocTag::Deprecated { .. })));
+        let api = rank > 0 && !hidden && parent.is_none_or(|parent| self.entries[parent].api);
+
+        let key = (name, group, is_static);
+        if let Some(&i) = self.keys.get(&key) {
+            let entry = &mut self.entries[i];
+            entry.rank = entry.rank.max(rank);
+            entry.api |= api;
+            entry.deprecated |= deprecated;
+            return i;
+        }
+        self.entries.push(ApiEntry {
+            name: key.0.clone(),
+            group,
+            is_static,
+            location,
+            rank,
+            api,
+            is_final: Attribute::find_final(attributes).is_some(),
+            is_interface: false,
+            deprecated,
+            parent,
+            signature,
+            supertypes,
+            readable: None,
+            writable: None,
+        });
+        self.keys.insert(key, self.entries.len() - 1);
+        self.entries.len() - 1
+    }
+
+    fn rank(container: OutlineSymbolKind, attributes: &[Attribute]) -> u8 {
+        match container {
+            // Members of interfaces and enums are implicitly public.
+            OutlineSymbolKind::Interface | OutlineSymbolKind::Enum => 2,
+            _ if Attribute::find_public(attributes).is_some() => 2,
+            OutlineSymbolKind::Class if Attribute::find_protected(attributes).is_some() => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_api_compatibility() {
+        let old_cu = CompilationUnit::new(None, r#"package p {
+    public class C {
+        public function f(a: int, b: String = ""): void {}
+        [Deprecated] public function g(): void {}
+        protected var v: int;
+        public function get w(): Number { return 0 }
+        public function set w(value: Number): void {}
+        /** @private */ public function h(): void {}
+        private function k(): void {}
+    }
+    public interface I { function m(): void; }
+    public enum E { const A; const B; }
+    internal class D {}
+}"#.into());
+        let new_cu = CompilationUnit::new(None, r#"package p {
+    public final class C {
+        public function f(x: int, y: String = "y"): void {}
+        private var v: int;
+        public function get w(): Number { return 0 }
+        public function k(): void {}
+    }
+    public interface I { function m(): void; function n(): void; }
+    public enum E { const B; const A; }
+}"#.into());
+        let old = ParserFacade(&old_cu, default()).parse_program();
+        let new = ParserFacade(&new_cu, default()).parse_program();
+        assert!(!old_cu.invalidated() && !new_cu.invalidated());
+        let changes = ApiBreakingChange::check(&[old], &[new]);
+        let summary: Vec<(ApiBreakingChangeKind, &str, bool)> = changes.iter()
+            .map(|change| (change.kind, change.name.as_str(), change.deprecated))
+            .collect();
+        assert_eq!(summary, [
+            (ApiBreakingChangeKind::BecameFinal, "p.C", false),
+            (ApiBreakingChangeKind::Removed, "p.C.g", true),
+            (ApiBreakingChangeKind::VisibilityNarrowed, "p.C.v", false),
+            (ApiBreakingChangeKind::SignatureChanged, "p.C.w", false),
+            (ApiBreakingChangeKind::EnumMemberChanged, "p.E.A", false),
+            (ApiBreakingChangeKind::EnumMemberChanged, "p.E.B", false),
+            (ApiBreakingChangeKind::InterfaceMemberAdded, "p.I.n", false),
+        ]);
+        assert_eq!(changes[1].new_location, None);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::ns::*;
+use serde::Serialize;
+
+/// A difference in the definitions of two versions of a program.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DefinitionChange {
+    pub kind: DefinitionChangeKind,
+    pub symbol_kind: OutlineSymbolKind,
+    /// Qualified name of the definition in the old program, such as `p.C.f`.
+    pub old_name: Option<String>,
+    /// Qualified name of the definition in the new program.
+    pub new_name: Option<String>,
+    pub old_location: Option<Location>,
+    pub new_location: Option<Location>,
+    /// The access modifier or namespace attribute of the definition,
+    /// preferring the one of the new program.
+    pub visibility: Option<Visibility>,
+    pub is_static: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum DefinitionChangeKind {
+    Added,
+    Removed,
+    /// The definition is unchanged, but moved into another package or type.
+    Moved,
+    Changed {
+        /// The attributes, type annotations, parameters, or
+        /// heritage clauses changed.
+        signature: bool,
+        /// The function body, variable initializer, or
+        /// statements of a class changed.
+        body: bool,
+        asdoc: bool,
+    },
+}
+
+/// A definition, with location-insensitive digests of its parts.
+struct DiffEntry {
+    name: String,
+    qualified_name: String,
+    kind: OutlineSymbolKind,
+    is_static: bool,
+    visibility: Option<Visibility>,
+    location: Location,
+    parent: Option<usize>,
+    signature: u64,
+    body: u64,
+    asdoc: u64,
+    /// Digest of the whole definition, including its members.
+    whole: u64,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum EntryStatus {
+    Unmatched,
+    Matched(usize),
+    Moved,
+}
+
+impl DefinitionChange {
+    /// Compares the definitions of two versions of a program, independently
+    /// of their source locations and formatting.
+    ///
+    /// Definitions are matched by qualified name, kind and whether they are
+    /// static. Members of added, removed or moved definitions are not reported
+    /// separately. Changes are ordered as the definitions of the new program,
+    /// followed by the removed definitions. Function bodies skipped by
+    /// declaration-only parsing are compared by their source text, thus
+    /// reformatting them is reported as a change.
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    /// let old_cu = CompilationUnit::new(None, "package p { public class C { public function f(a: int): void {} } }".into());
+    /// let new_cu = CompilationUnit::new(None, "package p {\n    public class C {\n        public function f(a: Number): void {}\n    }\n}".into());
+    /// let old = ParserFacade(&old_cu, default()).parse_program();
+    /// let new = ParserFacade(&new_cu, default()).parse_program();
+    /// let changes = DefinitionChange::diff(&old, &new);
+    /// assert_eq!(changes.len(), 1);
+    /// assert_eq!(changes[0].new_name.as_deref(), Some("p.C.f"));
+    /// assert_eq!(changes[0].kind, DefinitionChangeKind::Changed { signature: true, body: false, asdoc: false });
+    /// ```
+    pub fn diff(old: &Rc<Program>, new: &Rc<Program>) -> Vec<DefinitionChange> {
+        let old_entries = Self::entries(old);
+        let new_entries = Self::entries(new);
+        let mut old_status = vec![EntryStatus::Unmatched; old_entries.len()];
+        let mut new_status = vec![EntryStatus::Unmatched; new_entries.len()];
+
+        let mut by_name: HashMap<(&str, OutlineSymbolKind, bool), Vec<usize>> = HashMap::new();
+        for (i, entry) in old_entries.iter().enumerate().rev() {
+            by_name.entry((&entry.qualified_name, entry.kind, entry.is_static)).or_default().push(i);
+        }
+        for (i, entry) in new_entries.iter().enumerate() {
+            if let Some(j) = by_name.get_mut(&(entry.qualified_name.as_str(), entry.kind, entry.is_static)).and_then(|candidates| candidates.pop()) {
+                old_status[j] = EntryStatus::Matched(i);
+                new_status[i] = EntryStatus::Matched(j);
+            }
+        }
+
+        // Unmatched definitions that are identical except for
+        // their container were moved.
+        for (i, entry) in new_entries.iter().enumerate() {
+            if new_status[i] != EntryStatus::Unmatched || entry.parent.is_some_and(|parent| new_status[parent] == EntryStatus::Unmatched) {
+                continue;
+            }
+            let moved_from = old_entries.iter().enumerate().position(|(j, old_entry)| {
+                old_status[j] == EntryStatus::Unmatched
+                    && old_entry.name == entry.name
+                    && old_entry.kind == entry.kind
+                    && old_entry.is_static == entry.is_static
+                    && old_entry.whole == entry.whole
+            });
+            if let Some(j) = moved_from {
+                old_status[j] = EntryStatus::Moved;
+                new_status[i] = EntryStatus::Matched(j);
+            }
+        }
+
+        let mut changes = vec![];
+        for (i, entry) in new_entries.iter().enumerate() {
+            // Members of added or moved definitions are not reported.
+            let parent_reported = entry.parent.is_some_and(|parent| match new_status[parent] {
+                EntryStatus::Matched(j) => old_status[j] == EntryStatus::Moved,
+                _ => true,
+            });
+            if parent_reported {
+                continue;
+            }
+            let (kind, old_entry) = match new_status[i] {
+                EntryStatus::Matched(j) if old_status[j] == EntryStatus::Moved => (DefinitionChangeKind::Moved, Some(&old_entries[j])),
+                EntryStatus::Matched(j) => {
+                    let old_entry = &old_entries[j];
+                    let signature = old_entry.signature != entry.signature;
+                    let body = old_entry.body != entry.body;
+                    let asdoc = old_entry.asdoc != entry.asdoc;
+                    if !(signature || body || asdoc) {
+                        continue;
+                    }
+                    (DefinitionChangeKind::Changed { signature, body, asdoc }, Some(old_entry))
+                },
+                _ => (DefinitionChangeKind::Added, None),
+            };
+            changes.push(Self::change(kind, old_entry, Some(entry)));
+        }
+        for (i, entry) in old_entries.iter().enumerate() {
+            if old_status[i] == EntryStatus::Unmatched && entry.parent.is_none_or(|parent| old_status[parent] != EntryStatus::Unmatched) {
+                changes.push(Self::change(DefinitionChangeKind::Removed, Some(entry), None));
+            }
+        }
+        changes
+    }
+
+    fn change(kind: DefinitionChangeKind, old: Option<&DiffEntry>, new: Option<&DiffEntry>) -> DefinitionChange {
+        let entry = new.or(old).unwrap();
+        DefinitionChange {
+            kind,
+            symbol_kind: entry.kind,
+            old_name: old.map(|entry| entry.qualified_name.clone()),
+            new_name: new.map(|entry| entry.qualified_name.clone()),
+            old_location: old.map(|entry| entry.location.clone()),
+            new_location: new.map(|entry| entry.location.clone()),
+            visibility: entry.visibility.clone(),
+            is_static: entry.is_static,
+        }
+    }
+
+    fn entries(program: &Rc<Program>) -> Vec<DiffEntry> {
+        let mut entries = vec![];
+        for symbol in &OutlineSymbol::outline(program) {
+            Self::symbol(symbol, None, "", &mut entries);
+        }
+        entries
+    }
+
+    /// Collects a definition and its members, where `prefix` is the
+    /// qualified name of the enclosing definition.
+    fn symbol(symbol: &OutlineSymbol, parent: Option<usize>, prefix: &str, entries: &mut Vec<DiffEntry>) {
+        let (signature, body, whole) = match &symbol.node {
+            OutlineNode::Package(_) => {
+                for child in &symbol.children {
+                    Self::symbol(child, None, &symbol.name, entries);
+                }
+                return;
+            },
+            OutlineNode::VariableBinding(directive, i) => {
+                let Directive::VariableDefinition(defn) = directive.as_ref() else {
+                    return;
+                };
+                let binding = &defn.bindings[*i];
+                (
+                    structural_digest(&(&defn.attributes, &defn.kind.0, &binding.destructuring.type_annotation)),
+                    structural_digest(&binding.initializer),
+                    structural_digest(&(&defn.attributes, &defn.kind.0, binding, &defn.asdoc)),
+                )
+            },
+            OutlineNode::Definition(directive) => {
+                let (signature, body) = match directive.as_ref() {
+                    Directive::FunctionDefinition(defn) => (structural_digest(&(&defn.attributes, &defn.common.signature)), structural_digest(&defn.common.body)),
+                    Directive::ClassDefinition(defn) => (
+                        structural_digest(&(&defn.attributes, &defn.type_parameters, &defn.extends_clause, &defn.implements_clause)),
+                        Self::statements_digest(&defn.block.directives),
+                    ),
+                    Directive::InterfaceDefinition(defn) => (
+                        structural_digest(&(&defn.attributes, &defn.type_parameters, &defn.extends_clause)),
+                        Self::statements_digest(&defn.block.directives),
+                    ),
+                    Directive::EnumDefinition(defn) => (
+                        structural_digest(&(&defn.attributes, defn.is_set, &defn.as_clause)),
+                        Self::statements_digest(&defn.block.directives),
+                    ),
+                    Directive::TypeDefinition(defn) => (structural_digest(&(&defn.attributes, &defn.right)), 0),
+                    Directive::NamespaceDefinition(defn) => (structural_digest(&(&defn.attributes, &defn.right)), 0),
+                    _ => return,
+                };
+                (signature, body, structural_digest(directive.as_ref()))
+            },
+        };
+        let qualified_name = if prefix.is_empty() { symbol.name.clone() } else { format!("{prefix}.{}", symbol.name) };
+        entries.push(DiffEntry {
+            name: symbol.name.clone(),
+            qualified_name: qualified_name.clone(),
+            kind: symbol.kind,
+            is_static: symbol.is_static,
+            visibility: symbol.visibility.clone(),
+            location: symbol.location.clone(),
+            parent,
+            signature,
+            body,
+            asdoc: structural_digest(&symbol.asdoc),
+            whole,
+        });
+        let i = entries.len() - 1;
+        for child in &symbol.children {
+            Self::symbol(child, Some(i), &qualified_name, entries);
+        }
+    }
+
+    /// Digest of the directives of a block that are not definitions,
+    /// such as static initialization code of a class.
+    fn statements_digest(directives: &[Rc<Directive>]) -> u64 {
+        let statements: Vec<&Rc<Directive>> = directives.iter().filter(|directive| !matches!(directive.as_ref(),
+            Directive::IncludeDirective(_) | Directive::ConfigurationDirective(_) |
+            Directive::VariableDefinition(_) | Directive::FunctionDefinition(_) |
+            Directive::ClassDefinition(_) | Directive::InterfaceDefinition(_) | Directive::EnumDefinition(_) |
+            Directive::TypeDefinition(_) | Directive::NamespaceDefinition(_))).collect();
+        structural_digest(&statements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_definition_diff() {
+        let old_cu = CompilationUnit::new(None, "package p {\n    public class C {\n        public function f(): void { trace(1) }\n        public function g(): void {}\n        private var h: int;\n    }\n    public class D {}\n    public function k(): void {}\n}\npackage q {\n    public class E { public var x; }\n}".into());
+        let new_cu = CompilationUnit::new(None, "package p {\n    public class C {\n        /** Doc. */\n        public function f() : void {\n            trace( 2 );\n        }\n        public function g(): void {}\n        public var i: int;\n    }\n    public function k(): void {}\n    public class F { public var y; }\n}\npackage r {\n    public class E { public var x; }\n}".into());
+        let old = ParserFacade(&old_cu, default()).parse_program();
+        let new = ParserFacade(&new_cu, default()).parse_program();
+        assert!(!old_cu.invalidated() && !new_cu.invalidated());
+        let changes = DefinitionChange::diff(&old, &new);
+        let summary: Vec<(DefinitionChangeKind, Option<&str>, Option<&str>)> = changes.iter()
+            .map(|change| (change.kind, change.old_name.as_deref(), change.new_name.as_deref()))
+            .collect();
+        assert_eq!(summary, [
+            (DefinitionChangeKind::Changed { signature: false, body: true, asdoc: true }, Some("p.C.f"), Some("p.C.f")),
+            (DefinitionChangeKind::Added, None, Some("p.C.i")),
+            (DefinitionChangeKind::Added, None, Some("p.F")),
+            (DefinitionChangeKind::Moved, Some("q.E"), Some("r.E")),
+            (DefinitionChangeKind::Removed, Some("p.C.h"), None),
+            (DefinitionChangeKind::Removed, Some("p.D"), None),
+        ]);
+        assert_eq!(changes[0].new_location.as_ref().unwrap().first_line_number(), 4);
+        assert_eq!(serde_json::to_string(&changes[1]).unwrap(), "{\"kind\":\"Added\",\"symbol_kind\":\"Variable\",\"old_name\":null,\"new_name\":\"p.C.i\",\"old_location\":null,\"new_location\":\"8:20-8:26\",\"visibility\":\"Public\",\"is_static\":false}");
+    }
+}
@@ -0,0 +1,298 @@
+use crate::ns::*;
+
+/// A syntax node that may cover an offset in a [`NodePath`].
+#[derive(Clone)]
+pub enum CoveringNode {
+    Program(Rc<Program>),
+    PackageDefinition(Rc<PackageDefinition>),
+    Directive(Rc<Directive>),
+    Expression(Rc<Expression>),
+    Attribute(Attribute),
+    FunctionCommon(Rc<FunctionCommon>),
+    Parameter(Rc<Parameter>),
+    Mxml(Rc<Mxml>),
+    MxmlElement(Rc<MxmlElement>),
+    MxmlAttribute(Rc<MxmlAttribute>),
+    CssDocument(Rc<CssDocument>),
+    CssDirective(Rc<CssDirective>),
+    CssRule(Rc<CssRule>),
+    CssSelector(Rc<CssSelector>),
+    CssProperty(Rc<CssProperty>),
+}
+
+impl CoveringNode {
+    pub fn location(&self) -> Location {
+        match self {
+            Self::Program(node) => node.location.clone(),
+            Self::PackageDefinition(node) => node.location.clone(),
+            Self::Directive(node) => node.location(),
+            Self::Expression(node) => node.location(),
+            Self::Attribute(node) => node.location(),
+            Self::FunctionCommon(node) => node.location.clone(),
+            Self::Parameter(node) => node.location.clone(),
+            Self::Mxml(node) => node.location.clone(),
+            Self::MxmlElement(node) => node.location.clone(),
+            Self::MxmlAttribute(node) => node.location.clone(),
+            Self::CssDocument(node) => node.location.clone(),
+            Self::CssDirective(node) => node.location(),
+            Self::CssRule(node) => node.location.clone(),
+            Self::CssSelector(node) => node.location(),
+            Self::CssProperty(node) => node.location.clone(),
+        }
+    }
+}
+
+/// The chain of nodes covering an offset, from the root of a tree
+/// to the innermost node.
+///
+/// A node covers an offset if the offset lies between its first offset and
+/// its last offset, inclusive. Nodes from other compilation units, such as
+/// the contents of included sources, are never part of a path. Where two
+/// adjacent siblings cover the offset, the one starting at it is preferred.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let cu = CompilationUnit::new(None, "x = f(y);".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let path = NodePath::in_program(&program, cu.get_offset(1, 6).unwrap());
+/// assert_eq!(path.innermost().unwrap().location().text(), "y");
+/// assert_eq!(path.ancestors().map(|node| node.location().text()).collect::<Vec<_>>(), ["f(y)", "x = f(y)", "x = f(y);", "x = f(y);"]);
+/// ```
+#[derive(Clone, Default)]
+pub struct NodePath {
+    pub nodes: Vec<CoveringNode>,
+}
+
+impl NodePath {
+    pub fn in_program(program: &Rc<Program>, offset: usize) -> Self {
+        let mut finder = CoveringNodeFinder::new(&program.location, offset);
+        finder.visit_program(program);
+        Self { nodes: finder.nodes }
+    }
+
+    pub fn in_mxml(mxml: &Rc<Mxml>, offset: usize) -> Self {
+        let mut finder = CoveringNodeFinder::new(&mxml.location, offset);
+        if finder.enter(CoveringNode::Mxml(mxml.clone())) {
+            finder.find_in_mxml_content(&mxml.content);
+        }
+        Self { nodes: finder.nodes }
+    }
+
+    pub fn in_css(css: &Rc<CssDocument>, offset: usize) -> Self {
+        let mut finder = CoveringNodeFinder::new(&css.location, offset);
+        if finder.enter(CoveringNode::CssDocument(css.clone())) {
+            for directive in &css.directives {
+                finder.find_in_css_directive(directive);
+            }
+        }
+        Self { nodes: finder.nodes }
+    }
+
+    pub fn innermost(&self) -> Option<&CoveringNode> {
+        self.nodes.last()
+    }
+
+    /// Returns the ancestors of the innermost node, from its parent to the root.
+    pub fn ancestors(&self) -> impl Iterator<Item = &CoveringNode> {
+        self.nodes.iter().rev().skip(1)
+    }
+
+    /// Returns the innermost expression covering the offset.
+    pub fn innermost_expression(&self) -> Option<&Rc<Expression>> {
+        self.nodes.iter().rev().find_map(|node| if let CoveringNode::Expression(exp) = node { Some(exp) } else { None })
+    }
+
+    /// Returns the innermost directive covering the offset.
+    pub fn innermost_directive(&self) -> Option<&Rc<Directive>> {
+        self.nodes.iter().rev().find_map(|node| if let CoveringNode::Directive(directive) = node { Some(directive) } else { None })
+    }
+}
+
+struct CoveringNodeFinder {
+    compilation_unit: Rc<CompilationUnit>,
+    offset: usize,
+    nodes: Vec<CoveringNode>,
+    depth: usize,
+}
+
+impl CoveringNodeFinder {
+    fn new(root: &Location, offset: usize) -> Self {
+        Self { compilation_unit: root.compilation_unit(), offset, nodes: vec![], depth: 0 }
+    }
+
+    /// Adds a node to the path if it covers the offset, replacing
+    /// a previously found sibling, and returns whether it does.
+    /// A call returning true must be paired with [`Self::leave`].
+    fn enter(&mut self, node: CoveringNode) -> bool {
+        let location = node.location();
+        if !(Rc::ptr_eq(&location.compilation_unit(), &self.compilation_unit)
+            && location.first_offset() <= self.offset && self.offset <= location.last_offset()) {
+            return false;
+        }
+        self.nodes.truncate(self.depth);
+        self.nodes.push(node);
+        self.depth += 1;
+        true
+    }
+
+    fn leave(&mut self) {
+        self.depth -= 1;
+    }
+
+    fn find_in_mxml_content(&mut self, content: &[Rc<MxmlContent>]) {
+        for node in content {
+            let MxmlContent::Element(element) = node.as_ref() else {
+                continue;
+            };
+            if !self.enter(CoveringNode::MxmlElement(element.clone())) {
+                continue;
+            }
+            for attribute in &element.attributes {
+                if self.enter(CoveringNode::MxmlAttribute(attribute.clone())) {
+                    self.leave();
+                }
+            }
+            if let Some(content) = &element.content {
+                self.find_in_mxml_content(content);
+            }
+            self.leave();
+        }
+    }
+
+    fn find_in_css_directive(&mut self, directive: &Rc<CssDirective>) {
+        if !self.enter(CoveringNode::CssDirective(directive.clone())) {
+            return;
+        }
+        match directive.as_ref() {
+            CssDirective::Rule(rule) => self.find_in_css_rule_contents(rule),
+            CssDirective::MediaQuery(media) => {
+                for rule in &media.rules {
+                    if self.enter(CoveringNode::CssRule(rule.clone())) {
+                        self.find_in_css_rule_contents(rule);
+                        self.leave();
+                    }
+                }
+            },
+            CssDirective::FontFace(font_face) => self.find_in_css_properties(&font_face.properties),
+            _ => {},
+        }
+        self.leave();
+    }
+
+    fn find_in_css_rule_contents(&mut self, rule: &CssRule) {
+        for selector in &rule.selectors {
+            self.find_in_css_selector(selector);
+        }
+        self.find_in_css_properties(&rule.properties);
+    }
+
+    fn find_in_css_selector(&mut self, selector: &Rc<CssSelector>) {
+        if !self.enter(CoveringNode::CssSelector(selector.clone())) {
+            return;
+        }
+        if let CssSelector::Combinator(combinator) = selector.as_ref() {
+            self.find_in_css_selector(&combinator.left);
+            self.find_in_css_selector(&combinator.right);
+        }
+        self.leave();
+    }
+
+    fn find_in_css_properties(&mut self, properties: &[Rc<CssProperty>]) {
+        for property in properties {
+            if self.enter(CoveringNode::CssProperty(property.clone())) {
+                self.leave();
+            }
+        }
+    }
+}
+
+impl Visitor for CoveringNodeFinder {
+    fn visit_program(&mut self, node: &Rc<Program>) {
+        if self.enter(CoveringNode::Program(node.clone())) {
+            walk_program(self, node);
+            self.leave();
+        }
+    }
+
+    fn visit_package_definition(&mut self, node: &Rc<PackageDefinition>) {
+        if self.enter(CoveringNode::PackageDefinition(node.clone())) {
+            walk_package_definition(self, node);
+            self.leave();
+        }
+    }
+
+    fn visit_directive(&mut self, node: &Rc<Directive>) {
+        if self.enter(CoveringNode::Directive(node.clone())) {
+            walk_directive(self, node);
+            self.leave();
+        }
+    }
+
+    fn visit_expression(&mut self, node: &Rc<Expression>) {
+        if self.enter(CoveringNode::Expression(node.clone())) {
+            walk_expression(self, node);
+            self.leave();
+        }
+    }
+
+    fn visit_attribute(&mut self, node: &Attribute) {
+        if self.enter(CoveringNode::Attribute(node.clone())) {
+            walk_attribute(self, node);
+            self.leave();
+        }
+    }
+
+    fn visit_function_common(&mut self, node: &Rc<FunctionCommon>) {
+        if self.enter(CoveringNode::FunctionCommon(node.clone())) {
+            walk_function_common(self, node);
+            self.leave();
+        }
+    }
+
+    fn visit_parameter(&mut self, node: &Rc<Parameter>) {
+        if self.enter(CoveringNode::Parameter(node.clone())) {
+            walk_parameter(self, node);
+            self.leave();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_node_path() {
+        let cu = CompilationUnit::new(None, "package {\n    public class C {\n        [Inline] public function f(a: int): void { trace(a + 1) }\n    }\n}".into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        assert!(!cu.invalidated());
+
+        let path = NodePath::in_program(&program, cu.get_offset(3, 57).unwrap());
+        assert_eq!(path.innermost().unwrap().location().text(), "a");
+        assert_eq!(path.innermost_expression().unwrap().location().text(), "a");
+        assert!(matches!(path.innermost_directive().unwrap().as_ref(), Directive::ExpressionStatement(_)));
+        assert!(path.ancestors().any(|node| matches!(node, CoveringNode::FunctionCommon(_))));
+        assert!(matches!(path.nodes[0], CoveringNode::Program(_)));
+
+        let path = NodePath::in_program(&program, cu.get_offset(3, 10).unwrap());
+        assert!(matches!(path.innermost().unwrap(), CoveringNode::Attribute(Attribute::Metadata(_))));
+        let path = NodePath::in_program(&program, cu.get_offset(3, 35).unwrap());
+        assert!(path.ancestors().any(|node| matches!(node, CoveringNode::Parameter(_))));
+
+        let cu = CompilationUnit::new(None, "s|Button, s|Label .icon { color: red; }".into());
+        let css = CssParserFacade(&cu, default()).parse_document();
+        let path = NodePath::in_css(&css, 21);
+        assert!(matches!(path.innermost().unwrap(), CoveringNode::CssSelector(_)));
+        assert_eq!(path.innermost().unwrap().location().text(), ".icon");
+        assert_eq!(path.ancestors().next().unwrap().location().text(), "s|Label .icon");
+
+        let cu = CompilationUnit::new(None, "<s:Group xmlns:s=\"library://ns.adobe.com/flex/spark\">\n    <s:Button label=\"OK\"/>\n</s:Group>".into());
+        let mxml = ParserFacade(&cu, default()).parse_mxml();
+        let path = NodePath::in_mxml(&mxml, cu.get_offset(2, 16).unwrap());
+        assert!(matches!(path.innermost().unwrap(), CoveringNode::MxmlAttribute(attribute) if attribute.name.name == "label"));
+        assert_eq!(path.nodes.len(), 4);
+    }
+}
@@ -0,0 +1,285 @@
+use crate::ns::*;
+use serde::Serialize;
+
+/// An entry of the hierarchical outline of a program.
+#[derive(Clone, Debug)]
+pub struct OutlineSymbol {
+    pub name: String,
+    pub kind: OutlineSymbolKind,
+    /// Location of the name of the definition.
+    pub name_location: Location,
+    /// Location of the whole definition.
+    pub location: Location,
+    /// The declared access modifier or namespace attribute, if any.
+    pub visibility: Option<Visibility>,
+    pub is_static: bool,
+    /// A short declaration such as `function f(x: Number): void`,
+    /// excluding attributes.
+    pub signature: String,
+    pub asdoc: Option<Rc<Asdoc>>,
+    pub node: OutlineNode,
+    pub children: Vec<OutlineSymbol>,
+}
+
+/// The node that declares an outline symbol.
+#[derive(Clone, Debug)]
+pub enum OutlineNode {
+    Package(Rc<PackageDefinition>),
+    /// A definition other than a variable definition.
+    Definition(Rc<Directive>),
+    /// A binding of a variable definition, given by its index.
+    VariableBinding(Rc<Directive>, usize),
+}
+
+impl OutlineNode {
+    /// Returns the attributes of the definition.
+    pub fn attributes(&self) -> &[Attribute] {
+        match self {
+            Self::Package(_) => &[],
+            Self::Definition(directive) | Self::VariableBinding(directive, _) => match directive.as_ref() {
+                Directive::VariableDefinition(defn) => &defn.attributes,
+                Directive::FunctionDefinition(defn) => &defn.attributes,
+                Directive::ClassDefinition(defn) => &defn.attributes,
+                Directive::InterfaceDefinition(defn) => &defn.attributes,
+                Directive::EnumDefinition(defn) => &defn.attributes,
+                Directive::TypeDefinition(defn) => &defn.attributes,
+                Directive::NamespaceDefinition(defn) => &defn.attributes,
+                _ => &[],
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum OutlineSymbolKind {
+    Package,
+    Class,
+    Interface,
+    Enum,
+    TypeAlias,
+    Namespace,
+    Function,
+    /// A function defined in a class, interface or enum block.
+    Method,
+    Getter,
+    Setter,
+    Constructor,
+    Variable,
+    Constant,
+    EnumMember,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Visibility {
+    Public,
+    Private,
+    Protected,
+    Internal,
+    /// A user-defined namespace attribute, such as `mx_internal`.
+    Namespace(String),
+}
+
+impl Visibility {
+    /// Returns the access modifier or namespace attribute of a definition.
+    pub fn from_attributes(attributes: &[Attribute]) -> Option<Self> {
+        attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Public(_) => Some(Self::Public),
+            Attribute::Private(_) => Some(Self::Private),
+            Attribute::Protected(_) => Some(Self::Protected),
+            Attribute::Internal(_) => Some(Self::Internal),
+            Attribute::Expression(exp) => Some(Self::Namespace(PrinterFacade(default()).print_expression(exp))),
+            _ => None,
+        })
+    }
+}
+
+impl OutlineSymbol {
+    /// Builds the outline of a program.
+    ///
+    /// Definitions from included sources and from configuration directives
+    /// (such as `CONFIG::DEBUG { ... }`) are outlined as if they appeared in
+    /// place of the directive; the locations of included definitions belong
+    /// to the included compilation unit.
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    /// let cu = CompilationUnit::new(None, "package p { public class C { static function f(a: int): void {} } }".into());
+    /// let program = ParserFacade(&cu, default()).parse_program();
+    /// let outline = OutlineSymbol::outline(&program);
+    /// let f = &outline[0].children[0].children[0];
+    /// assert_eq!(f.signature, "function f(a: int): void");
+    /// assert!(f.is_static && f.kind == OutlineSymbolKind::Method);
+    /// ```
+    pub fn outline(program: &Rc<Program>) -> Vec<OutlineSymbol> {
+        let mut symbols: Vec<OutlineSymbol> = program.packages.iter().map(Self::package).collect();
+        Self::directives(&program.directives, OutlineSymbolKind::Package, &mut symbols);
+        symbols
+    }
+
+    fn package(package: &Rc<PackageDefinition>) -> OutlineSymbol {
+        let name = package.name.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>().join(".");
+        let name_location = package.name.first().zip(package.name.last())
+            .map_or(package.location.clone(), |(first, last)| first.1.combine_with(last.1.clone()));
+        let mut children = vec![];
+        Self::directives(&package.block.directives, OutlineSymbolKind::Package, &mut children);
+        OutlineSymbol {
+            signature: if name.is_empty() { "package".into() } else { format!("package {name}") },
+            name,
+            kind: OutlineSymbolKind::Package,
+            name_location,
+            location: package.location.clone(),
+            visibility: None,
+            is_static: false,
+            asdoc: package.asdoc.clone(),
+            node: OutlineNode::Package(package.clone()),
+            children,
+        }
+    }
+
+    /// Outlines the definitions of a block, where `container` is the kind
+    /// of the enclosing definition.
+    fn directives(directives: &[Rc<Directive>], container: OutlineSymbolKind, symbols: &mut Vec<OutlineSymbol>) {
+        let printer = PrinterFacade(default());
+        for directive in directives {
+            match directive.as_ref() {
+                Directive::IncludeDirective(include) => {
+                    symbols.extend(include.nested_packages.iter().map(Self::package));
+                    Self::directives(&include.nested_directives, container, symbols);
+                },
+                Directive::ConfigurationDirective(config) => match config.directive.as_ref() {
+                    Directive::Block(block) => Self::directives(&block.directives, container, symbols),
+                    _ => Self::directives(std::slice::from_ref(&config.directive), container, symbols),
+                },
+                Directive::VariableDefinition(defn) => {
+                    let (kind, keyword) = match (container, &defn.kind.0) {
+                        (OutlineSymbolKind::Enum, _) => (OutlineSymbolKind::EnumMember, "const"),
+                        (_, VariableDefinitionKind::Const) => (OutlineSymbolKind::Constant, "const"),
+                        (_, VariableDefinitionKind::Var) => (OutlineSymbolKind::Variable, "var"),
+                    };
+                    for (i, binding) in defn.bindings.iter().enumerate() {
+                        let Expression::QualifiedIdentifier(id) = binding.destructuring.destructuring.as_ref() else {
+                            continue;
+                        };
+                        let Some((name, name_location)) = id.to_identifier_name() else {
+                            continue;
+                        };
+                        let signature = match &binding.destructuring.type_annotation {
+                            Some(t) => format!("{keyword} {name}: {}", printer.print_expression(t)),
+                            None => format!("{keyword} {name}"),
+                        };
+                        symbols.push(Self::definition(name, kind, name_location, binding.location(), &defn.attributes, signature, &defn.asdoc, OutlineNode::VariableBinding(directive.clone(), i), vec![]));
+                    }
+                },
+                Directive::FunctionDefinition(defn) => {
+                    let (name, name_location) = defn.name_identifier();
+                    let (kind, prefix) = match &defn.name {
+                        FunctionName::Getter(_) => (OutlineSymbolKind::Getter, "get "),
+                        FunctionName::Setter(_) => (OutlineSymbolKind::Setter, "set "),
+                        FunctionName::Constructor(_) => (OutlineSymbolKind::Constructor, ""),
+                        FunctionName::Identifier(_) if container == OutlineSymbolKind::Package => (OutlineSymbolKind::Function, ""),
+                        FunctionName::Identifier(_) => (OutlineSymbolKind::Method, ""),
+                    };
+                    let signature = format!("function {prefix}{name}{}", printer.print_function_signature(&defn.common.signature));
+                    symbols.push(Self::definition(name, kind, name_location, defn.location.clone(), &defn.attributes, signature, &defn.asdoc, OutlineNode::Definition(directive.clone()), vec![]));
+                },
+                Directive::ClassDefinition(defn) => {
+                    let mut signature = format!("class {}{}", defn.name.0, Self::type_parameters(&defn.type_parameters));
+                    if let Some(extends_clause) = &defn.extends_clause {
+                        signature.push_str(&format!(" extends {}", printer.print_expression(extends_clause)));
+                    }
+                    if let Some(implements_clause) = &defn.implements_clause {
+                        signature.push_str(&format!(" implements {}", Self::type_list(implements_clause)));
+                    }
+                    let mut children = vec![];
+                    Self::directives(&defn.block.directives, OutlineSymbolKind::Class, &mut children);
+                    symbols.push(Self::definition(defn.name.0.clone(), OutlineSymbolKind::Class, defn.name.1.clone(), defn.location.clone(), &defn.attributes, signature, &defn.asdoc, OutlineNode::Definition(directive.clone()), children));
+                },
+                Directive::InterfaceDefinition(defn) => {
+                    let mut signature = format!("interface {}{}", defn.name.0, Self::type_parameters(&defn.type_parameters));
+                    if let Some(extends_clause) = &defn.extends_clause {
+                        signature.push_str(&format!(" extends {}", Self::type_list(extends_clause)));
+                    }
+                    let mut children = vec![];
+                    Self::directives(&defn.block.directives, OutlineSymbolKind::Interface, &mut children);
+                    symbols.push(Self::definition(defn.name.0.clone(), OutlineSymbolKind::Interface, defn.name.1.clone(), defn.location.clone(), &defn.attributes, signature, &defn.asdoc, OutlineNode::Definition(directive.clone()), children));
+                },
+                Directive::EnumDefinition(defn) => {
+                    let mut signature = format!("enum {}", defn.name.0);
+                    if let Some(as_clause) = &defn.as_clause {
+                        signature.push_str(&format!(" as {}", printer.print_expression(as_clause)));
+                    }
+                    let mut children = vec![];
+                    Self::directives(&defn.block.directives, OutlineSymbolKind::Enum, &mut children);
+                    symbols.push(Self::definition(defn.name.0.clone(), OutlineSymbolKind::Enum, defn.name.1.clone(), defn.location.clone(), &defn.attributes, signature, &defn.asdoc, OutlineNode::Definition(directive.clone()), children));
+                },
+                Directive::TypeDefinition(defn) => {
+                    let signature = format!("type {} = {}", defn.left.0, printer.print_expression(&defn.right));
+                    symbols.push(Self::definition(defn.left.0.clone(), OutlineSymbolKind::TypeAlias, defn.left.1.clone(), defn.location.clone(), &defn.attributes, signature, &defn.asdoc, OutlineNode::Definition(directive.clone()), vec![]));
+                },
+                Directive::NamespaceDefinition(defn) => {
+                    let signature = match &defn.right {
+                        Some(right) => format!("namespace {} = {}", defn.left.0, printer.print_expression(right)),
+                        None => format!("namespace {}", defn.left.0),
+                    };
+                    symbols.push(Self::definition(defn.left.0.clone(), OutlineSymbolKind::Namespace, defn.left.1.clone(), defn.location.clone(), &defn.attributes, signature, &defn.asdoc, OutlineNode::Definition(directive.clone()), vec![]));
+                },
+                _ => {},
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn definition(name: String, kind: OutlineSymbolKind, name_location: Location, location: Location, attributes: &[Attribute], signature: String, asdoc: &Option<Rc<Asdoc>>, node: OutlineNode, children: Vec<OutlineSymbol>) -> OutlineSymbol {
+        OutlineSymbol {
+            name,
+            kind,
+            name_location,
+            location,
+            visibility: Visibility::from_attributes(attributes),
+            is_static: Attribute::find_static(attributes).is_some(),
+            signature,
+            asdoc: asdoc.clone(),
+            node,
+            children,
+        }
+    }
+
+    fn type_parameters(type_parameters: &Option<Vec<Rc<TypeParameter>>>) -> String {
+        type_parameters.as_ref().map_or(String::new(), |list| {
+            format!(".<{}>", list.iter().map(|p| p.name.0.clone()).collect::<Vec<_>>().join(", "))
+        })
+    }
+
+    fn type_list(list: &[Rc<Expression>]) -> String {
+        list.iter().map(|t| PrinterFacade(default()).print_expression(t)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_outline() {
+        let cu = CompilationUnit::new(None, "package p {\n    CONFIG::DEBUG {\n        internal var log: Function;\n    }\n    mx_internal class C.<T> extends B implements I {\n        public function get n(): Number { return 0 }\n        CONFIG::DEBUG private static const K = 1;\n    }\n    enum E { const A; }\n}\ntype T = [int];\nnamespace ns = \"x\";".into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        assert!(!cu.invalidated());
+        let outline = OutlineSymbol::outline(&program);
+        let summary: Vec<(&str, OutlineSymbolKind, Option<Visibility>, bool, &str)> = outline.iter()
+            .flat_map(|symbol| std::iter::once(symbol).chain(symbol.children.iter()).chain(symbol.children.iter().flat_map(|child| child.children.iter())))
+            .map(|symbol| (symbol.name.as_str(), symbol.kind, symbol.visibility.clone(), symbol.is_static, symbol.signature.as_str()))
+            .collect();
+        assert_eq!(summary, [
+            ("p", OutlineSymbolKind::Package, None, false, "package p"),
+            ("log", OutlineSymbolKind::Variable, Some(Visibility::Internal), false, "var log: Function"),
+            ("C", OutlineSymbolKind::Class, Some(Visibility::Namespace("mx_internal".into())), false, "class C.<T> extends B implements I"),
+            ("E", OutlineSymbolKind::Enum, None, false, "enum E"),
+            ("n", OutlineSymbolKind::Getter, Some(Visibility::Public), false, "function get n(): Number"),
+            ("K", OutlineSymbolKind::Constant, Some(Visibility::Private), true, "const K"),
+            ("A", OutlineSymbolKind::EnumMember, None, false, "const A"),
+            ("T", OutlineSymbolKind::TypeAlias, None, false, "type T = [int]"),
+            ("ns", OutlineSymbolKind::Namespace, None, false, "namespace ns = \"x\""),
+        ]);
+        assert_eq!(outline[0].children[1].name_location.text(), "C");
+    }
+}
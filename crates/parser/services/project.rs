@@ -0,0 +1,266 @@
+use crate::ns::*;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// The sources of a project: every `.as`, `.mxml` and `.css` file of a set of
+/// source roots, parsed and indexed by the fully qualified names of their
+/// top-level definitions.
+///
+/// Loading checks that the package of every ActionScript file matches its
+/// directory, relative to its source root, and that it declares exactly one
+/// externally visible definition. Files ending in `.include.as` are only
+/// parsed through the include directives that refer to them.
+///
+/// # Example
+///
+/// ```no_run
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let project = Project::load(&["src"], &default()).unwrap();
+/// if let Some(file) = project.file_of("com.example.Main") {
+///     println!("{}", file.path.display());
+/// }
+/// for issue in &project.issues {
+///     println!("{issue}");
+/// }
+/// ```
+pub struct Project {
+    /// The source files, in source root order and then in path order.
+    pub files: Vec<ProjectFile>,
+    /// The package-level definitions by fully qualified name, such as
+    /// `com.example.Main`.
+    pub definitions: HashMap<String, ProjectDefinition>,
+    pub issues: Vec<ProjectIssue>,
+}
+
+pub struct ProjectFile {
+    pub path: PathBuf,
+    pub source_root: PathBuf,
+    /// The package name that the directory of the file implies.
+    pub package_name: String,
+    pub compilation_unit: Rc<CompilationUnit>,
+    pub tree: ProjectTree,
+}
+
+pub enum ProjectTree {
+    Program(Rc<Program>),
+    Mxml(Rc<Mxml>),
+    Css(Rc<CssDocument>),
+}
+
+#[derive(Clone, Debug)]
+pub struct ProjectDefinition {
+    pub qualified_name: String,
+    pub kind: OutlineSymbolKind,
+    pub visibility: Option<Visibility>,
+    /// Location of the name of the definition, or of the whole
+    /// document for a MXML component.
+    pub location: Location,
+    /// Index of the file in [`Project::files`].
+    pub file: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProjectIssue {
+    pub kind: ProjectIssueKind,
+    pub location: Location,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProjectIssueKind {
+    /// The package of a file differs from the one its directory implies.
+    PackageMismatch { expected: String, found: String },
+    NoVisibleDefinition,
+    /// A file declares more than one externally visible definition;
+    /// reported at each definition after the first one.
+    MultipleVisibleDefinitions,
+    /// A definition has the same fully qualified name as one
+    /// declared previously.
+    DuplicateDefinition(String),
+}
+
+impl Display for ProjectIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PackageMismatch { expected, found } => write!(f, "package '{found}' does not match the directory; expected '{expected}'"),
+            Self::NoVisibleDefinition => f.write_str("the file declares no externally visible definition"),
+            Self::MultipleVisibleDefinitions => f.write_str("the file declares more than one externally visible definition"),
+            Self::DuplicateDefinition(name) => write!(f, "duplicate definition '{name}'"),
+        }
+    }
+}
+
+impl Display for ProjectIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let file_path = self.location.compilation_unit().file_path().map_or(String::new(), |path| format!("{path}:"));
+        write!(f, "{file_path}{}:{}: {}", self.location.first_line_number(), self.location.first_column() + 1, self.kind)
+    }
+}
+
+impl Project {
+    /// Loads the sources of a set of source roots, reading them through
+    /// the source provider of `options`.
+    ///
+    /// The files are always discovered by listing the source roots in the
+    /// file system, so sources that only exist in the provider, such as
+    /// unsaved buffers of new files, are not loaded.
+    pub fn load<P: AsRef<Path>>(source_roots: &[P], options: &ParserOptions) -> std::io::Result<Self> {
+        let mut project = Self { files: vec![], definitions: default(), issues: vec![] };
+        for source_root in source_roots {
+            let source_root = source_root.as_ref();
+            for path in source_files(source_root, &["as", "mxml", "css"])? {
+                if path.to_string_lossy().ends_with(".include.as") {
+                    continue;
+                }
+                project.load_file(source_root, path, options)?;
+            }
+        }
+        Ok(project)
+    }
+
+    /// Returns the file that declares a definition, given its fully qualified name.
+    pub fn file_of(&self, qualified_name: &str) -> Option<&ProjectFile> {
+        self.definitions.get(qualified_name).map(|definition| &self.files[definition.file])
+    }
+
+    fn load_file(&mut self, source_root: &Path, path: PathBuf, options: &ParserOptions) -> std::io::Result<()> {
+        let file_path = path.to_string_lossy().into_owned();
+        let text = options.source_provider.load(&file_path)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("failed to load {file_path}")))?;
+        let compilation_unit = CompilationUnit::new(Some(file_path), text);
+        let package_name = path.parent().and_then(|directory| directory.strip_prefix(source_root).ok())
+            .map_or(vec![], |directory| directory.components().map(|component| component.as_os_str().to_string_lossy().into_owned()).collect())
+            .join(".");
+        let file = self.files.len();
+
+        let tree = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("mxml") => {
+                let mxml = ParserFacade(&compilation_unit, options.clone()).parse_mxml();
+                let name = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+                self.define(ProjectDefinition {
+                    qualified_name: qualify(&package_name, &name),
+                    kind: OutlineSymbolKind::Class,
+                    visibility: Some(Visibility::Public),
+                    location: mxml.location.clone(),
+                    file,
+                });
+                ProjectTree::Mxml(mxml)
+            },
+            Some("css") => ProjectTree::Css(CssParserFacade(&compilation_unit, options.clone()).parse_document()),
+            _ => {
+                let program = ParserFacade(&compilation_unit, options.clone()).parse_program();
+                self.index_program(&program, &package_name, file);
+                ProjectTree::Program(program)
+            },
+        };
+
+        self.files.push(ProjectFile { path, source_root: source_root.to_owned(), package_name, compilation_unit, tree });
+        Ok(())
+    }
+
+    fn index_program(&mut self, program: &Rc<Program>, package_name: &str, file: usize) {
+        let mut visible_count = 0;
+        for package in OutlineSymbol::outline(program).into_iter().filter(|symbol| symbol.kind == OutlineSymbolKind::Package) {
+            if package.name != package_name {
+                self.issues.push(ProjectIssue {
+                    kind: ProjectIssueKind::PackageMismatch { expected: package_name.to_owned(), found: package.name.clone() },
+                    location: package.name_location.clone(),
+                });
+            }
+            for symbol in package.children {
+                if symbol.visibility == Some(Visibility::Public) {
+                    visible_count += 1;
+                    if visible_count > 1 {
+                        self.issues.push(ProjectIssue {
+                            kind: ProjectIssueKind::MultipleVisibleDefinitions,
+                            location: symbol.name_location.clone(),
+                        });
+                    }
+                }
+                self.define(ProjectDefinition {
+                    qualified_name: qualify(&package.name, &symbol.name),
+                    kind: symbol.kind,
+                    visibility: symbol.visibility,
+                    location: symbol.name_location,
+                    file,
+                });
+            }
+        }
+        if visible_count == 0 {
+            self.issues.push(ProjectIssue {
+                kind: ProjectIssueKind::NoVisibleDefinition,
+                location: Location::with_offset(&program.location.compilation_unit(), 0),
+            });
+        }
+    }
+
+    fn define(&mut self, definition: ProjectDefinition) {
+        if self.definitions.contains_key(&definition.qualified_name) {
+            self.issues.push(ProjectIssue {
+                kind: ProjectIssueKind::DuplicateDefinition(definition.qualified_name.clone()),
+                location: definition.location,
+            });
+        } else {
+            self.definitions.insert(definition.qualified_name.clone(), definition);
+        }
+    }
+}
+
+fn qualify(package_name: &str, name: &str) -> String {
+    if package_name.is_empty() { name.to_owned() } else { format!("{package_name}.{name}") }
+}
+
+/// Returns the files of a directory and its subdirectories that have
+/// one of the given extensions, in path order.
+pub(crate) fn source_files(directory: &Path, extensions: &[&str]) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut entries: Vec<_> = std::fs::read_dir(directory)?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            files.extend(source_files(&path, extensions)?);
+        } else if path.extension().is_some_and(|extension| extensions.iter().any(|other| extension == *other)) {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_project() {
+        let root = std::env::temp_dir().join(format!("as3parser_project_{}", std::process::id()));
+        let files = [
+            ("com/example/Main.as", "package com.example { public class Main {} class Helper {} }"),
+            ("com/example/Empty.as", "package com.example { internal var x; }"),
+            ("com/example/Wrong.as", "package com.other { public class Wrong {} public function f(): void {} }"),
+            ("com/example/View.mxml", "<?xml version=\"1.0\"?>\n<s:Group xmlns:s=\"library://ns.adobe.com/flex/spark\"/>"),
+            ("com/example/log.include.as", "trace(1);"),
+            ("styles.css", "a { color: red; }"),
+        ];
+        for (path, text) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+        let project = Project::load(&[&root], &default());
+        std::fs::remove_dir_all(&root).unwrap();
+        let project = project.unwrap();
+
+        assert_eq!(project.files.len(), 5);
+        let mut names: Vec<&String> = project.definitions.keys().collect();
+        names.sort();
+        assert_eq!(names, ["com.example.Helper", "com.example.Main", "com.example.View", "com.example.x", "com.other.Wrong", "com.other.f"]);
+        assert!(project.file_of("com.example.Main").unwrap().path.ends_with("com/example/Main.as"));
+        let issues: Vec<ProjectIssueKind> = project.issues.iter().map(|issue| issue.kind.clone()).collect();
+        assert_eq!(issues, [
+            ProjectIssueKind::NoVisibleDefinition,
+            ProjectIssueKind::PackageMismatch { expected: "com.example".into(), found: "com.other".into() },
+            ProjectIssueKind::MultipleVisibleDefinitions,
+        ]);
+    }
+}
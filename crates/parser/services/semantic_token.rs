@@ -0,0 +1,446 @@
+use crate::ns::*;
+use serde::{Serialize, Deserialize};
+use std::ops::Range;
+
+/// A classified range of source text, suitable for semantic highlighting.
+#[derive(Clone, Debug)]
+pub struct SemanticToken {
+    pub location: Location,
+    pub kind: SemanticTokenKind,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SemanticTokenKind {
+    Keyword,
+    /// An identifier acting as a keyword, such as `get`, `set`, `each`,
+    /// `namespace`, `include` or `static`.
+    ContextKeyword,
+    /// An identifier used as a value or as a property name.
+    Identifier,
+    /// An identifier in type position or naming a type definition.
+    TypeName,
+    /// A namespace qualifier, namespace attribute or namespace definition name.
+    Namespace,
+    MetadataName,
+    Comment,
+    /// A tag name within an ASDoc comment, such as `@param`.
+    AsdocTag,
+    String,
+    Number,
+    RegExp,
+    /// E4X tags, names, comments, CDATA and processing instructions.
+    XmlMarkup,
+    XmlText,
+    Punctuator,
+}
+
+impl SemanticToken {
+    /// Classifies the tokens of the compilation unit of `program` in source order,
+    /// using the syntax tree to tell apart identifiers.
+    ///
+    /// Nodes that belong to other compilation units, such as included sources,
+    /// are not classified.
+    ///
+    /// ```
+    /// use mxmlextrema_as3parser::ns::*;
+    /// let cu = CompilationUnit::new(None, "var x: Number = y".into());
+    /// let program = ParserFacade(&cu, default()).parse_program();
+    /// let kinds: Vec<SemanticTokenKind> = SemanticToken::classify(&program).iter().map(|t| t.kind).collect();
+    /// assert_eq!(kinds[3], SemanticTokenKind::TypeName);
+    /// assert_eq!(kinds[5], SemanticTokenKind::Identifier);
+    /// ```
+    pub fn classify(program: &Rc<Program>) -> Vec<SemanticToken> {
+        let compilation_unit = program.location.compilation_unit();
+        let tokens: Vec<(Token, Location)> = Lexer::new(&compilation_unit, &default())
+            .filter(|(token, _)| !matches!(token, Token::Whitespace | Token::LineTerminator | Token::XmlWhitespace))
+            .collect();
+        let mut classifier = SemanticTokenClassifier {
+            compilation_unit: compilation_unit.clone(),
+            kinds: vec![None; tokens.len()],
+            tokens,
+            xml_ranges: vec![],
+            embedded_ranges: vec![],
+            asdoc_tags: vec![],
+        };
+        classifier.visit_program(program);
+        classifier.finish()
+    }
+}
+
+struct SemanticTokenClassifier {
+    compilation_unit: Rc<CompilationUnit>,
+    tokens: Vec<(Token, Location)>,
+    kinds: Vec<Option<SemanticTokenKind>>,
+    xml_ranges: Vec<Range<usize>>,
+    /// Ranges of expressions embedded in E4X literals.
+    embedded_ranges: Vec<Range<usize>>,
+    asdoc_tags: Vec<Range<usize>>,
+}
+
+impl SemanticTokenClassifier {
+    fn is_local(&self, location: &Location) -> bool {
+        Rc::ptr_eq(&location.compilation_unit(), &self.compilation_unit)
+    }
+
+    fn range(location: &Location) -> Range<usize> {
+        location.first_offset()..location.last_offset()
+    }
+
+    fn token_index(&self, offset: usize) -> usize {
+        self.tokens.partition_point(|(_, location)| location.first_offset() < offset)
+    }
+
+    /// Classifies the token at `location`, unless it has been classified before.
+    fn mark(&mut self, location: &Location, kind: SemanticTokenKind) {
+        if !self.is_local(location) {
+            return;
+        }
+        let i = self.token_index(location.first_offset());
+        if self.tokens.get(i).is_some_and(|(_, l)| l.last_offset() == location.last_offset()) {
+            self.kinds[i].get_or_insert(kind);
+        }
+    }
+
+    /// Classifies the first identifier equal to `keyword` that starts
+    /// within the given range as a context keyword.
+    fn mark_context_keyword(&mut self, location: &Location, last_offset: usize, keyword: &str) {
+        if !self.is_local(location) {
+            return;
+        }
+        let i = self.token_index(location.first_offset());
+        let found = self.tokens[i..].iter()
+            .take_while(|(_, l)| l.first_offset() < last_offset)
+            .position(|(token, _)| matches!(token, Token::Identifier(name) if name == keyword));
+        if let Some(j) = found {
+            self.kinds[i + j].get_or_insert(SemanticTokenKind::ContextKeyword);
+        }
+    }
+
+    fn mark_identifier(&mut self, id: &QualifiedIdentifier, kind: SemanticTokenKind) {
+        if let QualifiedIdentifierIdentifier::Id((_, location)) = &id.id {
+            self.mark(location, kind);
+        }
+    }
+
+    fn mark_type(&mut self, exp: &Rc<Expression>) {
+        match exp.as_ref() {
+            Expression::QualifiedIdentifier(id) => self.mark_identifier(id, SemanticTokenKind::TypeName),
+            Expression::Member(member) => self.mark_identifier(&member.identifier, SemanticTokenKind::TypeName),
+            Expression::WithTypeArguments(apply) => {
+                self.mark_type(&apply.base);
+                for argument in &apply.arguments {
+                    self.mark_type(argument);
+                }
+            },
+            Expression::NullableType(e) => self.mark_type(&e.base),
+            Expression::NonNullableType(e) => self.mark_type(&e.base),
+            Expression::ArrayType(e) => self.mark_type(&e.expression),
+            Expression::TupleType(e) => {
+                for element in &e.expressions {
+                    self.mark_type(element);
+                }
+            },
+            Expression::FunctionType(e) => {
+                for parameter in &e.parameters {
+                    if let Some(type_expression) = &parameter.type_expression {
+                        self.mark_type(type_expression);
+                    }
+                }
+                if let Some(result_type) = &e.result_type {
+                    self.mark_type(result_type);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn mark_namespace(&mut self, exp: &Rc<Expression>) {
+        if let Expression::QualifiedIdentifier(id) = exp.as_ref() {
+            self.mark_identifier(id, SemanticTokenKind::Namespace);
+        }
+    }
+
+    fn mark_embedded(&mut self, exp: &Rc<Expression>) {
+        if self.is_local(&exp.location()) {
+            self.embedded_ranges.push(Self::range(&exp.location()));
+        }
+    }
+
+    fn finish(mut self) -> Vec<SemanticToken> {
+        let mut xml = vec![false; self.tokens.len()];
+        for (ranges, value) in [(&self.xml_ranges, true), (&self.embedded_ranges, false)] {
+            for range in ranges {
+                let i = self.token_index(range.start);
+                for (j, (_, location)) in self.tokens[i..].iter().enumerate() {
+                    if location.last_offset() > range.end {
+                        break;
+                    }
+                    xml[i + j] = value;
+                }
+            }
+        }
+
+        self.asdoc_tags.sort_by_key(|range| range.start);
+        let mut result = vec![];
+        for (i, (token, location)) in self.tokens.iter().enumerate() {
+            let kind = self.kinds[i].unwrap_or_else(|| match token {
+                Token::Comment { .. } => SemanticTokenKind::Comment,
+                Token::Identifier(_) => SemanticTokenKind::Identifier,
+                Token::String(_) | Token::XmlAttributeValue(_) => SemanticTokenKind::String,
+                Token::Number(..) => SemanticTokenKind::Number,
+                Token::RegExp { .. } => SemanticTokenKind::RegExp,
+                Token::XmlText(_) => SemanticTokenKind::XmlText,
+                Token::XmlName(_) | Token::XmlMarkup(_) | Token::XmlLtSlash | Token::XmlSlashGt => SemanticTokenKind::XmlMarkup,
+                _ if token.is_reserved_word() => SemanticTokenKind::Keyword,
+                _ if xml[i] => SemanticTokenKind::XmlMarkup,
+                _ => SemanticTokenKind::Punctuator,
+            });
+            if kind != SemanticTokenKind::Comment {
+                result.push(SemanticToken { location: location.clone(), kind });
+                continue;
+            }
+            // Split ASDoc tags out of the comment.
+            let mut first_offset = location.first_offset();
+            let j = self.asdoc_tags.partition_point(|range| range.start < first_offset);
+            for tag in self.asdoc_tags[j..].iter().take_while(|range| range.end <= location.last_offset()) {
+                if tag.start > first_offset {
+                    result.push(SemanticToken { location: Location::with_offsets(&self.compilation_unit, first_offset, tag.start), kind });
+                }
+                result.push(SemanticToken { location: Location::with_offsets(&self.compilation_unit, tag.start, tag.end), kind: SemanticTokenKind::AsdocTag });
+                first_offset = tag.end;
+            }
+            if first_offset < location.last_offset() {
+                result.push(SemanticToken { location: Location::with_offsets(&self.compilation_unit, first_offset, location.last_offset()), kind });
+            }
+        }
+        result
+    }
+}
+
+impl Visitor for SemanticTokenClassifier {
+    fn visit_qualified_identifier(&mut self, node: &QualifiedIdentifier) {
+        if let Some(qualifier) = &node.qualifier {
+            self.mark_namespace(qualifier);
+        }
+        // Reserved words are identifiers in property position.
+        if !matches!(&node.id, QualifiedIdentifierIdentifier::Id((name, _)) if name == "*") {
+            self.mark_identifier(node, SemanticTokenKind::Identifier);
+        }
+        walk_qualified_identifier(self, node);
+    }
+
+    fn visit_xml_expression(&mut self, node: &XmlExpression) {
+        if self.is_local(&node.location) {
+            self.xml_ranges.push(Self::range(&node.location));
+        }
+        walk_xml_expression(self, node);
+    }
+
+    fn visit_xml_list_expression(&mut self, node: &XmlListExpression) {
+        if self.is_local(&node.location) {
+            self.xml_ranges.push(Self::range(&node.location));
+        }
+        walk_xml_list_expression(self, node);
+    }
+
+    fn visit_xml_element(&mut self, node: &Rc<XmlElement>) {
+        for name in [Some(&node.name), node.closing_name.as_ref()].into_iter().flatten() {
+            if let XmlTagName::Expression(exp) = name {
+                self.mark_embedded(exp);
+            }
+        }
+        if let Some(exp) = &node.attribute_expression {
+            self.mark_embedded(exp);
+        }
+        walk_xml_element(self, node);
+    }
+
+    fn visit_xml_attribute(&mut self, node: &Rc<XmlAttribute>) {
+        if let XmlAttributeValue::Expression(exp) = &node.value {
+            self.mark_embedded(exp);
+        }
+        walk_xml_attribute(self, node);
+    }
+
+    fn visit_xml_content(&mut self, node: &Rc<XmlContent>) {
+        if let XmlContent::Expression(exp) = node.as_ref() {
+            self.mark_embedded(exp);
+        }
+        walk_xml_content(self, node);
+    }
+
+    fn visit_vector_literal(&mut self, node: &VectorLiteral) {
+        self.mark_type(&node.element_type);
+        walk_vector_literal(self, node);
+    }
+
+    fn visit_apply_type_expression(&mut self, node: &ApplyTypeExpression) {
+        self.mark_type(&node.base);
+        for argument in &node.arguments {
+            self.mark_type(argument);
+        }
+        walk_apply_type_expression(self, node);
+    }
+
+    fn visit_typed_destructuring(&mut self, node: &TypedDestructuring) {
+        if let Some(type_annotation) = &node.type_annotation {
+            self.mark_type(type_annotation);
+        }
+        walk_typed_destructuring(self, node);
+    }
+
+    fn visit_for_in_statement(&mut self, node: &ForInStatement) {
+        if node.each {
+            self.mark_context_keyword(&node.location, node.right.location().first_offset(), "each");
+        }
+        walk_for_in_statement(self, node);
+    }
+
+    fn visit_default_xml_namespace_statement(&mut self, node: &DefaultXmlNamespaceStatement) {
+        let last_offset = node.right.location().first_offset();
+        self.mark_context_keyword(&node.location, last_offset, "xml");
+        self.mark_context_keyword(&node.location, last_offset, "namespace");
+        walk_default_xml_namespace_statement(self, node);
+    }
+
+    fn visit_configuration_directive(&mut self, node: &ConfigurationDirective) {
+        self.mark(&node.namespace.1, SemanticTokenKind::Namespace);
+        walk_configuration_directive(self, node);
+    }
+
+    fn visit_use_namespace_directive(&mut self, node: &UseNamespaceDirective) {
+        self.mark_context_keyword(&node.location, node.expression.location().first_offset(), "namespace");
+        self.mark_namespace(&node.expression);
+        walk_use_namespace_directive(self, node);
+    }
+
+    fn visit_include_directive(&mut self, node: &IncludeDirective) {
+        self.mark_context_keyword(&node.location, node.location.last_offset(), "include");
+        walk_include_directive(self, node);
+    }
+
+    fn visit_attribute(&mut self, node: &Attribute) {
+        match node {
+            Attribute::Expression(exp) => self.mark_namespace(exp),
+            Attribute::Final(location) |
+            Attribute::Native(location) |
+            Attribute::Static(location) |
+            Attribute::Abstract(location) |
+            Attribute::Override(location) |
+            Attribute::Dynamic(location) => self.mark(location, SemanticTokenKind::ContextKeyword),
+            _ => {},
+        }
+        walk_attribute(self, node);
+    }
+
+    fn visit_metadata(&mut self, node: &Rc<Metadata>) {
+        self.mark(&node.name.1, SemanticTokenKind::MetadataName);
+        walk_metadata(self, node);
+    }
+
+    fn visit_asdoc(&mut self, node: &Rc<Asdoc>) {
+        if !self.is_local(&node.location) {
+            return;
+        }
+        let text = self.compilation_unit.text();
+        for (_, location) in &node.tags {
+            let first_offset = location.first_offset();
+            let length = text[first_offset..].find(|ch: char| ch.is_whitespace()).unwrap_or(text.len() - first_offset);
+            self.asdoc_tags.push(first_offset..(first_offset + length).min(location.last_offset()));
+        }
+    }
+
+    fn visit_type_parameter(&mut self, node: &Rc<TypeParameter>) {
+        self.mark(&node.name.1, SemanticTokenKind::TypeName);
+    }
+
+    fn visit_function_definition(&mut self, node: &FunctionDefinition) {
+        match &node.name {
+            FunctionName::Getter((_, location)) => self.mark_context_keyword(&node.location, location.first_offset(), "get"),
+            FunctionName::Setter((_, location)) => self.mark_context_keyword(&node.location, location.first_offset(), "set"),
+            _ => {},
+        }
+        walk_function_definition(self, node);
+    }
+
+    fn visit_function_signature(&mut self, node: &FunctionSignature) {
+        if let Some(this_parameter) = &node.this_parameter {
+            self.mark_type(&this_parameter.type_annotation);
+        }
+        if let Some(result_type) = &node.result_type {
+            self.mark_type(result_type);
+        }
+        walk_function_signature(self, node);
+    }
+
+    fn visit_class_definition(&mut self, node: &ClassDefinition) {
+        self.mark(&node.name.1, SemanticTokenKind::TypeName);
+        for exp in node.extends_clause.iter().chain(node.implements_clause.iter().flatten()) {
+            self.mark_type(exp);
+        }
+        walk_class_definition(self, node);
+    }
+
+    fn visit_enum_definition(&mut self, node: &EnumDefinition) {
+        self.mark_context_keyword(&node.location, node.name.1.first_offset(), "enum");
+        self.mark(&node.name.1, SemanticTokenKind::TypeName);
+        if let Some(as_clause) = &node.as_clause {
+            self.mark_type(as_clause);
+        }
+        walk_enum_definition(self, node);
+    }
+
+    fn visit_interface_definition(&mut self, node: &InterfaceDefinition) {
+        self.mark(&node.name.1, SemanticTokenKind::TypeName);
+        for exp in node.extends_clause.iter().flatten() {
+            self.mark_type(exp);
+        }
+        walk_interface_definition(self, node);
+    }
+
+    fn visit_type_definition(&mut self, node: &TypeDefinition) {
+        self.mark_context_keyword(&node.location, node.left.1.first_offset(), "type");
+        self.mark(&node.left.1, SemanticTokenKind::TypeName);
+        self.mark_type(&node.right);
+        walk_type_definition(self, node);
+    }
+
+    fn visit_namespace_definition(&mut self, node: &NamespaceDefinition) {
+        self.mark_context_keyword(&node.location, node.left.1.first_offset(), "namespace");
+        self.mark(&node.left.1, SemanticTokenKind::Namespace);
+        walk_namespace_definition(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_classify() {
+        let text = "package p {\n    /** @private */\n    [Bindable]\n    public class C extends B implements I {\n        static var v: Vector.<T> = new <T>[];\n        ns::x = <a k={ o.default }>t</a>;\n        function get n(): N { for each (var e in l) {} return 0 }\n    }\n}";
+        let cu = CompilationUnit::new(None, text.into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        assert!(!cu.invalidated());
+        let tokens: Vec<(String, SemanticTokenKind)> = SemanticToken::classify(&program).iter().map(|t| (t.location.text(), t.kind)).collect();
+        let kind_of = |text: &str| tokens.iter().find(|(t, _)| t == text).unwrap().1;
+        assert_eq!(kind_of("package"), SemanticTokenKind::Keyword);
+        assert_eq!(kind_of("@private"), SemanticTokenKind::AsdocTag);
+        assert_eq!(kind_of(" */"), SemanticTokenKind::Comment);
+        assert_eq!(kind_of("Bindable"), SemanticTokenKind::MetadataName);
+        for name in ["C", "B", "I", "Vector", "T", "N"] {
+            assert_eq!(kind_of(name), SemanticTokenKind::TypeName, "{name}");
+        }
+        for name in ["static", "get", "each"] {
+            assert_eq!(kind_of(name), SemanticTokenKind::ContextKeyword, "{name}");
+        }
+        assert_eq!(kind_of("ns"), SemanticTokenKind::Namespace);
+        for name in ["x", "o", "default", "e", "l"] {
+            assert_eq!(kind_of(name), SemanticTokenKind::Identifier, "{name}");
+        }
+        assert_eq!(kind_of("k"), SemanticTokenKind::XmlMarkup);
+        assert_eq!(kind_of("t"), SemanticTokenKind::XmlText);
+        assert_eq!(kind_of("0"), SemanticTokenKind::Number);
+        assert_eq!(tokens.iter().filter(|(_, kind)| *kind == SemanticTokenKind::XmlMarkup).count(), 10);
+    }
+}
@@ -0,0 +1,244 @@
+use crate::ns::*;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A structural rewrite rule, replacing nodes that match a
+/// [`StructuralPattern`] with code where the placeholders of the
+/// pattern are substituted by the source text they captured.
+///
+/// Rewriting produces minimal text edits for the matched nodes, leaving
+/// the rest of the source text, such as comments and formatting, intact.
+/// Captured expressions and replacements are parenthesized where needed
+/// to keep their meaning.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let rule = StructuralRewrite::new("$a.length == 0", "$a.isEmpty()").unwrap();
+/// let cu = CompilationUnit::new(None, "if (list.length == 0) {}".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let edits = StructuralRewrite::edits(&[rule], &program);
+/// assert_eq!(TextEdit::apply_all(cu.text(), &edits), "if (list.isEmpty()) {}");
+/// ```
+pub struct StructuralRewrite {
+    pattern: StructuralPattern,
+    replacement: String,
+    /// The replacement as a pattern, if it is a single directive
+    /// other than a placeholder.
+    replacement_pattern: Option<StructuralPattern>,
+    /// Placeholders of the pattern that capture expressions.
+    expression_placeholders: Vec<String>,
+}
+
+/// Expressions that are parenthesized when they would
+/// otherwise bind differently.
+const COMPOUND_EXPRESSIONS: [&str; 5] = ["Unary", "Binary", "Conditional", "Assignment", "Sequence"];
+
+struct RewriteMatch<'a> {
+    rule: &'a StructuralRewrite,
+    range: Range<usize>,
+    captures: Vec<(String, Range<usize>)>,
+}
+
+impl StructuralRewrite {
+    pub fn new(pattern: &str, replacement: &str) -> Result<Self, StructuralPatternError> {
+        let pattern = StructuralPattern::new(pattern)?;
+        let replacement_pattern = match StructuralPattern::new(replacement) {
+            Ok(replacement_pattern) => Some(replacement_pattern),
+            Err(StructuralPatternError::OnlyPlaceholder | StructuralPatternError::NotSingleDirective) => None,
+            Err(error) => return Err(error),
+        };
+        let placeholders = pattern.placeholders();
+        for (name, _) in placeholder_occurrences(replacement) {
+            if !placeholders.iter().any(|(other, _)| *other == name) {
+                return Err(StructuralPatternError::UnboundPlaceholder(name));
+            }
+        }
+        Ok(Self {
+            pattern,
+            replacement: replacement.into(),
+            replacement_pattern,
+            expression_placeholders: placeholders.into_iter().filter(|(_, is_expression)| *is_expression).map(|(name, _)| name).collect(),
+        })
+    }
+
+    /// Returns the edits that apply rules to the source text of a program,
+    /// excluding included sources.
+    ///
+    /// Where matches of the rules overlap, the outermost match is rewritten,
+    /// and matches within its captures are rewritten within the substituted
+    /// text. Where several rules match the same node, the first one is applied.
+    pub fn edits(rules: &[StructuralRewrite], program: &Rc<Program>) -> Vec<TextEdit> {
+        let compilation_unit = program.location.compilation_unit();
+        let text = compilation_unit.text();
+        let mut matches = vec![];
+        for rule in rules {
+            for found in rule.pattern.find(program) {
+                if !Rc::ptr_eq(&found.location.compilation_unit(), &compilation_unit) {
+                    continue;
+                }
+                matches.push(RewriteMatch {
+                    rule,
+                    range: found.location.first_offset()..found.location.last_offset(),
+                    captures: found.captures.iter().map(|(name, location)| (name.clone(), location.first_offset()..location.last_offset())).collect(),
+                });
+            }
+        }
+        matches.sort_by_key(|found| (found.range.start, std::cmp::Reverse(found.range.end)));
+
+        Self::outermost(&matches, 0..text.len()).into_iter().filter_map(|found| {
+            minimal_edit(text, found.range.clone(), &Self::rewrite_match(found, &matches, text))
+        }).collect()
+    }
+
+    pub fn edits_in_file(rules: &[StructuralRewrite], path: &Path) -> std::io::Result<Vec<TextEdit>> {
+        let text = std::fs::read_to_string(path)?;
+        let compilation_unit = CompilationUnit::new(Some(path.to_string_lossy().into_owned()), text);
+        let program = ParserFacade(&compilation_unit, default()).parse_program();
+        Ok(Self::edits(rules, &program))
+    }
+
+    /// Returns the edits to every `.as` file of a directory and its
+    /// subdirectories that is changed by the rules, in path order.
+    pub fn edits_in_directory(rules: &[StructuralRewrite], directory: &Path) -> std::io::Result<Vec<(PathBuf, Vec<TextEdit>)>> {
+        let mut files = vec![];
+        for path in source_files(directory, &["as"])? {
+            let edits = Self::edits_in_file(rules, &path)?;
+            if !edits.is_empty() {
+                files.push((path, edits));
+            }
+        }
+        Ok(files)
+    }
+
+    /// Returns the matches within a range that are not within other
+    /// matches, skipping matches that overlap previous ones.
+    fn outermost<'a, 'b>(matches: &'b [RewriteMatch<'a>], range: Range<usize>) -> Vec<&'b RewriteMatch<'a>> {
+        let mut outermost = vec![];
+        let mut end = range.start;
+        for found in matches {
+            if found.range.start >= end && found.range.end <= range.end && found.range.start < found.range.end {
+                end = found.range.end;
+                outermost.push(found);
+            }
+        }
+        outermost
+    }
+
+    /// Returns the text of a range, with the matches within it rewritten.
+    fn rewrite_range(range: Range<usize>, matches: &[RewriteMatch], text: &str) -> String {
+        let mut result = String::new();
+        let mut i = range.start;
+        for found in Self::outermost(matches, range.clone()) {
+            result.push_str(&text[i..found.range.start]);
+            result.push_str(&Self::rewrite_match(found, matches, text));
+            i = found.range.end;
+        }
+        result.push_str(&text[i..range.end]);
+        result
+    }
+
+    fn rewrite_match(found: &RewriteMatch, matches: &[RewriteMatch], text: &str) -> String {
+        let captures: Vec<(String, String)> = found.captures.iter()
+            .map(|(name, range)| (name.clone(), Self::rewrite_range(range.clone(), matches, text)))
+            .collect();
+        found.rule.substitute(&captures, &text[found.range.clone()])
+    }
+
+    fn substitute(&self, captures: &[(String, String)], matched_text: &str) -> String {
+        let mut new_text = substitute_placeholders(&self.replacement, captures);
+        if self.replacement_pattern.as_ref().is_some_and(|pattern| !keeps_captures(pattern, &new_text, captures)) {
+            let captures: Vec<(String, String)> = captures.iter().map(|(name, text)| {
+                if self.expression_placeholders.contains(name) && is_compound(text) {
+                    (name.clone(), format!("({text})"))
+                } else {
+                    (name.clone(), text.clone())
+                }
+            }).collect();
+            new_text = substitute_placeholders(&self.replacement, &captures);
+        }
+        if self.pattern.is_expression() && is_compound(&new_text) && !is_compound(matched_text) {
+            new_text = format!("({new_text})");
+        }
+        new_text
+    }
+}
+
+/// Whether code matches a replacement pattern with the same captures
+/// it was substituted with.
+fn keeps_captures(pattern: &StructuralPattern, text: &str, captures: &[(String, String)]) -> bool {
+    pattern.match_text(text).is_some_and(|found| found.iter().all(|(name, text)| {
+        captures.iter().find(|(other, _)| other == name).is_none_or(|(_, captured)| captured == text)
+    }))
+}
+
+fn is_compound(text: &str) -> bool {
+    expression_kind(text).is_some_and(|kind| COMPOUND_EXPRESSIONS.contains(&kind.as_str()))
+}
+
+/// Returns the placeholders of a replacement with their byte ranges.
+fn placeholder_occurrences(replacement: &str) -> Vec<(String, Range<usize>)> {
+    let mut occurrences = vec![];
+    let mut chars = replacement.char_indices().peekable();
+    while let Some((i, ch)) = chars.next() {
+        if ch != '$' {
+            continue;
+        }
+        let mut end = i + 1;
+        while let Some((j, ch)) = chars.next_if(|(_, ch)| ch.is_alphanumeric() || *ch == '_') {
+            end = j + ch.len_utf8();
+        }
+        if end > i + 1 {
+            occurrences.push((replacement[i..end].to_owned(), i..end));
+        }
+    }
+    occurrences
+}
+
+fn substitute_placeholders(replacement: &str, captures: &[(String, String)]) -> String {
+    let mut result = String::new();
+    let mut i = 0;
+    for (name, range) in placeholder_occurrences(replacement) {
+        result.push_str(&replacement[i..range.start]);
+        result.push_str(captures.iter().find(|(other, _)| *other == name).map_or("", |(_, text)| text));
+        i = range.end;
+    }
+    result.push_str(&replacement[i..]);
+    result
+}
+
+/// Returns an edit replacing a range by a text, excluding the
+/// prefix and suffix they have in common.
+fn minimal_edit(text: &str, range: Range<usize>, new_text: &str) -> Option<TextEdit> {
+    let old_text = &text[range.clone()];
+    if old_text == new_text {
+        return None;
+    }
+    let prefix: usize = old_text.chars().zip(new_text.chars()).take_while(|(a, b)| a == b).map(|(ch, _)| ch.len_utf8()).sum();
+    let suffix: usize = old_text[prefix..].chars().rev().zip(new_text[prefix..].chars().rev()).take_while(|(a, b)| a == b).map(|(ch, _)| ch.len_utf8()).sum();
+    Some(TextEdit::new(range.start + prefix..range.end - suffix, &new_text[prefix..new_text.len() - suffix]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_structural_rewrite() {
+        let rules: Vec<StructuralRewrite> = [
+            ("$a.length == 0", "$a.isEmpty()"),
+            ("trace($x)", "$x.toString()"),
+            ("Vector.<$T>", "Array.<$T>"),
+            ("Boolean($x)", "$x != null"),
+        ].into_iter().map(|(pattern, replacement)| StructuralRewrite::new(pattern, replacement).unwrap()).collect();
+        let cu = CompilationUnit::new(None, "if (items.length  ==  0) trace(a + b); // Note\nvar v: Vector.<Vector.<int>> = Boolean(y).valueOf();\n".into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        let edits = StructuralRewrite::edits(&rules, &program);
+        assert_eq!(TextEdit::apply_all(cu.text(), &edits), "if (items.isEmpty()) (a + b).toString(); // Note\nvar v: Array.<Array.<int>> = (y != null).valueOf();\n");
+        assert_eq!(edits[0], TextEdit::new(10..23, "isEmpty()"));
+
+        assert_eq!(StructuralRewrite::new("trace($x)", "log($y)").err(), Some(StructuralPatternError::UnboundPlaceholder("$y".into())));
+    }
+}
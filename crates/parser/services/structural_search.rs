@@ -0,0 +1,350 @@
+use crate::ns::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+/// A structural search pattern, written as ActionScript 3 code where
+/// identifiers starting with `$` are placeholders.
+///
+/// A placeholder matches any expression when used as an expression, any
+/// statement when used as a statement, and any name when used as the name
+/// of a property or definition. A placeholder that occurs more than once
+/// matches structurally equal nodes; the `$_` placeholder matches anything
+/// and is not captured.
+///
+/// Patterns are compared to nodes independently of locations and formatting.
+/// A pattern consisting of an expression statement matches expressions;
+/// any other pattern consists of a single directive and matches directives.
+/// Function bodies skipped by declaration-only parsing are not searched,
+/// thus programs should be parsed without `ParserOptions::declaration_only`.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let pattern = StructuralPattern::new("trace($x)").unwrap();
+/// let cu = CompilationUnit::new(None, "trace(a + b);\nlogger.trace(c);".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let matches = pattern.find(&program);
+/// assert_eq!(matches.len(), 1);
+/// assert_eq!(matches[0].location.text(), "trace(a + b)");
+/// assert_eq!(matches[0].capture("$x").unwrap().text(), "a + b");
+/// ```
+#[derive(Clone)]
+pub struct StructuralPattern {
+    root: Value,
+    is_expression: bool,
+}
+
+/// A node matched by a [`StructuralPattern`].
+#[derive(Clone, Debug, Serialize)]
+pub struct StructuralMatch {
+    pub location: Location,
+    /// The nodes matched by placeholders, in source order.
+    pub captures: Vec<(String, Location)>,
+}
+
+impl StructuralMatch {
+    pub fn capture(&self, name: &str) -> Option<&Location> {
+        self.captures.iter().find(|(capture_name, _)| capture_name == name).map(|(_, location)| location)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StructuralPatternError {
+    /// The pattern has a syntax error, given as its English message.
+    Syntax(String),
+    /// The pattern does not consist of a single directive.
+    NotSingleDirective,
+    /// The pattern consists of a placeholder only.
+    OnlyPlaceholder,
+    /// A replacement uses a placeholder that is not in the pattern.
+    UnboundPlaceholder(String),
+}
+
+impl Display for StructuralPatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "invalid pattern: {message}"),
+            Self::NotSingleDirective => f.write_str("a pattern must consist of a single expression or directive"),
+            Self::OnlyPlaceholder => f.write_str("a pattern must not consist of a placeholder only"),
+            Self::UnboundPlaceholder(name) => write!(f, "the placeholder {name} does not occur in the pattern"),
+        }
+    }
+}
+
+impl std::error::Error for StructuralPatternError {}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> Result<Self, StructuralPatternError> {
+        let (root, is_expression) = parse_root(pattern)?;
+        if node_placeholder(&root).is_some() {
+            return Err(StructuralPatternError::OnlyPlaceholder);
+        }
+        Ok(Self { root, is_expression })
+    }
+
+    /// Whether the pattern matches expressions rather than directives.
+    pub(crate) fn is_expression(&self) -> bool {
+        self.is_expression
+    }
+
+    /// Returns the names of the placeholders, and whether each is used as
+    /// an expression.
+    pub(crate) fn placeholders(&self) -> Vec<(String, bool)> {
+        let mut placeholders = vec![];
+        collect_placeholders(&self.root, &mut placeholders);
+        placeholders
+    }
+
+    /// Matches code against the root of the pattern, returning the
+    /// text of the captures.
+    pub(crate) fn match_text(&self, text: &str) -> Option<Vec<(String, String)>> {
+        let compilation_unit = CompilationUnit::new(None, text.into());
+        let (value, is_expression) = parse_root_in(&compilation_unit).ok()?;
+        let mut captures = vec![];
+        if is_expression != self.is_expression || !match_value(&self.root, &value, &mut captures) {
+            return None;
+        }
+        captures.into_iter().map(|(name, value)| Some((name, value_location(value, std::slice::from_ref(&compilation_unit))?.text()))).collect()
+    }
+
+    /// Finds the nodes of a program that match the pattern, including
+    /// nodes of included sources.
+    pub fn find(&self, program: &Rc<Program>) -> Vec<StructuralMatch> {
+        let compilation_unit = program.location.compilation_unit();
+        let mut units = vec![compilation_unit];
+        let mut i = 0;
+        while i < units.len() {
+            units.extend(units[i].nested_compilation_units());
+            i += 1;
+        }
+        let tree = tree_value(program, units.clone());
+        let mut matches = vec![];
+        self.find_in_value(&tree, &units, &mut matches);
+        matches
+    }
+
+    /// Finds the nodes that match the pattern in every `.as` file of
+    /// a directory and its subdirectories, in path order.
+    pub fn find_in_directory(&self, directory: &Path) -> std::io::Result<Vec<StructuralMatch>> {
+        let mut matches = vec![];
+        for path in source_files(directory, &["as"])? {
+            matches.extend(self.find_in_file(&path)?);
+        }
+        Ok(matches)
+    }
+
+    pub fn find_in_file(&self, path: &Path) -> std::io::Result<Vec<StructuralMatch>> {
+        let text = std::fs::read_to_string(path)?;
+        let compilation_unit = CompilationUnit::new(Some(path.to_string_lossy().into_owned()), text);
+        let program = ParserFacade(&compilation_unit, default()).parse_program();
+        Ok(self.find(&program))
+    }
+
+    fn find_in_value(&self, value: &Value, units: &[Rc<CompilationUnit>], matches: &mut Vec<StructuralMatch>) {
+        let children: Vec<&Value> = match value {
+            Value::Object(object) if !is_location(value) => object.values().collect(),
+            Value::Array(array) => array.iter().collect(),
+            _ => return,
+        };
+        let mut captures = vec![];
+        if match_value(&self.root, value, &mut captures) {
+            if let Some(location) = value_location(value, units) {
+                let mut captures: Vec<(String, Location)> = captures.into_iter()
+                    .filter_map(|(name, value)| Some((name, value_location(value, units)?)))
+                    .collect();
+                captures.sort_by_key(|(_, location)| location.first_offset());
+                matches.push(StructuralMatch { location, captures });
+            }
+        }
+        for child in children {
+            self.find_in_value(child, units, matches);
+        }
+    }
+}
+
+/// Parses code consisting of a single directive, returning the expression of
+/// an expression statement or else the directive, and whether it is an expression.
+fn parse_root(text: &str) -> Result<(Value, bool), StructuralPatternError> {
+    parse_root_in(&CompilationUnit::new(None, text.into()))
+}
+
+fn parse_root_in(compilation_unit: &Rc<CompilationUnit>) -> Result<(Value, bool), StructuralPatternError> {
+    let program = ParserFacade(compilation_unit, default()).parse_program();
+    if compilation_unit.invalidated() {
+        compilation_unit.sort_diagnostics();
+        let message = compilation_unit.diagnostics().first().map_or(String::new(), |diagnostic| diagnostic.format_english());
+        return Err(StructuralPatternError::Syntax(message));
+    }
+    if !program.packages.is_empty() || program.directives.len() != 1 {
+        return Err(StructuralPatternError::NotSingleDirective);
+    }
+    Ok(match program.directives[0].as_ref() {
+        Directive::ExpressionStatement(statement) => (tree_value(&statement.expression, vec![compilation_unit.clone()]), true),
+        directive => (tree_value(directive, vec![compilation_unit.clone()]), false),
+    })
+}
+
+/// Returns the variant name of an expression, such as `Binary`,
+/// if the code consists of an expression.
+pub(crate) fn expression_kind(text: &str) -> Option<String> {
+    match parse_root(text) {
+        Ok((value, true)) => value_kind(&value).map(str::to_owned),
+        _ => None,
+    }
+}
+
+fn value_kind(value: &Value) -> Option<&str> {
+    value.as_object().filter(|object| object.len() == 1)?.keys().next().map(String::as_str)
+}
+
+fn collect_placeholders(value: &Value, placeholders: &mut Vec<(String, bool)>) {
+    let placeholder = node_placeholder(value).map(|name| (name, value.get("QualifiedIdentifier").is_some()))
+        .or_else(|| qualified_identifier_placeholder(value).or_else(|| name_placeholder(value)).map(|name| (name, false)));
+    if let Some((name, is_expression)) = placeholder {
+        if !placeholders.iter().any(|(other, _)| other == name) {
+            placeholders.push((name.to_owned(), is_expression));
+        }
+        return;
+    }
+    match value {
+        Value::Object(object) => object.values().for_each(|value| collect_placeholders(value, placeholders)),
+        Value::Array(array) => array.iter().for_each(|value| collect_placeholders(value, placeholders)),
+        _ => {},
+    }
+}
+
+/// Serializes a node, where locations are offsets into `units`.
+fn tree_value<T: Serialize + ?Sized>(node: &T, units: Vec<Rc<CompilationUnit>>) -> Value {
+    let _guard = LocatedUnitsGuard::new(units);
+    serde_json::to_value(node).unwrap()
+}
+
+fn is_location(value: &Value) -> bool {
+    value.as_object().is_some_and(|object| object.len() == 3 && ["unit", "first_offset", "last_offset"].iter().all(|key| object.contains_key(*key)))
+}
+
+fn location_from_value(value: &Value, units: &[Rc<CompilationUnit>]) -> Option<Location> {
+    let offset = |key: &str| value.get(key).and_then(Value::as_u64).map(|offset| offset as usize);
+    Some(Location::with_offsets(units.get(offset("unit")?)?, offset("first_offset")?, offset("last_offset")?))
+}
+
+/// Returns the location of a node: its `location` field, the location
+/// of a name, or the location of the node wrapped by an enumeration variant.
+fn value_location(value: &Value, units: &[Rc<CompilationUnit>]) -> Option<Location> {
+    match value {
+        Value::Object(object) => {
+            if let Some(location) = object.get("location").filter(|location| is_location(location)) {
+                return location_from_value(location, units);
+            }
+            match object.values().next() {
+                Some(inner) if object.len() == 1 => value_location(inner, units),
+                _ => None,
+            }
+        },
+        Value::Array(array) if array.len() == 2 && is_location(&array[1]) => location_from_value(&array[1], units),
+        _ => None,
+    }
+}
+
+/// Returns the name of a placeholder qualified identifier.
+fn qualified_identifier_placeholder(value: &Value) -> Option<&str> {
+    let object = value.as_object()?;
+    if object.get("qualifier") != Some(&Value::Null) || object.get("attribute") != Some(&Value::Bool(false)) {
+        return None;
+    }
+    name_placeholder(object.get("id")?.get("Id")?)
+}
+
+/// Returns the name of a placeholder `(String, Location)` name.
+fn name_placeholder(value: &Value) -> Option<&str> {
+    match value.as_array()?.as_slice() {
+        [Value::String(name), location] if name.starts_with('$') && is_location(location) => Some(name),
+        _ => None,
+    }
+}
+
+/// Returns the name of a placeholder expression or statement.
+fn node_placeholder(value: &Value) -> Option<&str> {
+    if let Some(id) = value.get("QualifiedIdentifier") {
+        return qualified_identifier_placeholder(id);
+    }
+    let statement = value.get("ExpressionStatement")?;
+    node_placeholder(statement.get("expression")?)
+}
+
+/// Matches a value against a pattern, recording the values of placeholders.
+fn match_value<'a>(pattern: &Value, value: &'a Value, captures: &mut Vec<(String, &'a Value)>) -> bool {
+    if is_location(pattern) {
+        return is_location(value);
+    }
+    let placeholder = node_placeholder(pattern)
+        .or_else(|| qualified_identifier_placeholder(pattern))
+        .or_else(|| name_placeholder(pattern).filter(|_| value.as_array().is_some_and(|array| array.len() == 2 && array[0].is_string())));
+    if let Some(name) = placeholder {
+        if name == "$_" {
+            return true;
+        }
+        if let Some((_, captured)) = captures.iter().find(|(captured_name, _)| captured_name == name) {
+            return equal_ignoring_locations(captured, value);
+        }
+        captures.push((name.to_owned(), value));
+        return true;
+    }
+    match (pattern, value) {
+        (Value::Object(pattern), Value::Object(object)) => {
+            pattern.len() == object.len() && pattern.iter().all(|(key, pattern)| {
+                object.get(key).is_some_and(|value| {
+                    // ASDoc comments are not written in patterns.
+                    (key == "asdoc" && pattern.is_null()) || match_value(pattern, value, captures)
+                })
+            })
+        },
+        (Value::Array(pattern), Value::Array(array)) => {
+            pattern.len() == array.len() && pattern.iter().zip(array).all(|(pattern, value)| match_value(pattern, value, captures))
+        },
+        _ => pattern == value,
+    }
+}
+
+fn equal_ignoring_locations(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        _ if is_location(a) => is_location(b),
+        (Value::Object(a), Value::Object(b)) => a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| equal_ignoring_locations(a, b))),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal_ignoring_locations(a, b)),
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_structural_search() {
+        let cu = CompilationUnit::new(None, "package {\n    public class C {\n        public var trace: Function;\n        function f(list: Array) {\n            for each (var item: int in list) {\n                trace(item as String);\n            }\n            this.trace(x == x);\n            trace(x == y);\n        }\n    }\n}".into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        assert!(!cu.invalidated());
+
+        let find = |pattern: &str| -> Vec<(String, Vec<(String, String)>)> {
+            StructuralPattern::new(pattern).unwrap().find(&program).iter().map(|m| {
+                (m.location.text(), m.captures.iter().map(|(name, location)| (name.clone(), location.text())).collect())
+            }).collect()
+        };
+        assert_eq!(find("trace($x)").len(), 2);
+        assert_eq!(find("$a as $T"), [("item as String".to_owned(), vec![("$a".to_owned(), "item".to_owned()), ("$T".to_owned(), "String".to_owned())])]);
+        assert_eq!(find("for each (var $v: $_ in $e) $body")[0].1.iter().map(|(name, text)| format!("{name}={text}")).collect::<Vec<_>>(), [
+            "$v=item",
+            "$e=list",
+            "$body={\n                trace(item as String);\n            }",
+        ]);
+        assert_eq!(find("$o.$m($a == $a)")[0].1[1].1, "trace");
+        assert_eq!(find("public var $v: Function").len(), 1);
+        assert_eq!(find("var $v: Function").len(), 0);
+        assert_eq!(StructuralPattern::new("a; b").err(), Some(StructuralPatternError::NotSingleDirective));
+        assert_eq!(StructuralPattern::new("$x;").err(), Some(StructuralPatternError::OnlyPlaceholder));
+    }
+}
@@ -159,5 +159,15 @@ pub use mxml::*;
 mod css;
 pub use css::*;
 
+mod node_id;
+pub use node_id::*;
 mod node_assignment;
-pub use node_assignment::*;
\ No newline at end of file
+pub use node_assignment::*;
+mod structural_eq;
+pub use structural_eq::*;
+
+// Traversal
+mod visitor;
+pub use visitor::*;
+mod folder;
+pub use folder::*;
\ No newline at end of file
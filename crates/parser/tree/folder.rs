@@ -0,0 +1,1103 @@
+use crate::ns::*;
+
+/// Rewriting traversal over the ActionScript 3 syntax tree.
+///
+/// Every method has a default implementation that invokes the
+/// corresponding `rebuild_*` function, which folds every child of the node
+/// and returns a new node. Source locations found in the tree are
+/// passed through [`Folder::fold_location`], allowing a folder
+/// to relocate a tree into another compilation unit.
+///
+/// To replace a node by a node of another kind, override
+/// [`Folder::fold_expression`] or [`Folder::fold_directive`].
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// struct RenameTrace;
+///
+/// impl Folder for RenameTrace {
+///     fn fold_qualified_identifier(&mut self, node: &QualifiedIdentifier) -> QualifiedIdentifier {
+///         let mut node = rebuild_qualified_identifier(self, node);
+///         if let QualifiedIdentifierIdentifier::Id((name, _)) = &mut node.id {
+///             if name == "trace" {
+///                 *name = "log".into();
+///             }
+///         }
+///         node
+///     }
+/// }
+///
+/// let cu = CompilationUnit::new(None, "trace(x)".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let program = RenameTrace.fold_program(&program);
+/// let Directive::ExpressionStatement(stmt) = program.directives[0].as_ref() else { panic!() };
+/// let Expression::Call(call) = stmt.expression.as_ref() else { panic!() };
+/// assert_eq!(call.base.to_identifier_name().unwrap().0, "log");
+/// ```
+pub trait Folder: Sized {
+    fn fold_location(&mut self, location: &Location) -> Location { location.clone() }
+
+    fn fold_program(&mut self, node: &Rc<Program>) -> Rc<Program> { rebuild_program(self, node) }
+    fn fold_package_definition(&mut self, node: &Rc<PackageDefinition>) -> Rc<PackageDefinition> { rebuild_package_definition(self, node) }
+
+    // Expressions
+    fn fold_expression(&mut self, node: &Rc<Expression>) -> Rc<Expression> { rebuild_expression(self, node) }
+    fn fold_qualified_identifier(&mut self, node: &QualifiedIdentifier) -> QualifiedIdentifier { rebuild_qualified_identifier(self, node) }
+    fn fold_paren_expression(&mut self, node: &ParenExpression) -> ParenExpression { rebuild_paren_expression(self, node) }
+    fn fold_null_literal(&mut self, node: &NullLiteral) -> NullLiteral { rebuild_null_literal(self, node) }
+    fn fold_boolean_literal(&mut self, node: &BooleanLiteral) -> BooleanLiteral { rebuild_boolean_literal(self, node) }
+    fn fold_numeric_literal(&mut self, node: &NumericLiteral) -> NumericLiteral { rebuild_numeric_literal(self, node) }
+    fn fold_string_literal(&mut self, node: &StringLiteral) -> StringLiteral { rebuild_string_literal(self, node) }
+    fn fold_this_literal(&mut self, node: &ThisLiteral) -> ThisLiteral { rebuild_this_literal(self, node) }
+    fn fold_regexp_literal(&mut self, node: &RegExpLiteral) -> RegExpLiteral { rebuild_regexp_literal(self, node) }
+    fn fold_xml_expression(&mut self, node: &XmlExpression) -> XmlExpression { rebuild_xml_expression(self, node) }
+    fn fold_xml_markup_expression(&mut self, node: &XmlMarkupExpression) -> XmlMarkupExpression { rebuild_xml_markup_expression(self, node) }
+    fn fold_xml_list_expression(&mut self, node: &XmlListExpression) -> XmlListExpression { rebuild_xml_list_expression(self, node) }
+    fn fold_xml_element(&mut self, node: &Rc<XmlElement>) -> Rc<XmlElement> { rebuild_xml_element(self, node) }
+    fn fold_xml_attribute(&mut self, node: &Rc<XmlAttribute>) -> Rc<XmlAttribute> { rebuild_xml_attribute(self, node) }
+    fn fold_xml_content(&mut self, node: &Rc<XmlContent>) -> Rc<XmlContent> { rebuild_xml_content(self, node) }
+    fn fold_array_literal(&mut self, node: &ArrayLiteral) -> ArrayLiteral { rebuild_array_literal(self, node) }
+    fn fold_vector_literal(&mut self, node: &VectorLiteral) -> VectorLiteral { rebuild_vector_literal(self, node) }
+    fn fold_array_element(&mut self, node: &Element) -> Element { rebuild_array_element(self, node) }
+    fn fold_object_initializer(&mut self, node: &ObjectInitializer) -> ObjectInitializer { rebuild_object_initializer(self, node) }
+    fn fold_initializer_field(&mut self, node: &Rc<InitializerField>) -> Rc<InitializerField> { rebuild_initializer_field(self, node) }
+    fn fold_function_expression(&mut self, node: &FunctionExpression) -> FunctionExpression { rebuild_function_expression(self, node) }
+    fn fold_import_meta(&mut self, node: &ImportMeta) -> ImportMeta { rebuild_import_meta(self, node) }
+    fn fold_new_expression(&mut self, node: &NewExpression) -> NewExpression { rebuild_new_expression(self, node) }
+    fn fold_member_expression(&mut self, node: &MemberExpression) -> MemberExpression { rebuild_member_expression(self, node) }
+    fn fold_computed_member_expression(&mut self, node: &ComputedMemberExpression) -> ComputedMemberExpression { rebuild_computed_member_expression(self, node) }
+    fn fold_descendants_expression(&mut self, node: &DescendantsExpression) -> DescendantsExpression { rebuild_descendants_expression(self, node) }
+    fn fold_filter_expression(&mut self, node: &FilterExpression) -> FilterExpression { rebuild_filter_expression(self, node) }
+    fn fold_super_expression(&mut self, node: &SuperExpression) -> SuperExpression { rebuild_super_expression(self, node) }
+    fn fold_call_expression(&mut self, node: &CallExpression) -> CallExpression { rebuild_call_expression(self, node) }
+    fn fold_apply_type_expression(&mut self, node: &ApplyTypeExpression) -> ApplyTypeExpression { rebuild_apply_type_expression(self, node) }
+    fn fold_unary_expression(&mut self, node: &UnaryExpression) -> UnaryExpression { rebuild_unary_expression(self, node) }
+    fn fold_optional_chaining_expression(&mut self, node: &OptionalChainingExpression) -> OptionalChainingExpression { rebuild_optional_chaining_expression(self, node) }
+    fn fold_optional_chaining_placeholder(&mut self, node: &OptionalChainingPlaceholder) -> OptionalChainingPlaceholder { rebuild_optional_chaining_placeholder(self, node) }
+    fn fold_binary_expression(&mut self, node: &BinaryExpression) -> BinaryExpression { rebuild_binary_expression(self, node) }
+    fn fold_conditional_expression(&mut self, node: &ConditionalExpression) -> ConditionalExpression { rebuild_conditional_expression(self, node) }
+    fn fold_assignment_expression(&mut self, node: &AssignmentExpression) -> AssignmentExpression { rebuild_assignment_expression(self, node) }
+    fn fold_sequence_expression(&mut self, node: &SequenceExpression) -> SequenceExpression { rebuild_sequence_expression(self, node) }
+    fn fold_nullable_type_expression(&mut self, node: &NullableTypeExpression) -> NullableTypeExpression { rebuild_nullable_type_expression(self, node) }
+    fn fold_non_nullable_type_expression(&mut self, node: &NonNullableTypeExpression) -> NonNullableTypeExpression { rebuild_non_nullable_type_expression(self, node) }
+    fn fold_any_type_expression(&mut self, node: &AnyTypeExpression) -> AnyTypeExpression { rebuild_any_type_expression(self, node) }
+    fn fold_void_type_expression(&mut self, node: &VoidTypeExpression) -> VoidTypeExpression { rebuild_void_type_expression(self, node) }
+    fn fold_array_type_expression(&mut self, node: &ArrayTypeExpression) -> ArrayTypeExpression { rebuild_array_type_expression(self, node) }
+    fn fold_tuple_type_expression(&mut self, node: &TupleTypeExpression) -> TupleTypeExpression { rebuild_tuple_type_expression(self, node) }
+    fn fold_function_type_expression(&mut self, node: &FunctionTypeExpression) -> FunctionTypeExpression { rebuild_function_type_expression(self, node) }
+    fn fold_function_type_parameter(&mut self, node: &Rc<FunctionTypeParameter>) -> Rc<FunctionTypeParameter> { rebuild_function_type_parameter(self, node) }
+    fn fold_invalidated_node(&mut self, node: &InvalidatedNode) -> InvalidatedNode { rebuild_invalidated_node(self, node) }
+    fn fold_reserved_namespace_expression(&mut self, node: &ReservedNamespaceExpression) -> ReservedNamespaceExpression { rebuild_reserved_namespace_expression(self, node) }
+
+    // Destructuring
+    fn fold_typed_destructuring(&mut self, node: &TypedDestructuring) -> TypedDestructuring { rebuild_typed_destructuring(self, node) }
+
+    // Statements
+    fn fold_directive(&mut self, node: &Rc<Directive>) -> Rc<Directive> { rebuild_directive(self, node) }
+    fn fold_empty_statement(&mut self, node: &EmptyStatement) -> EmptyStatement { rebuild_empty_statement(self, node) }
+    fn fold_expression_statement(&mut self, node: &ExpressionStatement) -> ExpressionStatement { rebuild_expression_statement(self, node) }
+    fn fold_super_statement(&mut self, node: &SuperStatement) -> SuperStatement { rebuild_super_statement(self, node) }
+    fn fold_block(&mut self, node: &Block) -> Block { rebuild_block(self, node) }
+    fn fold_labeled_statement(&mut self, node: &LabeledStatement) -> LabeledStatement { rebuild_labeled_statement(self, node) }
+    fn fold_if_statement(&mut self, node: &IfStatement) -> IfStatement { rebuild_if_statement(self, node) }
+    fn fold_switch_statement(&mut self, node: &SwitchStatement) -> SwitchStatement { rebuild_switch_statement(self, node) }
+    fn fold_case(&mut self, node: &Case) -> Case { rebuild_case(self, node) }
+    fn fold_switch_type_statement(&mut self, node: &SwitchTypeStatement) -> SwitchTypeStatement { rebuild_switch_type_statement(self, node) }
+    fn fold_type_case(&mut self, node: &TypeCase) -> TypeCase { rebuild_type_case(self, node) }
+    fn fold_do_statement(&mut self, node: &DoStatement) -> DoStatement { rebuild_do_statement(self, node) }
+    fn fold_while_statement(&mut self, node: &WhileStatement) -> WhileStatement { rebuild_while_statement(self, node) }
+    fn fold_for_statement(&mut self, node: &ForStatement) -> ForStatement { rebuild_for_statement(self, node) }
+    fn fold_for_in_statement(&mut self, node: &ForInStatement) -> ForInStatement { rebuild_for_in_statement(self, node) }
+    fn fold_simple_variable_definition(&mut self, node: &Rc<SimpleVariableDefinition>) -> Rc<SimpleVariableDefinition> { rebuild_simple_variable_definition(self, node) }
+    fn fold_break_statement(&mut self, node: &BreakStatement) -> BreakStatement { rebuild_break_statement(self, node) }
+    fn fold_continue_statement(&mut self, node: &ContinueStatement) -> ContinueStatement { rebuild_continue_statement(self, node) }
+    fn fold_with_statement(&mut self, node: &WithStatement) -> WithStatement { rebuild_with_statement(self, node) }
+    fn fold_return_statement(&mut self, node: &ReturnStatement) -> ReturnStatement { rebuild_return_statement(self, node) }
+    fn fold_throw_statement(&mut self, node: &ThrowStatement) -> ThrowStatement { rebuild_throw_statement(self, node) }
+    fn fold_default_xml_namespace_statement(&mut self, node: &DefaultXmlNamespaceStatement) -> DefaultXmlNamespaceStatement { rebuild_default_xml_namespace_statement(self, node) }
+    fn fold_try_statement(&mut self, node: &TryStatement) -> TryStatement { rebuild_try_statement(self, node) }
+    fn fold_catch_clause(&mut self, node: &CatchClause) -> CatchClause { rebuild_catch_clause(self, node) }
+    fn fold_finally_clause(&mut self, node: &FinallyClause) -> FinallyClause { rebuild_finally_clause(self, node) }
+
+    // Directives
+    fn fold_configuration_directive(&mut self, node: &ConfigurationDirective) -> ConfigurationDirective { rebuild_configuration_directive(self, node) }
+    fn fold_import_directive(&mut self, node: &ImportDirective) -> ImportDirective { rebuild_import_directive(self, node) }
+    fn fold_use_namespace_directive(&mut self, node: &UseNamespaceDirective) -> UseNamespaceDirective { rebuild_use_namespace_directive(self, node) }
+    fn fold_include_directive(&mut self, node: &IncludeDirective) -> IncludeDirective { rebuild_include_directive(self, node) }
+    fn fold_package_concat_directive(&mut self, node: &PackageConcatDirective) -> PackageConcatDirective { rebuild_package_concat_directive(self, node) }
+    fn fold_directive_injection_node(&mut self, node: &DirectiveInjectionNode) -> DirectiveInjectionNode { rebuild_directive_injection_node(self, node) }
+
+    // Miscellaneous
+    fn fold_attribute(&mut self, node: &Attribute) -> Attribute { rebuild_attribute(self, node) }
+    fn fold_metadata(&mut self, node: &Rc<Metadata>) -> Rc<Metadata> { rebuild_metadata(self, node) }
+    fn fold_metadata_entry(&mut self, node: &Rc<MetadataEntry>) -> Rc<MetadataEntry> { rebuild_metadata_entry(self, node) }
+    fn fold_asdoc(&mut self, node: &Rc<Asdoc>) -> Rc<Asdoc> { rebuild_asdoc(self, node) }
+    fn fold_asdoc_reference(&mut self, node: &Rc<AsdocReference>) -> Rc<AsdocReference> { rebuild_asdoc_reference(self, node) }
+    fn fold_type_parameter(&mut self, node: &Rc<TypeParameter>) -> Rc<TypeParameter> { rebuild_type_parameter(self, node) }
+
+    // Definitions
+    fn fold_variable_definition(&mut self, node: &VariableDefinition) -> VariableDefinition { rebuild_variable_definition(self, node) }
+    fn fold_variable_binding(&mut self, node: &Rc<VariableBinding>) -> Rc<VariableBinding> { rebuild_variable_binding(self, node) }
+    fn fold_function_definition(&mut self, node: &FunctionDefinition) -> FunctionDefinition { rebuild_function_definition(self, node) }
+    fn fold_function_common(&mut self, node: &Rc<FunctionCommon>) -> Rc<FunctionCommon> { rebuild_function_common(self, node) }
+    fn fold_function_signature(&mut self, node: &FunctionSignature) -> FunctionSignature { rebuild_function_signature(self, node) }
+    fn fold_this_parameter(&mut self, node: &Rc<ThisParameter>) -> Rc<ThisParameter> { rebuild_this_parameter(self, node) }
+    fn fold_parameter(&mut self, node: &Rc<Parameter>) -> Rc<Parameter> { rebuild_parameter(self, node) }
+    fn fold_class_definition(&mut self, node: &ClassDefinition) -> ClassDefinition { rebuild_class_definition(self, node) }
+    fn fold_enum_definition(&mut self, node: &EnumDefinition) -> EnumDefinition { rebuild_enum_definition(self, node) }
+    fn fold_interface_definition(&mut self, node: &InterfaceDefinition) -> InterfaceDefinition { rebuild_interface_definition(self, node) }
+    fn fold_type_definition(&mut self, node: &TypeDefinition) -> TypeDefinition { rebuild_type_definition(self, node) }
+    fn fold_namespace_definition(&mut self, node: &NamespaceDefinition) -> NamespaceDefinition { rebuild_namespace_definition(self, node) }
+}
+
+fn fold_name<F: Folder>(f: &mut F, name: &(String, Location)) -> (String, Location) {
+    (name.0.clone(), f.fold_location(&name.1))
+}
+
+fn fold_names<F: Folder>(f: &mut F, names: &[(String, Location)]) -> Vec<(String, Location)> {
+    names.iter().map(|name| fold_name(f, name)).collect()
+}
+
+fn fold_expressions<F: Folder>(f: &mut F, list: &[Rc<Expression>]) -> Vec<Rc<Expression>> {
+    list.iter().map(|exp| f.fold_expression(exp)).collect()
+}
+
+fn fold_opt_expression<F: Folder>(f: &mut F, exp: &Option<Rc<Expression>>) -> Option<Rc<Expression>> {
+    exp.as_ref().map(|exp| f.fold_expression(exp))
+}
+
+fn fold_directives<F: Folder>(f: &mut F, list: &[Rc<Directive>]) -> Vec<Rc<Directive>> {
+    list.iter().map(|drtv| f.fold_directive(drtv)).collect()
+}
+
+fn fold_block_rc<F: Folder>(f: &mut F, block: &Rc<Block>) -> Rc<Block> {
+    Rc::new(f.fold_block(block))
+}
+
+fn fold_opt_asdoc<F: Folder>(f: &mut F, asdoc: &Option<Rc<Asdoc>>) -> Option<Rc<Asdoc>> {
+    asdoc.as_ref().map(|asdoc| f.fold_asdoc(asdoc))
+}
+
+fn fold_attributes<F: Folder>(f: &mut F, list: &[Attribute]) -> Vec<Attribute> {
+    list.iter().map(|attr| f.fold_attribute(attr)).collect()
+}
+
+fn fold_type_parameters<F: Folder>(f: &mut F, list: &Option<Vec<Rc<TypeParameter>>>) -> Option<Vec<Rc<TypeParameter>>> {
+    list.as_ref().map(|list| list.iter().map(|param| f.fold_type_parameter(param)).collect())
+}
+
+fn fold_import_specifier<F: Folder>(f: &mut F, specifier: &ImportSpecifier) -> ImportSpecifier {
+    match specifier {
+        ImportSpecifier::Wildcard(loc) => ImportSpecifier::Wildcard(f.fold_location(loc)),
+        ImportSpecifier::Recursive(loc) => ImportSpecifier::Recursive(f.fold_location(loc)),
+        ImportSpecifier::Identifier(name) => ImportSpecifier::Identifier(fold_name(f, name)),
+    }
+}
+
+fn fold_xml_tag_name<F: Folder>(f: &mut F, name: &XmlTagName) -> XmlTagName {
+    match name {
+        XmlTagName::Name(name) => XmlTagName::Name(fold_name(f, name)),
+        XmlTagName::Expression(exp) => XmlTagName::Expression(f.fold_expression(exp)),
+    }
+}
+
+pub fn rebuild_program<F: Folder>(f: &mut F, node: &Rc<Program>) -> Rc<Program> {
+    Rc::new(Program {
+        location: f.fold_location(&node.location),
+        packages: node.packages.iter().map(|pckg| f.fold_package_definition(pckg)).collect(),
+        directives: fold_directives(f, &node.directives),
+    })
+}
+
+pub fn rebuild_package_definition<F: Folder>(f: &mut F, node: &Rc<PackageDefinition>) -> Rc<PackageDefinition> {
+    Rc::new(PackageDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        name: fold_names(f, &node.name),
+        block: fold_block_rc(f, &node.block),
+    })
+}
+
+pub fn rebuild_expression<F: Folder>(f: &mut F, node: &Rc<Expression>) -> Rc<Expression> {
+    Rc::new(match node.as_ref() {
+        Expression::QualifiedIdentifier(e) => Expression::QualifiedIdentifier(f.fold_qualified_identifier(e)),
+        Expression::Paren(e) => Expression::Paren(f.fold_paren_expression(e)),
+        Expression::NullLiteral(e) => Expression::NullLiteral(f.fold_null_literal(e)),
+        Expression::BooleanLiteral(e) => Expression::BooleanLiteral(f.fold_boolean_literal(e)),
+        Expression::NumericLiteral(e) => Expression::NumericLiteral(f.fold_numeric_literal(e)),
+        Expression::StringLiteral(e) => Expression::StringLiteral(f.fold_string_literal(e)),
+        Expression::ThisLiteral(e) => Expression::ThisLiteral(f.fold_this_literal(e)),
+        Expression::RegExpLiteral(e) => Expression::RegExpLiteral(f.fold_regexp_literal(e)),
+        Expression::Xml(e) => Expression::Xml(f.fold_xml_expression(e)),
+        Expression::XmlMarkup(e) => Expression::XmlMarkup(f.fold_xml_markup_expression(e)),
+        Expression::XmlList(e) => Expression::XmlList(f.fold_xml_list_expression(e)),
+        Expression::ArrayLiteral(e) => Expression::ArrayLiteral(f.fold_array_literal(e)),
+        Expression::VectorLiteral(e) => Expression::VectorLiteral(f.fold_vector_literal(e)),
+        Expression::ObjectInitializer(e) => Expression::ObjectInitializer(f.fold_object_initializer(e)),
+        Expression::Function(e) => Expression::Function(f.fold_function_expression(e)),
+        Expression::ImportMeta(e) => Expression::ImportMeta(f.fold_import_meta(e)),
+        Expression::New(e) => Expression::New(f.fold_new_expression(e)),
+        Expression::Member(e) => Expression::Member(f.fold_member_expression(e)),
+        Expression::ComputedMember(e) => Expression::ComputedMember(f.fold_computed_member_expression(e)),
+        Expression::Descendants(e) => Expression::Descendants(f.fold_descendants_expression(e)),
+        Expression::Filter(e) => Expression::Filter(f.fold_filter_expression(e)),
+        Expression::Super(e) => Expression::Super(f.fold_super_expression(e)),
+        Expression::Call(e) => Expression::Call(f.fold_call_expression(e)),
+        Expression::WithTypeArguments(e) => Expression::WithTypeArguments(f.fold_apply_type_expression(e)),
+        Expression::Unary(e) => Expression::Unary(f.fold_unary_expression(e)),
+        Expression::OptionalChaining(e) => Expression::OptionalChaining(f.fold_optional_chaining_expression(e)),
+        Expression::OptionalChainingPlaceholder(e) => Expression::OptionalChainingPlaceholder(f.fold_optional_chaining_placeholder(e)),
+        Expression::Binary(e) => Expression::Binary(f.fold_binary_expression(e)),
+        Expression::Conditional(e) => Expression::Conditional(f.fold_conditional_expression(e)),
+        Expression::Assignment(e) => Expression::Assignment(f.fold_assignment_expression(e)),
+        Expression::Sequence(e) => Expression::Sequence(f.fold_sequence_expression(e)),
+        Expression::NullableType(e) => Expression::NullableType(f.fold_nullable_type_expression(e)),
+        Expression::NonNullableType(e) => Expression::NonNullableType(f.fold_non_nullable_type_expression(e)),
+        Expression::AnyType(e) => Expression::AnyType(f.fold_any_type_expression(e)),
+        Expression::VoidType(e) => Expression::VoidType(f.fold_void_type_expression(e)),
+        Expression::ArrayType(e) => Expression::ArrayType(f.fold_array_type_expression(e)),
+        Expression::TupleType(e) => Expression::TupleType(f.fold_tuple_type_expression(e)),
+        Expression::FunctionType(e) => Expression::FunctionType(f.fold_function_type_expression(e)),
+        Expression::Invalidated(e) => Expression::Invalidated(f.fold_invalidated_node(e)),
+        Expression::ReservedNamespace(e) => Expression::ReservedNamespace(f.fold_reserved_namespace_expression(e)),
+    })
+}
+
+pub fn rebuild_qualified_identifier<F: Folder>(f: &mut F, node: &QualifiedIdentifier) -> QualifiedIdentifier {
+    QualifiedIdentifier {
+        location: f.fold_location(&node.location),
+        attribute: node.attribute,
+        qualifier: fold_opt_expression(f, &node.qualifier),
+        id: match &node.id {
+            QualifiedIdentifierIdentifier::Id(name) => QualifiedIdentifierIdentifier::Id(fold_name(f, name)),
+            QualifiedIdentifierIdentifier::Brackets(exp) => QualifiedIdentifierIdentifier::Brackets(f.fold_expression(exp)),
+        },
+    }
+}
+
+pub fn rebuild_paren_expression<F: Folder>(f: &mut F, node: &ParenExpression) -> ParenExpression {
+    ParenExpression {
+        location: f.fold_location(&node.location),
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+pub fn rebuild_null_literal<F: Folder>(f: &mut F, node: &NullLiteral) -> NullLiteral {
+    NullLiteral { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_boolean_literal<F: Folder>(f: &mut F, node: &BooleanLiteral) -> BooleanLiteral {
+    BooleanLiteral { location: f.fold_location(&node.location), value: node.value }
+}
+
+pub fn rebuild_numeric_literal<F: Folder>(f: &mut F, node: &NumericLiteral) -> NumericLiteral {
+    NumericLiteral { location: f.fold_location(&node.location), value: node.value.clone(), suffix: node.suffix }
+}
+
+pub fn rebuild_string_literal<F: Folder>(f: &mut F, node: &StringLiteral) -> StringLiteral {
+    StringLiteral { location: f.fold_location(&node.location), value: node.value.clone() }
+}
+
+pub fn rebuild_this_literal<F: Folder>(f: &mut F, node: &ThisLiteral) -> ThisLiteral {
+    ThisLiteral { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_regexp_literal<F: Folder>(f: &mut F, node: &RegExpLiteral) -> RegExpLiteral {
+    RegExpLiteral { location: f.fold_location(&node.location), body: node.body.clone(), flags: node.flags.clone() }
+}
+
+pub fn rebuild_xml_expression<F: Folder>(f: &mut F, node: &XmlExpression) -> XmlExpression {
+    XmlExpression {
+        location: f.fold_location(&node.location),
+        element: f.fold_xml_element(&node.element),
+    }
+}
+
+pub fn rebuild_xml_markup_expression<F: Folder>(f: &mut F, node: &XmlMarkupExpression) -> XmlMarkupExpression {
+    XmlMarkupExpression { location: f.fold_location(&node.location), markup: node.markup.clone() }
+}
+
+pub fn rebuild_xml_list_expression<F: Folder>(f: &mut F, node: &XmlListExpression) -> XmlListExpression {
+    XmlListExpression {
+        location: f.fold_location(&node.location),
+        content: node.content.iter().map(|content| f.fold_xml_content(content)).collect(),
+    }
+}
+
+pub fn rebuild_xml_element<F: Folder>(f: &mut F, node: &Rc<XmlElement>) -> Rc<XmlElement> {
+    Rc::new(XmlElement {
+        location: f.fold_location(&node.location),
+        name: fold_xml_tag_name(f, &node.name),
+        attributes: node.attributes.iter().map(|attr| f.fold_xml_attribute(attr)).collect(),
+        attribute_expression: fold_opt_expression(f, &node.attribute_expression),
+        content: node.content.as_ref().map(|content| content.iter().map(|content| f.fold_xml_content(content)).collect()),
+        closing_name: node.closing_name.as_ref().map(|name| fold_xml_tag_name(f, name)),
+    })
+}
+
+pub fn rebuild_xml_attribute<F: Folder>(f: &mut F, node: &Rc<XmlAttribute>) -> Rc<XmlAttribute> {
+    Rc::new(XmlAttribute {
+        location: f.fold_location(&node.location),
+        name: fold_name(f, &node.name),
+        value: match &node.value {
+            XmlAttributeValue::Value(value) => XmlAttributeValue::Value(fold_name(f, value)),
+            XmlAttributeValue::Expression(exp) => XmlAttributeValue::Expression(f.fold_expression(exp)),
+        },
+    })
+}
+
+pub fn rebuild_xml_content<F: Folder>(f: &mut F, node: &Rc<XmlContent>) -> Rc<XmlContent> {
+    Rc::new(match node.as_ref() {
+        XmlContent::Characters(chars) => XmlContent::Characters(fold_name(f, chars)),
+        XmlContent::Markup(markup) => XmlContent::Markup(fold_name(f, markup)),
+        XmlContent::Element(elem) => XmlContent::Element(f.fold_xml_element(elem)),
+        XmlContent::Expression(exp) => XmlContent::Expression(f.fold_expression(exp)),
+    })
+}
+
+pub fn rebuild_array_literal<F: Folder>(f: &mut F, node: &ArrayLiteral) -> ArrayLiteral {
+    ArrayLiteral {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        elements: node.elements.iter().map(|elem| f.fold_array_element(elem)).collect(),
+    }
+}
+
+pub fn rebuild_vector_literal<F: Folder>(f: &mut F, node: &VectorLiteral) -> VectorLiteral {
+    VectorLiteral {
+        location: f.fold_location(&node.location),
+        element_type: f.fold_expression(&node.element_type),
+        elements: node.elements.iter().map(|elem| f.fold_array_element(elem)).collect(),
+    }
+}
+
+pub fn rebuild_array_element<F: Folder>(f: &mut F, node: &Element) -> Element {
+    match node {
+        Element::Elision => Element::Elision,
+        Element::Expression(exp) => Element::Expression(f.fold_expression(exp)),
+        Element::Rest((exp, loc)) => Element::Rest((f.fold_expression(exp), f.fold_location(loc))),
+    }
+}
+
+pub fn rebuild_object_initializer<F: Folder>(f: &mut F, node: &ObjectInitializer) -> ObjectInitializer {
+    ObjectInitializer {
+        location: f.fold_location(&node.location),
+        fields: node.fields.iter().map(|field| f.fold_initializer_field(field)).collect(),
+    }
+}
+
+pub fn rebuild_initializer_field<F: Folder>(f: &mut F, node: &Rc<InitializerField>) -> Rc<InitializerField> {
+    Rc::new(match node.as_ref() {
+        InitializerField::Field { name, non_null, value } => {
+            let field_name = match &name.0 {
+                FieldName::Identifier(id) => FieldName::Identifier(f.fold_qualified_identifier(id)),
+                FieldName::Brackets(exp) => FieldName::Brackets(f.fold_expression(exp)),
+                FieldName::StringLiteral(exp) => FieldName::StringLiteral(f.fold_expression(exp)),
+                FieldName::NumericLiteral(exp) => FieldName::NumericLiteral(f.fold_expression(exp)),
+            };
+            InitializerField::Field {
+                name: (field_name, f.fold_location(&name.1)),
+                non_null: *non_null,
+                value: fold_opt_expression(f, value),
+            }
+        },
+        InitializerField::Rest((exp, loc)) => InitializerField::Rest((f.fold_expression(exp), f.fold_location(loc))),
+    })
+}
+
+pub fn rebuild_function_expression<F: Folder>(f: &mut F, node: &FunctionExpression) -> FunctionExpression {
+    FunctionExpression {
+        location: f.fold_location(&node.location),
+        name: node.name.as_ref().map(|name| fold_name(f, name)),
+        common: f.fold_function_common(&node.common),
+    }
+}
+
+pub fn rebuild_import_meta<F: Folder>(f: &mut F, node: &ImportMeta) -> ImportMeta {
+    ImportMeta { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_new_expression<F: Folder>(f: &mut F, node: &NewExpression) -> NewExpression {
+    NewExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        arguments: node.arguments.as_ref().map(|list| fold_expressions(f, list)),
+    }
+}
+
+pub fn rebuild_member_expression<F: Folder>(f: &mut F, node: &MemberExpression) -> MemberExpression {
+    MemberExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        identifier: f.fold_qualified_identifier(&node.identifier),
+    }
+}
+
+pub fn rebuild_computed_member_expression<F: Folder>(f: &mut F, node: &ComputedMemberExpression) -> ComputedMemberExpression {
+    ComputedMemberExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        key: f.fold_expression(&node.key),
+    }
+}
+
+pub fn rebuild_descendants_expression<F: Folder>(f: &mut F, node: &DescendantsExpression) -> DescendantsExpression {
+    DescendantsExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        identifier: f.fold_qualified_identifier(&node.identifier),
+    }
+}
+
+pub fn rebuild_filter_expression<F: Folder>(f: &mut F, node: &FilterExpression) -> FilterExpression {
+    FilterExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        test: f.fold_expression(&node.test),
+    }
+}
+
+pub fn rebuild_super_expression<F: Folder>(f: &mut F, node: &SuperExpression) -> SuperExpression {
+    SuperExpression {
+        location: f.fold_location(&node.location),
+        object: node.object.as_ref().map(|list| fold_expressions(f, list)),
+    }
+}
+
+pub fn rebuild_call_expression<F: Folder>(f: &mut F, node: &CallExpression) -> CallExpression {
+    CallExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        arguments: fold_expressions(f, &node.arguments),
+    }
+}
+
+pub fn rebuild_apply_type_expression<F: Folder>(f: &mut F, node: &ApplyTypeExpression) -> ApplyTypeExpression {
+    ApplyTypeExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        arguments: fold_expressions(f, &node.arguments),
+    }
+}
+
+pub fn rebuild_unary_expression<F: Folder>(f: &mut F, node: &UnaryExpression) -> UnaryExpression {
+    UnaryExpression {
+        location: f.fold_location(&node.location),
+        operator: node.operator,
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+pub fn rebuild_optional_chaining_expression<F: Folder>(f: &mut F, node: &OptionalChainingExpression) -> OptionalChainingExpression {
+    OptionalChainingExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+pub fn rebuild_optional_chaining_placeholder<F: Folder>(f: &mut F, node: &OptionalChainingPlaceholder) -> OptionalChainingPlaceholder {
+    OptionalChainingPlaceholder { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_binary_expression<F: Folder>(f: &mut F, node: &BinaryExpression) -> BinaryExpression {
+    BinaryExpression {
+        location: f.fold_location(&node.location),
+        operator: node.operator,
+        left: f.fold_expression(&node.left),
+        right: f.fold_expression(&node.right),
+    }
+}
+
+pub fn rebuild_conditional_expression<F: Folder>(f: &mut F, node: &ConditionalExpression) -> ConditionalExpression {
+    ConditionalExpression {
+        location: f.fold_location(&node.location),
+        test: f.fold_expression(&node.test),
+        consequent: f.fold_expression(&node.consequent),
+        alternative: f.fold_expression(&node.alternative),
+    }
+}
+
+pub fn rebuild_assignment_expression<F: Folder>(f: &mut F, node: &AssignmentExpression) -> AssignmentExpression {
+    AssignmentExpression {
+        location: f.fold_location(&node.location),
+        compound: node.compound,
+        left: f.fold_expression(&node.left),
+        right: f.fold_expression(&node.right),
+    }
+}
+
+pub fn rebuild_sequence_expression<F: Folder>(f: &mut F, node: &SequenceExpression) -> SequenceExpression {
+    SequenceExpression {
+        location: f.fold_location(&node.location),
+        left: f.fold_expression(&node.left),
+        right: f.fold_expression(&node.right),
+    }
+}
+
+pub fn rebuild_nullable_type_expression<F: Folder>(f: &mut F, node: &NullableTypeExpression) -> NullableTypeExpression {
+    NullableTypeExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+    }
+}
+
+pub fn rebuild_non_nullable_type_expression<F: Folder>(f: &mut F, node: &NonNullableTypeExpression) -> NonNullableTypeExpression {
+    NonNullableTypeExpression {
+        location: f.fold_location(&node.location),
+        base: f.fold_expression(&node.base),
+    }
+}
+
+pub fn rebuild_any_type_expression<F: Folder>(f: &mut F, node: &AnyTypeExpression) -> AnyTypeExpression {
+    AnyTypeExpression { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_void_type_expression<F: Folder>(f: &mut F, node: &VoidTypeExpression) -> VoidTypeExpression {
+    VoidTypeExpression { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_array_type_expression<F: Folder>(f: &mut F, node: &ArrayTypeExpression) -> ArrayTypeExpression {
+    ArrayTypeExpression {
+        location: f.fold_location(&node.location),
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+pub fn rebuild_tuple_type_expression<F: Folder>(f: &mut F, node: &TupleTypeExpression) -> TupleTypeExpression {
+    TupleTypeExpression {
+        location: f.fold_location(&node.location),
+        expressions: fold_expressions(f, &node.expressions),
+    }
+}
+
+pub fn rebuild_function_type_expression<F: Folder>(f: &mut F, node: &FunctionTypeExpression) -> FunctionTypeExpression {
+    FunctionTypeExpression {
+        location: f.fold_location(&node.location),
+        parameters: node.parameters.iter().map(|param| f.fold_function_type_parameter(param)).collect(),
+        result_type: fold_opt_expression(f, &node.result_type),
+    }
+}
+
+pub fn rebuild_function_type_parameter<F: Folder>(f: &mut F, node: &Rc<FunctionTypeParameter>) -> Rc<FunctionTypeParameter> {
+    Rc::new(FunctionTypeParameter {
+        location: f.fold_location(&node.location),
+        kind: node.kind,
+        type_expression: fold_opt_expression(f, &node.type_expression),
+    })
+}
+
+pub fn rebuild_invalidated_node<F: Folder>(f: &mut F, node: &InvalidatedNode) -> InvalidatedNode {
+    InvalidatedNode { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_reserved_namespace_expression<F: Folder>(f: &mut F, node: &ReservedNamespaceExpression) -> ReservedNamespaceExpression {
+    match node {
+        ReservedNamespaceExpression::Public(loc) => ReservedNamespaceExpression::Public(f.fold_location(loc)),
+        ReservedNamespaceExpression::Private(loc) => ReservedNamespaceExpression::Private(f.fold_location(loc)),
+        ReservedNamespaceExpression::Protected(loc) => ReservedNamespaceExpression::Protected(f.fold_location(loc)),
+        ReservedNamespaceExpression::Internal(loc) => ReservedNamespaceExpression::Internal(f.fold_location(loc)),
+    }
+}
+
+pub fn rebuild_typed_destructuring<F: Folder>(f: &mut F, node: &TypedDestructuring) -> TypedDestructuring {
+    TypedDestructuring {
+        location: f.fold_location(&node.location),
+        destructuring: f.fold_expression(&node.destructuring),
+        type_annotation: fold_opt_expression(f, &node.type_annotation),
+    }
+}
+
+pub fn rebuild_directive<F: Folder>(f: &mut F, node: &Rc<Directive>) -> Rc<Directive> {
+    Rc::new(match node.as_ref() {
+        Directive::EmptyStatement(d) => Directive::EmptyStatement(f.fold_empty_statement(d)),
+        Directive::ExpressionStatement(d) => Directive::ExpressionStatement(f.fold_expression_statement(d)),
+        Directive::SuperStatement(d) => Directive::SuperStatement(f.fold_super_statement(d)),
+        Directive::Block(d) => Directive::Block(f.fold_block(d)),
+        Directive::LabeledStatement(d) => Directive::LabeledStatement(f.fold_labeled_statement(d)),
+        Directive::IfStatement(d) => Directive::IfStatement(f.fold_if_statement(d)),
+        Directive::SwitchStatement(d) => Directive::SwitchStatement(f.fold_switch_statement(d)),
+        Directive::SwitchTypeStatement(d) => Directive::SwitchTypeStatement(f.fold_switch_type_statement(d)),
+        Directive::DoStatement(d) => Directive::DoStatement(f.fold_do_statement(d)),
+        Directive::WhileStatement(d) => Directive::WhileStatement(f.fold_while_statement(d)),
+        Directive::ForStatement(d) => Directive::ForStatement(f.fold_for_statement(d)),
+        Directive::ForInStatement(d) => Directive::ForInStatement(f.fold_for_in_statement(d)),
+        Directive::BreakStatement(d) => Directive::BreakStatement(f.fold_break_statement(d)),
+        Directive::ContinueStatement(d) => Directive::ContinueStatement(f.fold_continue_statement(d)),
+        Directive::WithStatement(d) => Directive::WithStatement(f.fold_with_statement(d)),
+        Directive::ReturnStatement(d) => Directive::ReturnStatement(f.fold_return_statement(d)),
+        Directive::ThrowStatement(d) => Directive::ThrowStatement(f.fold_throw_statement(d)),
+        Directive::DefaultXmlNamespaceStatement(d) => Directive::DefaultXmlNamespaceStatement(f.fold_default_xml_namespace_statement(d)),
+        Directive::TryStatement(d) => Directive::TryStatement(f.fold_try_statement(d)),
+        Directive::Invalidated(d) => Directive::Invalidated(f.fold_invalidated_node(d)),
+        Directive::ConfigurationDirective(d) => Directive::ConfigurationDirective(f.fold_configuration_directive(d)),
+        Directive::ImportDirective(d) => Directive::ImportDirective(f.fold_import_directive(d)),
+        Directive::UseNamespaceDirective(d) => Directive::UseNamespaceDirective(f.fold_use_namespace_directive(d)),
+        Directive::IncludeDirective(d) => Directive::IncludeDirective(f.fold_include_directive(d)),
+        Directive::PackageConcatDirective(d) => Directive::PackageConcatDirective(f.fold_package_concat_directive(d)),
+        Directive::DirectiveInjection(d) => Directive::DirectiveInjection(f.fold_directive_injection_node(d)),
+        Directive::VariableDefinition(d) => Directive::VariableDefinition(f.fold_variable_definition(d)),
+        Directive::FunctionDefinition(d) => Directive::FunctionDefinition(f.fold_function_definition(d)),
+        Directive::ClassDefinition(d) => Directive::ClassDefinition(f.fold_class_definition(d)),
+        Directive::EnumDefinition(d) => Directive::EnumDefinition(f.fold_enum_definition(d)),
+        Directive::InterfaceDefinition(d) => Directive::InterfaceDefinition(f.fold_interface_definition(d)),
+        Directive::TypeDefinition(d) => Directive::TypeDefinition(f.fold_type_definition(d)),
+        Directive::NamespaceDefinition(d) => Directive::NamespaceDefinition(f.fold_namespace_definition(d)),
+    })
+}
+
+pub fn rebuild_empty_statement<F: Folder>(f: &mut F, node: &EmptyStatement) -> EmptyStatement {
+    EmptyStatement { location: f.fold_location(&node.location) }
+}
+
+pub fn rebuild_expression_statement<F: Folder>(f: &mut F, node: &ExpressionStatement) -> ExpressionStatement {
+    ExpressionStatement {
+        location: f.fold_location(&node.location),
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+pub fn rebuild_super_statement<F: Folder>(f: &mut F, node: &SuperStatement) -> SuperStatement {
+    SuperStatement {
+        location: f.fold_location(&node.location),
+        arguments: fold_expressions(f, &node.arguments),
+    }
+}
+
+pub fn rebuild_block<F: Folder>(f: &mut F, node: &Block) -> Block {
+    Block {
+        location: f.fold_location(&node.location),
+        directives: fold_directives(f, &node.directives),
+    }
+}
+
+pub fn rebuild_labeled_statement<F: Folder>(f: &mut F, node: &LabeledStatement) -> LabeledStatement {
+    LabeledStatement {
+        location: f.fold_location(&node.location),
+        label: fold_name(f, &node.label),
+        substatement: f.fold_directive(&node.substatement),
+    }
+}
+
+pub fn rebuild_if_statement<F: Folder>(f: &mut F, node: &IfStatement) -> IfStatement {
+    IfStatement {
+        location: f.fold_location(&node.location),
+        test: f.fold_expression(&node.test),
+        consequent: f.fold_directive(&node.consequent),
+        alternative: node.alternative.as_ref().map(|drtv| f.fold_directive(drtv)),
+    }
+}
+
+pub fn rebuild_switch_statement<F: Folder>(f: &mut F, node: &SwitchStatement) -> SwitchStatement {
+    SwitchStatement {
+        location: f.fold_location(&node.location),
+        discriminant: f.fold_expression(&node.discriminant),
+        cases: node.cases.iter().map(|case| f.fold_case(case)).collect(),
+    }
+}
+
+pub fn rebuild_case<F: Folder>(f: &mut F, node: &Case) -> Case {
+    Case {
+        location: f.fold_location(&node.location),
+        labels: node.labels.iter().map(|label| match label {
+            CaseLabel::Case((exp, loc)) => CaseLabel::Case((f.fold_expression(exp), f.fold_location(loc))),
+            CaseLabel::Default(loc) => CaseLabel::Default(f.fold_location(loc)),
+        }).collect(),
+        directives: fold_directives(f, &node.directives),
+    }
+}
+
+pub fn rebuild_switch_type_statement<F: Folder>(f: &mut F, node: &SwitchTypeStatement) -> SwitchTypeStatement {
+    SwitchTypeStatement {
+        location: f.fold_location(&node.location),
+        discriminant: f.fold_expression(&node.discriminant),
+        cases: node.cases.iter().map(|case| f.fold_type_case(case)).collect(),
+    }
+}
+
+pub fn rebuild_type_case<F: Folder>(f: &mut F, node: &TypeCase) -> TypeCase {
+    TypeCase {
+        location: f.fold_location(&node.location),
+        parameter: node.parameter.as_ref().map(|param| f.fold_typed_destructuring(param)),
+        block: fold_block_rc(f, &node.block),
+    }
+}
+
+pub fn rebuild_do_statement<F: Folder>(f: &mut F, node: &DoStatement) -> DoStatement {
+    DoStatement {
+        location: f.fold_location(&node.location),
+        body: f.fold_directive(&node.body),
+        test: f.fold_expression(&node.test),
+    }
+}
+
+pub fn rebuild_while_statement<F: Folder>(f: &mut F, node: &WhileStatement) -> WhileStatement {
+    WhileStatement {
+        location: f.fold_location(&node.location),
+        test: f.fold_expression(&node.test),
+        body: f.fold_directive(&node.body),
+    }
+}
+
+pub fn rebuild_for_statement<F: Folder>(f: &mut F, node: &ForStatement) -> ForStatement {
+    ForStatement {
+        location: f.fold_location(&node.location),
+        init: node.init.as_ref().map(|init| match init {
+            ForInitializer::Expression(exp) => ForInitializer::Expression(f.fold_expression(exp)),
+            ForInitializer::VariableDefinition(defn) => ForInitializer::VariableDefinition(f.fold_simple_variable_definition(defn)),
+        }),
+        test: fold_opt_expression(f, &node.test),
+        update: fold_opt_expression(f, &node.update),
+        body: f.fold_directive(&node.body),
+    }
+}
+
+pub fn rebuild_for_in_statement<F: Folder>(f: &mut F, node: &ForInStatement) -> ForInStatement {
+    ForInStatement {
+        location: f.fold_location(&node.location),
+        each: node.each,
+        left: match &node.left {
+            ForInBinding::Expression(exp) => ForInBinding::Expression(f.fold_expression(exp)),
+            ForInBinding::VariableDefinition(defn) => ForInBinding::VariableDefinition(f.fold_simple_variable_definition(defn)),
+        },
+        right: f.fold_expression(&node.right),
+        body: f.fold_directive(&node.body),
+    }
+}
+
+pub fn rebuild_simple_variable_definition<F: Folder>(f: &mut F, node: &Rc<SimpleVariableDefinition>) -> Rc<SimpleVariableDefinition> {
+    Rc::new(SimpleVariableDefinition {
+        location: f.fold_location(&node.location),
+        kind: (node.kind.0, f.fold_location(&node.kind.1)),
+        bindings: node.bindings.iter().map(|binding| f.fold_variable_binding(binding)).collect(),
+    })
+}
+
+pub fn rebuild_break_statement<F: Folder>(f: &mut F, node: &BreakStatement) -> BreakStatement {
+    BreakStatement {
+        location: f.fold_location(&node.location),
+        label: node.label.as_ref().map(|label| fold_name(f, label)),
+    }
+}
+
+pub fn rebuild_continue_statement<F: Folder>(f: &mut F, node: &ContinueStatement) -> ContinueStatement {
+    ContinueStatement {
+        location: f.fold_location(&node.location),
+        label: node.label.as_ref().map(|label| fold_name(f, label)),
+    }
+}
+
+pub fn rebuild_with_statement<F: Folder>(f: &mut F, node: &WithStatement) -> WithStatement {
+    WithStatement {
+        location: f.fold_location(&node.location),
+        object: f.fold_expression(&node.object),
+        body: f.fold_directive(&node.body),
+    }
+}
+
+pub fn rebuild_return_statement<F: Folder>(f: &mut F, node: &ReturnStatement) -> ReturnStatement {
+    ReturnStatement {
+        location: f.fold_location(&node.location),
+        expression: fold_opt_expression(f, &node.expression),
+    }
+}
+
+pub fn rebuild_throw_statement<F: Folder>(f: &mut F, node: &ThrowStatement) -> ThrowStatement {
+    ThrowStatement {
+        location: f.fold_location(&node.location),
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+pub fn rebuild_default_xml_namespace_statement<F: Folder>(f: &mut F, node: &DefaultXmlNamespaceStatement) -> DefaultXmlNamespaceStatement {
+    DefaultXmlNamespaceStatement {
+        location: f.fold_location(&node.location),
+        right: f.fold_expression(&node.right),
+    }
+}
+
+pub fn rebuild_try_statement<F: Folder>(f: &mut F, node: &TryStatement) -> TryStatement {
+    TryStatement {
+        location: f.fold_location(&node.location),
+        block: fold_block_rc(f, &node.block),
+        catch_clauses: node.catch_clauses.iter().map(|clause| f.fold_catch_clause(clause)).collect(),
+        finally_clause: node.finally_clause.as_ref().map(|clause| f.fold_finally_clause(clause)),
+    }
+}
+
+pub fn rebuild_catch_clause<F: Folder>(f: &mut F, node: &CatchClause) -> CatchClause {
+    CatchClause {
+        location: f.fold_location(&node.location),
+        parameter: f.fold_typed_destructuring(&node.parameter),
+        block: fold_block_rc(f, &node.block),
+    }
+}
+
+pub fn rebuild_finally_clause<F: Folder>(f: &mut F, node: &FinallyClause) -> FinallyClause {
+    FinallyClause {
+        location: f.fold_location(&node.location),
+        block: fold_block_rc(f, &node.block),
+    }
+}
+
+pub fn rebuild_configuration_directive<F: Folder>(f: &mut F, node: &ConfigurationDirective) -> ConfigurationDirective {
+    ConfigurationDirective {
+        location: f.fold_location(&node.location),
+        namespace: fold_name(f, &node.namespace),
+        constant_name: fold_name(f, &node.constant_name),
+        directive: f.fold_directive(&node.directive),
+    }
+}
+
+pub fn rebuild_import_directive<F: Folder>(f: &mut F, node: &ImportDirective) -> ImportDirective {
+    ImportDirective {
+        location: f.fold_location(&node.location),
+        alias: node.alias.as_ref().map(|alias| fold_name(f, alias)),
+        package_name: fold_names(f, &node.package_name),
+        import_specifier: fold_import_specifier(f, &node.import_specifier),
+    }
+}
+
+pub fn rebuild_use_namespace_directive<F: Folder>(f: &mut F, node: &UseNamespaceDirective) -> UseNamespaceDirective {
+    UseNamespaceDirective {
+        location: f.fold_location(&node.location),
+        expression: f.fold_expression(&node.expression),
+    }
+}
+
+/// Rebuilds an `include` directive. The nested compilation unit
+/// is shared with the original node.
+pub fn rebuild_include_directive<F: Folder>(f: &mut F, node: &IncludeDirective) -> IncludeDirective {
+    IncludeDirective {
+        location: f.fold_location(&node.location),
+        source: node.source.clone(),
+        nested_compilation_unit: node.nested_compilation_unit.clone(),
+        nested_packages: node.nested_packages.iter().map(|pckg| f.fold_package_definition(pckg)).collect(),
+        nested_directives: fold_directives(f, &node.nested_directives),
+    }
+}
+
+pub fn rebuild_package_concat_directive<F: Folder>(f: &mut F, node: &PackageConcatDirective) -> PackageConcatDirective {
+    PackageConcatDirective {
+        location: f.fold_location(&node.location),
+        package_name: fold_names(f, &node.package_name),
+        import_specifier: fold_import_specifier(f, &node.import_specifier),
+    }
+}
+
+pub fn rebuild_directive_injection_node<F: Folder>(f: &mut F, node: &DirectiveInjectionNode) -> DirectiveInjectionNode {
+    DirectiveInjectionNode {
+        location: f.fold_location(&node.location),
+        directives: RefCell::new(fold_directives(f, &node.directives.borrow())),
+    }
+}
+
+pub fn rebuild_attribute<F: Folder>(f: &mut F, node: &Attribute) -> Attribute {
+    match node {
+        Attribute::Metadata(metadata) => Attribute::Metadata(f.fold_metadata(metadata)),
+        Attribute::Expression(exp) => Attribute::Expression(f.fold_expression(exp)),
+        Attribute::Public(loc) => Attribute::Public(f.fold_location(loc)),
+        Attribute::Private(loc) => Attribute::Private(f.fold_location(loc)),
+        Attribute::Protected(loc) => Attribute::Protected(f.fold_location(loc)),
+        Attribute::Internal(loc) => Attribute::Internal(f.fold_location(loc)),
+        Attribute::Final(loc) => Attribute::Final(f.fold_location(loc)),
+        Attribute::Native(loc) => Attribute::Native(f.fold_location(loc)),
+        Attribute::Static(loc) => Attribute::Static(f.fold_location(loc)),
+        Attribute::Abstract(loc) => Attribute::Abstract(f.fold_location(loc)),
+        Attribute::Override(loc) => Attribute::Override(f.fold_location(loc)),
+        Attribute::Dynamic(loc) => Attribute::Dynamic(f.fold_location(loc)),
+    }
+}
+
+pub fn rebuild_metadata<F: Folder>(f: &mut F, node: &Rc<Metadata>) -> Rc<Metadata> {
+    Rc::new(Metadata {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        name: fold_name(f, &node.name),
+        entries: node.entries.as_ref().map(|entries| entries.iter().map(|entry| f.fold_metadata_entry(entry)).collect()),
+    })
+}
+
+pub fn rebuild_metadata_entry<F: Folder>(f: &mut F, node: &Rc<MetadataEntry>) -> Rc<MetadataEntry> {
+    Rc::new(MetadataEntry {
+        location: f.fold_location(&node.location),
+        key: node.key.as_ref().map(|key| fold_name(f, key)),
+        value: Rc::new(match node.value.as_ref() {
+            MetadataValue::IdentifierString(value) => MetadataValue::IdentifierString(fold_name(f, value)),
+            MetadataValue::String(value) => MetadataValue::String(fold_name(f, value)),
+        }),
+    })
+}
+
+pub fn rebuild_asdoc<F: Folder>(f: &mut F, node: &Rc<Asdoc>) -> Rc<Asdoc> {
+    Rc::new(Asdoc {
+        location: f.fold_location(&node.location),
+        main_body: node.main_body.as_ref().map(|body| fold_name(f, body)),
+        tags: node.tags.iter().map(|(tag, loc)| {
+            let tag = match tag {
+                AsdocTag::Copy(reference) => AsdocTag::Copy(f.fold_asdoc_reference(reference)),
+                AsdocTag::See { reference, display_text } => AsdocTag::See {
+                    reference: f.fold_asdoc_reference(reference),
+                    display_text: display_text.clone(),
+                },
+                AsdocTag::EventType(exp) => AsdocTag::EventType(f.fold_expression(exp)),
+                AsdocTag::Throws { class_reference, description } => AsdocTag::Throws {
+                    class_reference: f.fold_expression(class_reference),
+                    description: description.clone(),
+                },
+                tag => tag.clone(),
+            };
+            (tag, f.fold_location(loc))
+        }).collect(),
+    })
+}
+
+pub fn rebuild_asdoc_reference<F: Folder>(f: &mut F, node: &Rc<AsdocReference>) -> Rc<AsdocReference> {
+    Rc::new(AsdocReference {
+        base: fold_opt_expression(f, &node.base),
+        instance_property: node.instance_property.as_ref().map(|id| Rc::new(f.fold_qualified_identifier(id))),
+    })
+}
+
+pub fn rebuild_type_parameter<F: Folder>(f: &mut F, node: &Rc<TypeParameter>) -> Rc<TypeParameter> {
+    Rc::new(TypeParameter {
+        location: f.fold_location(&node.location),
+        name: fold_name(f, &node.name),
+    })
+}
+
+pub fn rebuild_variable_definition<F: Folder>(f: &mut F, node: &VariableDefinition) -> VariableDefinition {
+    VariableDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        kind: (node.kind.0, f.fold_location(&node.kind.1)),
+        bindings: node.bindings.iter().map(|binding| f.fold_variable_binding(binding)).collect(),
+    }
+}
+
+pub fn rebuild_variable_binding<F: Folder>(f: &mut F, node: &Rc<VariableBinding>) -> Rc<VariableBinding> {
+    Rc::new(VariableBinding {
+        destructuring: f.fold_typed_destructuring(&node.destructuring),
+        initializer: fold_opt_expression(f, &node.initializer),
+    })
+}
+
+pub fn rebuild_function_definition<F: Folder>(f: &mut F, node: &FunctionDefinition) -> FunctionDefinition {
+    FunctionDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        name: match &node.name {
+            FunctionName::Identifier(name) => FunctionName::Identifier(fold_name(f, name)),
+            FunctionName::Getter(name) => FunctionName::Getter(fold_name(f, name)),
+            FunctionName::Setter(name) => FunctionName::Setter(fold_name(f, name)),
+            FunctionName::Constructor(name) => FunctionName::Constructor(fold_name(f, name)),
+        },
+        common: f.fold_function_common(&node.common),
+    }
+}
+
+pub fn rebuild_function_common<F: Folder>(f: &mut F, node: &Rc<FunctionCommon>) -> Rc<FunctionCommon> {
+    Rc::new(FunctionCommon {
+        location: f.fold_location(&node.location),
+        contains_yield: node.contains_yield,
+        contains_await: node.contains_await,
+        signature: f.fold_function_signature(&node.signature),
+        body: node.body.as_ref().map(|body| match body {
+            FunctionBody::Expression(exp) => FunctionBody::Expression(f.fold_expression(exp)),
+            FunctionBody::Block(block) => FunctionBody::Block(fold_block_rc(f, block)),
+            FunctionBody::Lazy(body) => FunctionBody::Lazy(Rc::new(LazyFunctionBody {
+                location: f.fold_location(&body.location),
+                constructor: body.constructor,
+                source_hash: body.source_hash,
+            })),
+        }),
+    })
+}
+
+pub fn rebuild_function_signature<F: Folder>(f: &mut F, node: &FunctionSignature) -> FunctionSignature {
+    FunctionSignature {
+        location: f.fold_location(&node.location),
+        this_parameter: node.this_parameter.as_ref().map(|param| f.fold_this_parameter(param)),
+        parameters: node.parameters.iter().map(|param| f.fold_parameter(param)).collect(),
+        result_type: fold_opt_expression(f, &node.result_type),
+    }
+}
+
+pub fn rebuild_this_parameter<F: Folder>(f: &mut F, node: &Rc<ThisParameter>) -> Rc<ThisParameter> {
+    Rc::new(ThisParameter {
+        location: f.fold_location(&node.location),
+        type_annotation: f.fold_expression(&node.type_annotation),
+    })
+}
+
+pub fn rebuild_parameter<F: Folder>(f: &mut F, node: &Rc<Parameter>) -> Rc<Parameter> {
+    Rc::new(Parameter {
+        location: f.fold_location(&node.location),
+        kind: node.kind,
+        destructuring: f.fold_typed_destructuring(&node.destructuring),
+        default_value: fold_opt_expression(f, &node.default_value),
+    })
+}
+
+pub fn rebuild_class_definition<F: Folder>(f: &mut F, node: &ClassDefinition) -> ClassDefinition {
+    ClassDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        name: fold_name(f, &node.name),
+        type_parameters: fold_type_parameters(f, &node.type_parameters),
+        extends_clause: fold_opt_expression(f, &node.extends_clause),
+        implements_clause: node.implements_clause.as_ref().map(|list| fold_expressions(f, list)),
+        block: fold_block_rc(f, &node.block),
+    }
+}
+
+pub fn rebuild_enum_definition<F: Folder>(f: &mut F, node: &EnumDefinition) -> EnumDefinition {
+    EnumDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        is_set: node.is_set,
+        name: fold_name(f, &node.name),
+        as_clause: fold_opt_expression(f, &node.as_clause),
+        block: fold_block_rc(f, &node.block),
+    }
+}
+
+pub fn rebuild_interface_definition<F: Folder>(f: &mut F, node: &InterfaceDefinition) -> InterfaceDefinition {
+    InterfaceDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        name: fold_name(f, &node.name),
+        type_parameters: fold_type_parameters(f, &node.type_parameters),
+        extends_clause: node.extends_clause.as_ref().map(|list| fold_expressions(f, list)),
+        block: fold_block_rc(f, &node.block),
+    }
+}
+
+pub fn rebuild_type_definition<F: Folder>(f: &mut F, node: &TypeDefinition) -> TypeDefinition {
+    TypeDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        left: fold_name(f, &node.left),
+        right: f.fold_expression(&node.right),
+    }
+}
+
+pub fn rebuild_namespace_definition<F: Folder>(f: &mut F, node: &NamespaceDefinition) -> NamespaceDefinition {
+    NamespaceDefinition {
+        location: f.fold_location(&node.location),
+        asdoc: fold_opt_asdoc(f, &node.asdoc),
+        attributes: fold_attributes(f, &node.attributes),
+        left: fold_name(f, &node.left),
+        right: fold_opt_expression(f, &node.right),
+    }
+}
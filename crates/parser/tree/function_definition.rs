@@ -71,7 +71,7 @@ pub struct FunctionCommon {
 
 impl FunctionCommon {
     pub(crate) fn has_block_body(&self) -> bool {
-        if let Some(ref body) = self.body { matches!(body, FunctionBody::Block(_)) } else { false }
+        if let Some(ref body) = self.body { matches!(body, FunctionBody::Block(_) | FunctionBody::Lazy(_)) } else { false }
     }
 }
 
@@ -115,4 +115,19 @@ impl ParameterKind {
 pub enum FunctionBody {
     Expression(Rc<Expression>),
     Block(Rc<Block>),
-}
\ No newline at end of file
+    /// A block whose directives were skipped by declaration-only parsing.
+    Lazy(Rc<LazyFunctionBody>),
+}
+
+/// A function body skipped by declaration-only parsing, which
+/// `ParserFacade::parse_lazy_function_body` parses on demand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LazyFunctionBody {
+    /// Location of the block, including its braces.
+    pub location: Location,
+    /// Indicates whether the body belongs to a constructor.
+    pub constructor: bool,
+    /// Hash of the source text of the block, which distinguishes
+    /// bodies in structural comparisons.
+    pub source_hash: u64,
+}
@@ -5,7 +5,12 @@ use serde::{Serialize, Deserialize};
 pub struct IncludeDirective {
     pub location: Location,
     pub source: String,
-    #[serde(skip)]
+    #[serde(
+        skip_serializing_if = "outside_located_tree",
+        serialize_with = "serialize_unit_reference",
+        deserialize_with = "deserialize_unit_reference",
+        default,
+    )]
     pub nested_compilation_unit: Rc<CompilationUnit>,
     pub nested_packages: Vec<Rc<PackageDefinition>>,
     pub nested_directives: Vec<Rc<Directive>>,
@@ -1,29 +1,53 @@
 use crate::ns::*;
-use by_address::ByAddress;
-
-const LARGE_BYTES: usize = 26_000;
+use serde::{Serialize, Deserialize};
 
 /// Represents the mapping of any node to something.
-/// 
+///
 /// A limited subtype of nodes may be mapped to something within this
 /// structure through using the implemented `NodeAssignmentMethod`
 /// methods, such as `.get()` and `.set()`.
+///
+/// Nodes are mapped through their [`NodeId`], into a [`NodeTable`]
+/// per compilation unit. Tables of dropped compilation units
+/// are discarded.
 pub struct NodeAssignment<S> {
-    common: NodeAssignment1<S>,
-    large_units: RefCell<HashMap<ByAddress<Rc<CompilationUnit>>, NodeAssignment1<S>>>,
+    units: RefCell<HashMap<*const CompilationUnit, UnitTable<S>>>,
 }
 
+type UnitTable<S> = (Weak<CompilationUnit>, NodeTable<S>);
+
 impl<S: Clone> NodeAssignment<S> {
     pub fn new() -> Self {
         Self {
-            common: NodeAssignment1::new(),
-            large_units: RefCell::new(HashMap::new()),
+            units: RefCell::new(HashMap::new()),
         }
     }
 
     pub fn clear(&self) {
-        self.common.clear();
-        self.large_units.borrow_mut().clear();
+        self.units.borrow_mut().clear();
+    }
+
+    /// Returns the table of a compilation unit, such as
+    /// for persisting it alongside a cached tree.
+    pub fn table(&self, compilation_unit: &Rc<CompilationUnit>) -> NodeTable<S> {
+        self.units.borrow().get(&Rc::as_ptr(compilation_unit))
+            .filter(|(unit, _)| unit.strong_count() != 0)
+            .map(|(_, table)| table.clone())
+            .unwrap_or_default()
+    }
+
+    /// Replaces the table of a compilation unit, such as
+    /// by a table persisted alongside a cached tree.
+    pub fn set_table(&self, compilation_unit: &Rc<CompilationUnit>, table: NodeTable<S>) {
+        let mut units = self.units.borrow_mut();
+        units.retain(|_, (unit, _)| unit.strong_count() != 0);
+        units.insert(Rc::as_ptr(compilation_unit), (Rc::downgrade(compilation_unit), table));
+    }
+
+    fn with_table<R>(&self, compilation_unit: &Rc<CompilationUnit>, f: impl FnOnce(&NodeTable<S>) -> R) -> Option<R> {
+        let units = self.units.borrow();
+        let (unit, table) = units.get(&Rc::as_ptr(compilation_unit))?;
+        (unit.strong_count() != 0).then(|| f(table))
     }
 }
 
@@ -37,216 +61,95 @@ pub trait NodeAssignmentMethod<T, S: Clone> {
     fn has(&self, node: &Rc<T>) -> bool;
 }
 
-macro impl_semantics_with_loc_call {
-    (struct $node_assignment_id:ident, $($nodetype:ident),*$(,)?) => {
-        $(
-            impl<S: Clone> NodeAssignmentMethod<$nodetype, S> for $node_assignment_id<S> {
-                fn get(&self, node: &Rc<$nodetype>) -> Option<S> {
-                    let cu = node.location().compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.get(node)
-                    } else {
-                        let large_units = self.large_units.borrow();
-                        let m1 = large_units.get(&ByAddress(cu));
-                        m1.and_then(|m1| m1.get(node))
-                    }
-                }
-                fn set(&self, node: &Rc<$nodetype>, symbol: Option<S>) {
-                    let cu = node.location().compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.set(node, symbol);
-                    } else {
-                        let mut large_units = self.large_units.borrow_mut();
-                        let m1 = large_units.get_mut(&ByAddress(cu.clone()));
-                        if let Some(m1) = m1 {
-                            m1.set(node, symbol);
-                        } else {
-                            let m1 = NodeAssignment1::new();
-                            m1.set(node, symbol);
-                            large_units.insert(ByAddress(cu), m1);
-                        }
-                    }
-                }
-                fn delete(&self, node: &Rc<$nodetype>) -> bool {
-                    let cu = node.location().compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.delete(node)
-                    } else {
-                        let mut large_units = self.large_units.borrow_mut();
-                        let m1 = large_units.get_mut(&ByAddress(cu));
-                        m1.map(|m1| m1.delete(node)).unwrap_or(false)
-                    }
-                }
-                fn has(&self, node: &Rc<$nodetype>) -> bool {
-                    let cu = node.location().compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.has(node)
-                    } else {
-                        let large_units = self.large_units.borrow();
-                        let m1 = large_units.get(&ByAddress(cu));
-                        m1.map(|m1| m1.has(node)).unwrap_or(false)
-                    }
-                }
+impl<T: IdentifiedNode, S: Clone> NodeAssignmentMethod<T, S> for NodeAssignment<S> {
+    fn get(&self, node: &Rc<T>) -> Option<S> {
+        let cu = node.node_location().compilation_unit();
+        let id = cu.node_index.borrow().find(node)?;
+        self.with_table(&cu, |table| table.get(id).cloned().flatten()).flatten()
+    }
+
+    fn set(&self, node: &Rc<T>, symbol: Option<S>) {
+        let cu = node.node_location().compilation_unit();
+        let id = node.node_id();
+        let mut units = self.units.borrow_mut();
+        if let Some((unit, table)) = units.get_mut(&Rc::as_ptr(&cu)) {
+            if unit.strong_count() != 0 {
+                table.set(id, symbol);
+                return;
             }
-        )*
-    },
+        }
+        units.retain(|_, (unit, _)| unit.strong_count() != 0);
+        let mut table = NodeTable::new();
+        table.set(id, symbol);
+        units.insert(Rc::as_ptr(&cu), (Rc::downgrade(&cu), table));
+    }
+
+    fn delete(&self, node: &Rc<T>) -> bool {
+        let cu = node.node_location().compilation_unit();
+        let Some(id) = cu.node_index.borrow().find(node) else {
+            return false;
+        };
+        let mut units = self.units.borrow_mut();
+        units.get_mut(&Rc::as_ptr(&cu)).is_some_and(|(unit, table)| unit.strong_count() != 0 && table.delete(id))
+    }
+
+    fn has(&self, node: &Rc<T>) -> bool {
+        let cu = node.node_location().compilation_unit();
+        let Some(id) = cu.node_index.borrow().find(node) else {
+            return false;
+        };
+        self.with_table(&cu, |table| table.has(id)).unwrap_or(false)
+    }
 }
 
-macro impl_semantics_with_loc_field {
-    (struct $node_assignment_id:ident, $($nodetype:ident),*$(,)?) => {
-        $(
-            impl<S: Clone> NodeAssignmentMethod<$nodetype, S> for $node_assignment_id<S> {
-                fn get(&self, node: &Rc<$nodetype>) -> Option<S> {
-                    let cu = node.location.compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.get(node)
-                    } else {
-                        let large_units = self.large_units.borrow();
-                        let m1 = large_units.get(&ByAddress(cu));
-                        m1.and_then(|m1| m1.get(node))
-                    }
-                }
-                fn set(&self, node: &Rc<$nodetype>, symbol: Option<S>) {
-                    let cu = node.location.compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.set(node, symbol);
-                    } else {
-                        let mut large_units = self.large_units.borrow_mut();
-                        let m1 = large_units.get_mut(&ByAddress(cu.clone()));
-                        if let Some(m1) = m1 {
-                            m1.set(node, symbol);
-                        } else {
-                            let m1 = NodeAssignment1::new();
-                            m1.set(node, symbol);
-                            large_units.insert(ByAddress(cu), m1);
-                        }
-                    }
-                }
-                fn delete(&self, node: &Rc<$nodetype>) -> bool {
-                    let cu = node.location.compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.delete(node)
-                    } else {
-                        let mut large_units = self.large_units.borrow_mut();
-                        let m1 = large_units.get_mut(&ByAddress(cu));
-                        m1.map(|m1| m1.delete(node)).unwrap_or(false)
-                    }
-                }
-                fn has(&self, node: &Rc<$nodetype>) -> bool {
-                    let cu = node.location.compilation_unit();
-                    if cu.text().len() < LARGE_BYTES {
-                        self.common.has(node)
-                    } else {
-                        let large_units = self.large_units.borrow();
-                        let m1 = large_units.get(&ByAddress(cu));
-                        m1.map(|m1| m1.has(node)).unwrap_or(false)
-                    }
-                }
-            }
-        )*
-    },
+/// A dense mapping from the [`NodeId`]s of a compilation unit to something.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NodeTable<S> {
+    slots: Vec<NodeTableSlot<S>>,
 }
 
-macro impl_semantics_1 {
-    (struct $node_assignment_1_id:ident, fn $new_id:ident, fn $clear_id:ident, $($nodetype:ident),*$(,)?) => {
-        #[allow(non_snake_case)]
-        struct $node_assignment_1_id<S> {
-            $($nodetype: RefCell<HashMap<NodeAsKey<Rc<$nodetype>>, Option<S>>>,)*
+#[derive(Clone, Serialize, Deserialize)]
+enum NodeTableSlot<S> {
+    Vacant,
+    Occupied(Option<S>),
+}
+
+impl<S> Default for NodeTable<S> {
+    fn default() -> Self {
+        Self { slots: vec![] }
+    }
+}
+
+impl<S> NodeTable<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, id: NodeId) -> Option<&Option<S>> {
+        match self.slots.get(id.index()) {
+            Some(NodeTableSlot::Occupied(value)) => Some(value),
+            _ => None,
         }
+    }
 
-        impl<S: Clone> $node_assignment_1_id<S> {
-            pub fn $new_id() -> Self {
-                Self {
-                    $($nodetype: RefCell::new(HashMap::new()),)*
-                }
-            }
+    pub fn set(&mut self, id: NodeId, value: Option<S>) {
+        if self.slots.len() <= id.index() {
+            self.slots.resize_with(id.index() + 1, || NodeTableSlot::Vacant);
+        }
+        self.slots[id.index()] = NodeTableSlot::Occupied(value);
+    }
 
-            pub fn $clear_id(&self) {
-                $(self.$nodetype.borrow_mut().clear();)*
-            } 
+    pub fn delete(&mut self, id: NodeId) -> bool {
+        match self.slots.get_mut(id.index()) {
+            Some(slot @ NodeTableSlot::Occupied(_)) => {
+                *slot = NodeTableSlot::Vacant;
+                true
+            },
+            _ => false,
         }
+    }
 
-        $(
-            impl<S: Clone> NodeAssignmentMethod<$nodetype, S> for $node_assignment_1_id<S> {
-                fn get(&self, node: &Rc<$nodetype>) -> Option<S> {
-                    let m = self.$nodetype.borrow();
-                    let v = m.get(&NodeAsKey(node.clone()));
-                    if let Some(v) = v {
-                        v.clone()
-                    } else {
-                        None
-                    }
-                }
-                fn set(&self, node: &Rc<$nodetype>, symbol: Option<S>) {
-                    self.$nodetype.borrow_mut().insert(NodeAsKey(node.clone()), symbol);
-                }
-                fn delete(&self, node: &Rc<$nodetype>) -> bool {
-                    self.$nodetype.borrow_mut().remove(&NodeAsKey(node.clone())).is_some()
-                }
-                fn has(&self, node: &Rc<$nodetype>) -> bool {
-                    self.$nodetype.borrow().contains_key(&NodeAsKey(node.clone()))
-                }
-            }
-        )*
-    },
+    pub fn has(&self, id: NodeId) -> bool {
+        matches!(self.slots.get(id.index()), Some(NodeTableSlot::Occupied(_)))
+    }
 }
-
-impl_semantics_with_loc_call!(
-    struct NodeAssignment,
-    Expression,
-    InitializerField,
-    Directive,
-    MxmlContent,
-    CssDirective,
-    CssMediaQueryCondition,
-    CssSelectorCondition,
-    CssPropertyValue,
-    CssSelector,
-);
-
-impl_semantics_with_loc_field!(
-    struct NodeAssignment,
-    FunctionCommon,
-    Block,
-    Program,
-    PackageDefinition,
-    SimpleVariableDefinition,
-    Metadata,
-    MetadataEntry,
-    Mxml,
-    MxmlElement,
-    MxmlAttribute,
-    CssProperty,
-    CssRule,
-    CssDocument,
-    QualifiedIdentifier,
-);
-
-impl_semantics_1!(
-    struct NodeAssignment1,
-    fn new,
-    fn clear,
-    Expression,
-    InitializerField,
-    Directive,
-    FunctionCommon,
-    Block,
-    Program,
-    PackageDefinition,
-    SimpleVariableDefinition,
-    QualifiedIdentifier,
-    Metadata,
-    MetadataEntry,
-    Mxml,
-    MxmlContent,
-    MxmlElement,
-    MxmlAttribute,
-    CssDirective,
-    CssRule,
-    CssMediaQueryCondition,
-    CssSelectorCondition,
-    CssPropertyValue,
-    CssSelector,
-    CssProperty,
-    CssDocument,
-);
\ No newline at end of file
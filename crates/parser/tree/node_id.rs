@@ -0,0 +1,461 @@
+use crate::ns::*;
+use serde::{Serialize, Deserialize};
+use std::any::{Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::hash::{BuildHasherDefault, Hasher};
+
+/// Identifies a node within its compilation unit.
+///
+/// Identifiers are assigned in pre-order when a tree is parsed, so parsing
+/// the same source text with the same options results in the same identifiers.
+/// A node that was not part of a parsed tree, such as a copy of a node,
+/// is assigned its own identifier once one is requested.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeId(pub u32);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A node that may be assigned a [`NodeId`]
+/// and attached to a `NodeAssignment`.
+pub trait IdentifiedNode: Any + MaybeSync {
+    fn node_location(&self) -> Location;
+
+    /// Returns the identifier of the node, assigning one if the node
+    /// was not part of a parsed tree.
+    fn node_id(self: &Rc<Self>) -> NodeId where Self: Sized {
+        self.node_location().compilation_unit().node_index.borrow_mut().find_or_assign(self)
+    }
+}
+
+macro impl_identified_node_with_loc_call {
+    ($($nodetype:ident),*$(,)?) => {
+        $(
+            impl IdentifiedNode for $nodetype {
+                fn node_location(&self) -> Location {
+                    self.location()
+                }
+            }
+        )*
+    },
+}
+
+macro impl_identified_node_with_loc_field {
+    ($($nodetype:ident),*$(,)?) => {
+        $(
+            impl IdentifiedNode for $nodetype {
+                fn node_location(&self) -> Location {
+                    self.location.clone()
+                }
+            }
+        )*
+    },
+}
+
+impl_identified_node_with_loc_call!(
+    Expression,
+    InitializerField,
+    Directive,
+    MxmlContent,
+    CssDirective,
+    CssMediaQueryCondition,
+    CssSelectorCondition,
+    CssPropertyValue,
+    CssSelector,
+);
+
+impl_identified_node_with_loc_field!(
+    FunctionCommon,
+    Block,
+    Program,
+    PackageDefinition,
+    SimpleVariableDefinition,
+    Metadata,
+    MetadataEntry,
+    Mxml,
+    MxmlElement,
+    MxmlAttribute,
+    CssProperty,
+    CssRule,
+    CssDocument,
+    QualifiedIdentifier,
+);
+
+/// The nodes of a compilation unit, indexed by their identifiers.
+#[derive(Default)]
+pub(crate) struct NodeIndex {
+    nodes: Vec<NodeSlot>,
+    /// The first node of each type and source range.
+    ids: HashMap<NodeKey, NodeId, BuildHasherDefault<NodeKeyHasher>>,
+}
+
+type NodeKey = (TypeId, usize, usize);
+
+struct NodeSlot {
+    node: Option<Weak<DynAny>>,
+    /// The next node of the same type and source range.
+    next: Option<NodeId>,
+}
+
+/// A fast hasher for the keys of a `NodeIndex`.
+#[derive(Default)]
+struct NodeKeyHasher(u64);
+
+impl Hasher for NodeKeyHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for chunk in bytes.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write_u64(u64::from_le_bytes(word));
+        }
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.0 = (self.0.rotate_left(5) ^ value).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+
+    fn write_usize(&mut self, value: usize) {
+        self.write_u64(value as u64);
+    }
+}
+
+impl NodeIndex {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn get<T: IdentifiedNode>(&self, id: NodeId) -> Option<Rc<T>> {
+        self.nodes.get(id.index())?.node.as_ref()?.upgrade()?.downcast::<T>().ok()
+    }
+
+    fn candidates(&self, first: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(first), |id| self.nodes[id.index()].next)
+    }
+
+    fn is_node<T: IdentifiedNode>(&self, id: NodeId, node: &Rc<T>) -> bool {
+        self.nodes[id.index()].node.as_ref().is_some_and(|weak| std::ptr::addr_eq(weak.as_ptr(), Rc::as_ptr(node)))
+    }
+
+    /// Assigns an identifier to a node unless the node itself
+    /// was already assigned one.
+    fn assign<T: IdentifiedNode>(&mut self, key: NodeKey, node: &Rc<T>) -> NodeId {
+        let weak: Weak<DynAny> = Rc::<T>::downgrade(node);
+        match self.ids.entry(key) {
+            Entry::Occupied(entry) => {
+                let first = *entry.get();
+                let nodes = &self.nodes;
+                let mut candidates = std::iter::successors(Some(first), |id| nodes[id.index()].next);
+                if let Some(id) = candidates.find(|id| nodes[id.index()].node.as_ref().is_some_and(|other| Weak::ptr_eq(other, &weak))) {
+                    return id;
+                }
+                let id = NodeId(self.nodes.len() as u32);
+                let next = self.nodes[first.index()].next.replace(id);
+                self.nodes.push(NodeSlot { node: Some(weak), next });
+                id
+            },
+            Entry::Vacant(entry) => {
+                let id = *entry.insert(NodeId(self.nodes.len() as u32));
+                self.nodes.push(NodeSlot { node: Some(weak), next: None });
+                id
+            },
+        }
+    }
+
+    /// Assigns an identifier to a node held by value, without
+    /// recording the node itself, unless a node of the same type
+    /// already occupies the same range.
+    fn assign_by_value(&mut self, key: NodeKey) {
+        if let Entry::Vacant(entry) = self.ids.entry(key) {
+            entry.insert(NodeId(self.nodes.len() as u32));
+            self.nodes.push(NodeSlot { node: None, next: None });
+        }
+    }
+
+    pub fn find<T: IdentifiedNode>(&self, node: &Rc<T>) -> Option<NodeId> {
+        let first = *self.ids.get(&Self::key(node.as_ref(), &node.node_location()))?;
+        self.candidates(first).find(|id| self.is_node(*id, node))
+    }
+
+    pub fn find_or_assign<T: IdentifiedNode>(&mut self, node: &Rc<T>) -> NodeId {
+        self.find(node).unwrap_or_else(|| self.assign(Self::key(node.as_ref(), &node.node_location()), node))
+    }
+
+    fn key<T: IdentifiedNode>(_node: &T, location: &Location) -> NodeKey {
+        (TypeId::of::<T>(), location.first_offset(), location.last_offset())
+    }
+}
+
+/// Assigns node identifiers throughout a parsed tree.
+pub(crate) struct NodeIdAssigner;
+
+impl NodeIdAssigner {
+    pub fn assign<T: IdentifiedNode>(node: &Rc<T>) {
+        let location = node.node_location();
+        location.compilation_unit.node_index.borrow_mut().assign(NodeIndex::key(node.as_ref(), &location), node);
+    }
+
+    fn assign_by_value<T: IdentifiedNode>(node: &T) {
+        let location = node.node_location();
+        location.compilation_unit.node_index.borrow_mut().assign_by_value(NodeIndex::key(node, &location));
+    }
+
+    pub fn assign_mxml(mxml: &Rc<Mxml>) {
+        Self::assign(mxml);
+        Self::assign_mxml_content(&mxml.content);
+    }
+
+    fn assign_mxml_content(content: &[Rc<MxmlContent>]) {
+        for node in content {
+            Self::assign(node);
+            if let MxmlContent::Element(element) = node.as_ref() {
+                Self::assign(element);
+                for attribute in &element.attributes {
+                    Self::assign(attribute);
+                }
+                if let Some(content) = &element.content {
+                    Self::assign_mxml_content(content);
+                }
+            }
+        }
+    }
+
+    pub fn assign_css(css: &Rc<CssDocument>) {
+        Self::assign(css);
+        for directive in &css.directives {
+            Self::assign(directive);
+            match directive.as_ref() {
+                CssDirective::Rule(rule) => Self::assign_css_rule_contents(rule),
+                CssDirective::MediaQuery(media) => {
+                    for condition in &media.conditions {
+                        Self::assign_css_media_query_condition(condition);
+                    }
+                    for rule in &media.rules {
+                        Self::assign(rule);
+                        Self::assign_css_rule_contents(rule);
+                    }
+                },
+                CssDirective::FontFace(font_face) => Self::assign_css_properties(&font_face.properties),
+                _ => {},
+            }
+        }
+    }
+
+    fn assign_css_media_query_condition(condition: &Rc<CssMediaQueryCondition>) {
+        Self::assign(condition);
+        match condition.as_ref() {
+            CssMediaQueryCondition::ParenProperty((property, _)) => Self::assign_css_properties(std::slice::from_ref(property)),
+            CssMediaQueryCondition::And { left, right, .. } => {
+                Self::assign_css_media_query_condition(left);
+                Self::assign_css_media_query_condition(right);
+            },
+            _ => {},
+        }
+    }
+
+    fn assign_css_rule_contents(rule: &CssRule) {
+        for selector in &rule.selectors {
+            Self::assign_css_selector(selector);
+        }
+        Self::assign_css_properties(&rule.properties);
+    }
+
+    fn assign_css_selector(selector: &Rc<CssSelector>) {
+        Self::assign(selector);
+        match selector.as_ref() {
+            CssSelector::Base(base) => {
+                for condition in &base.conditions {
+                    Self::assign_css_selector_condition(condition);
+                }
+            },
+            CssSelector::Combinator(combinator) => {
+                Self::assign_css_selector(&combinator.left);
+                Self::assign_css_selector(&combinator.right);
+            },
+            CssSelector::Invalidated(_) => {},
+        }
+    }
+
+    fn assign_css_selector_condition(condition: &Rc<CssSelectorCondition>) {
+        Self::assign(condition);
+        if let CssSelectorCondition::Not { condition, .. } = condition.as_ref() {
+            Self::assign_css_selector_condition(condition);
+        }
+    }
+
+    fn assign_css_properties(properties: &[Rc<CssProperty>]) {
+        for property in properties {
+            Self::assign(property);
+            Self::assign_css_property_value(&property.value);
+        }
+    }
+
+    fn assign_css_property_value(value: &Rc<CssPropertyValue>) {
+        Self::assign(value);
+        match value.as_ref() {
+            CssPropertyValue::Array(array) => {
+                for element in &array.elements {
+                    Self::assign_css_property_value(element);
+                }
+            },
+            CssPropertyValue::MultiValue(multi_value) => {
+                for value in &multi_value.values {
+                    Self::assign_css_property_value(value);
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+impl Visitor for NodeIdAssigner {
+    fn visit_program(&mut self, node: &Rc<Program>) {
+        Self::assign(node);
+        walk_program(self, node);
+    }
+
+    fn visit_package_definition(&mut self, node: &Rc<PackageDefinition>) {
+        Self::assign(node);
+        Self::assign(&node.block);
+        walk_package_definition(self, node);
+    }
+
+    fn visit_expression(&mut self, node: &Rc<Expression>) {
+        Self::assign(node);
+        walk_expression(self, node);
+    }
+
+    fn visit_qualified_identifier(&mut self, node: &QualifiedIdentifier) {
+        Self::assign_by_value(node);
+        walk_qualified_identifier(self, node);
+    }
+
+    fn visit_initializer_field(&mut self, node: &Rc<InitializerField>) {
+        Self::assign(node);
+        walk_initializer_field(self, node);
+    }
+
+    fn visit_directive(&mut self, node: &Rc<Directive>) {
+        Self::assign(node);
+        walk_directive(self, node);
+    }
+
+    fn visit_block(&mut self, node: &Block) {
+        Self::assign_by_value(node);
+        walk_block(self, node);
+    }
+
+    fn visit_simple_variable_definition(&mut self, node: &Rc<SimpleVariableDefinition>) {
+        Self::assign(node);
+        walk_simple_variable_definition(self, node);
+    }
+
+    fn visit_metadata(&mut self, node: &Rc<Metadata>) {
+        Self::assign(node);
+        walk_metadata(self, node);
+    }
+
+    fn visit_metadata_entry(&mut self, node: &Rc<MetadataEntry>) {
+        Self::assign(node);
+    }
+
+    fn visit_function_common(&mut self, node: &Rc<FunctionCommon>) {
+        Self::assign(node);
+        if let Some(FunctionBody::Block(block)) = &node.body {
+            Self::assign(block);
+        }
+        walk_function_common(self, node);
+    }
+
+    fn visit_class_definition(&mut self, node: &ClassDefinition) {
+        Self::assign(&node.block);
+        walk_class_definition(self, node);
+    }
+
+    fn visit_enum_definition(&mut self, node: &EnumDefinition) {
+        Self::assign(&node.block);
+        walk_enum_definition(self, node);
+    }
+
+    fn visit_interface_definition(&mut self, node: &InterfaceDefinition) {
+        Self::assign(&node.block);
+        walk_interface_definition(self, node);
+    }
+
+    fn visit_type_case(&mut self, node: &TypeCase) {
+        Self::assign(&node.block);
+        walk_type_case(self, node);
+    }
+
+    fn visit_try_statement(&mut self, node: &TryStatement) {
+        Self::assign(&node.block);
+        walk_try_statement(self, node);
+    }
+
+    fn visit_catch_clause(&mut self, node: &CatchClause) {
+        Self::assign(&node.block);
+        walk_catch_clause(self, node);
+    }
+
+    fn visit_finally_clause(&mut self, node: &FinallyClause) {
+        Self::assign(&node.block);
+        walk_finally_clause(self, node);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_node_id() {
+        let source = "package p { public function f(): void { x = y + 1 } }";
+        let cu = CompilationUnit::new(None, source.into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        assert_eq!(program.node_id(), NodeId(0));
+        assert!(Rc::ptr_eq(&cu.node::<Program>(NodeId(0)).unwrap(), &program));
+        assert!(cu.node::<Expression>(NodeId(0)).is_none());
+
+        let Directive::FunctionDefinition(defn) = program.packages[0].block.directives[0].as_ref() else {
+            panic!();
+        };
+        let Some(FunctionBody::Block(block)) = &defn.common.body else {
+            panic!();
+        };
+        let Directive::ExpressionStatement(statement) = block.directives[0].as_ref() else {
+            panic!();
+        };
+        let exp = statement.expression.clone();
+        assert!(Rc::ptr_eq(&cu.node::<Expression>(exp.node_id()).unwrap(), &exp));
+
+        // A copy of a node is assigned its own identifier.
+        let copy = Rc::new(exp.as_ref().clone());
+        let count = cu.node_count();
+        assert_ne!(copy.node_id(), exp.node_id());
+        assert_eq!(copy.node_id(), NodeId(count as u32));
+        assert_eq!(cu.node_count(), count + 1);
+        let assignment: NodeAssignment<String> = NodeAssignment::new();
+        assignment.set(&exp, Some("assignment".into()));
+        assert_eq!(assignment.get(&exp), Some("assignment".into()));
+        assert!(!assignment.has(&copy));
+
+        // Tables persist across parses of the same source.
+        let table = serde_json::to_string(&assignment.table(&cu)).unwrap();
+        let cu2 = CompilationUnit::new(None, source.into());
+        let program2 = ParserFacade(&cu2, default()).parse_program();
+        assignment.set_table(&cu2, serde_json::from_str(&table).unwrap());
+        let exp2 = cu2.node::<Expression>(exp.node_id()).unwrap();
+        assert_eq!(exp2.location().text(), "x = y + 1");
+        assert_eq!(assignment.get(&exp2), Some("assignment".into()));
+        assert!(!assignment.has(&program2));
+        assert!(assignment.delete(&exp2));
+        assert!(!assignment.has(&exp2));
+    }
+}
+
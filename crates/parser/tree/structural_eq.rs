@@ -0,0 +1,154 @@
+use crate::ns::*;
+use serde::Serialize;
+use std::hash::{Hash, Hasher};
+
+thread_local! {
+    /// Whether locations are being left out of the serialization
+    /// of a node in the current thread.
+    static IGNORING_LOCATIONS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Whether locations are being left out of a structural comparison.
+pub(crate) fn ignoring_locations() -> bool {
+    IGNORING_LOCATIONS.with(|cell| cell.get())
+}
+
+/// Location-insensitive structural equality and hashing of nodes.
+///
+/// Two nodes are structurally equal if they consist of the same nodes,
+/// names, operators, literal values and ASDoc comments, regardless of
+/// their source locations and compilation units. The structural hash of
+/// a node is stable across runs and platforms, and is the same for
+/// structurally equal nodes.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// let cu = CompilationUnit::new(None, "x = a + b;\ny = a  +  b;\nz = a + c;".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let values: Vec<Rc<Expression>> = program.directives.iter().map(|directive| {
+///     let Directive::ExpressionStatement(statement) = directive.as_ref() else { panic!() };
+///     let Expression::Assignment(assignment) = statement.expression.as_ref() else { panic!() };
+///     assignment.right.clone()
+/// }).collect();
+///
+/// assert!(values[0].structural_eq(&values[1]));
+/// assert_eq!(values[0].structural_hash(), values[1].structural_hash());
+/// assert!(!values[0].structural_eq(&values[2]));
+/// ```
+pub trait StructuralEq {
+    fn structural_eq(&self, other: &Self) -> bool;
+    fn structural_hash(&self) -> u64;
+}
+
+macro impl_structural_eq {
+    ($($nodetype:ident),*$(,)?) => {
+        $(
+            impl StructuralEq for $nodetype {
+                fn structural_eq(&self, other: &Self) -> bool {
+                    std::ptr::eq(self, other) || structural_bytes(self) == structural_bytes(other)
+                }
+
+                fn structural_hash(&self) -> u64 {
+                    structural_digest(self)
+                }
+            }
+        )*
+    },
+}
+
+impl_structural_eq!(
+    Expression,
+    Directive,
+    Attribute,
+    NullableTypeExpression,
+    NonNullableTypeExpression,
+    AnyTypeExpression,
+    VoidTypeExpression,
+    ArrayTypeExpression,
+    TupleTypeExpression,
+    FunctionTypeExpression,
+    FunctionTypeParameter,
+);
+
+/// Leaves locations out of serialization in the current thread
+/// until dropped.
+struct IgnoringLocationsGuard(bool);
+
+impl IgnoringLocationsGuard {
+    fn new() -> Self {
+        Self(IGNORING_LOCATIONS.with(|cell| cell.replace(true)))
+    }
+}
+
+impl Drop for IgnoringLocationsGuard {
+    fn drop(&mut self) {
+        IGNORING_LOCATIONS.with(|cell| cell.set(self.0));
+    }
+}
+
+/// Serializes a node in the binary format, leaving locations out.
+fn structural_bytes<T: Serialize + ?Sized>(node: &T) -> Vec<u8> {
+    let _guard = IgnoringLocationsGuard::new();
+    let mut serializer = BinarySerializer::new().allow_skipped_fields();
+    node.serialize(&mut serializer).unwrap();
+    serializer.finish()
+}
+
+/// Location-insensitive hash of any part of a tree, such as
+/// a tuple of the attributes and signature of a definition.
+pub(crate) fn structural_digest<T: Serialize + ?Sized>(node: &T) -> u64 {
+    xxhash_rust::xxh3::xxh3_64(&structural_bytes(node))
+}
+
+/// Wraps a node for use as a key in hash maps and sets,
+/// comparing and hashing it structurally.
+#[derive(Clone)]
+pub struct StructuralKey<T: StructuralEq>(pub Rc<T>);
+
+impl<T: StructuralEq> PartialEq for StructuralKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.structural_eq(&other.0)
+    }
+}
+
+impl<T: StructuralEq> Eq for StructuralKey<T> {}
+
+impl<T: StructuralEq> Hash for StructuralKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.0.structural_hash());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    #[test]
+    fn test_structural_eq() {
+        let cu = CompilationUnit::new(None, "package {\n    public class C {\n        public var v: Vector.<int>;\n        /* comment */ public var w : Vector.<int>;\n        public var x: Vector.<uint>;\n    }\n}".into());
+        let program = ParserFacade(&cu, default()).parse_program();
+        let Directive::ClassDefinition(class) = program.packages[0].block.directives[0].as_ref() else {
+            panic!();
+        };
+        let definitions: Vec<&VariableDefinition> = class.block.directives.iter().map(|directive| match directive.as_ref() {
+            Directive::VariableDefinition(definition) => definition,
+            _ => panic!(),
+        }).collect();
+        let types: Vec<Rc<Expression>> = definitions.iter().map(|definition| definition.bindings[0].destructuring.type_annotation.clone().unwrap()).collect();
+        assert!(types[0].structural_eq(&types[1]));
+        assert!(!types[0].structural_eq(&types[2]));
+        assert!(definitions[0].attributes[0].structural_eq(&definitions[2].attributes[0]));
+
+        // Directives differing only in names are distinct keys.
+        let keys: Vec<StructuralKey<Directive>> = class.block.directives.iter().map(|directive| StructuralKey(directive.clone())).collect();
+        assert!(keys[0] != keys[1] && keys[1] != keys[2] && keys[0] != keys[2]);
+        let digests: HashSet<u64> = class.block.directives.iter().map(|directive| directive.structural_hash()).collect();
+        assert_eq!(digests.len(), 3);
+
+        // Location-sensitive serialization is unaffected.
+        assert_eq!(serde_json::to_string(&definitions[0].attributes[0]).unwrap(), "{\"Public\":\"3:9-3:15\"}");
+    }
+}
@@ -0,0 +1,779 @@
+use crate::ns::*;
+
+/// Read-only traversal over the ActionScript 3 syntax tree.
+///
+/// Every method has a default implementation that invokes the
+/// corresponding `walk_*` function, which visits every child of the node.
+/// Overriding a method and not calling its `walk_*` function
+/// prevents traversal into that node's children.
+///
+/// Nodes that are held by `Rc` are passed as `&Rc<T>`,
+/// allowing them to be used as `NodeAssignment` keys.
+///
+/// # Example
+///
+/// ```
+/// use mxmlextrema_as3parser::ns::*;
+///
+/// struct CallCounter(usize);
+///
+/// impl Visitor for CallCounter {
+///     fn visit_call_expression(&mut self, node: &CallExpression) {
+///         self.0 += 1;
+///         walk_call_expression(self, node);
+///     }
+/// }
+///
+/// let cu = CompilationUnit::new(None, "f(g(), h())".into());
+/// let program = ParserFacade(&cu, default()).parse_program();
+/// let mut counter = CallCounter(0);
+/// counter.visit_program(&program);
+/// assert_eq!(counter.0, 3);
+/// ```
+#[allow(unused_variables)]
+pub trait Visitor: Sized {
+    fn visit_program(&mut self, node: &Rc<Program>) { walk_program(self, node) }
+    fn visit_package_definition(&mut self, node: &Rc<PackageDefinition>) { walk_package_definition(self, node) }
+
+    // Expressions
+    fn visit_expression(&mut self, node: &Rc<Expression>) { walk_expression(self, node) }
+    fn visit_qualified_identifier(&mut self, node: &QualifiedIdentifier) { walk_qualified_identifier(self, node) }
+    fn visit_paren_expression(&mut self, node: &ParenExpression) { walk_paren_expression(self, node) }
+    fn visit_null_literal(&mut self, node: &NullLiteral) {}
+    fn visit_boolean_literal(&mut self, node: &BooleanLiteral) {}
+    fn visit_numeric_literal(&mut self, node: &NumericLiteral) {}
+    fn visit_string_literal(&mut self, node: &StringLiteral) {}
+    fn visit_this_literal(&mut self, node: &ThisLiteral) {}
+    fn visit_regexp_literal(&mut self, node: &RegExpLiteral) {}
+    fn visit_xml_expression(&mut self, node: &XmlExpression) { walk_xml_expression(self, node) }
+    fn visit_xml_markup_expression(&mut self, node: &XmlMarkupExpression) {}
+    fn visit_xml_list_expression(&mut self, node: &XmlListExpression) { walk_xml_list_expression(self, node) }
+    fn visit_xml_element(&mut self, node: &Rc<XmlElement>) { walk_xml_element(self, node) }
+    fn visit_xml_attribute(&mut self, node: &Rc<XmlAttribute>) { walk_xml_attribute(self, node) }
+    fn visit_xml_content(&mut self, node: &Rc<XmlContent>) { walk_xml_content(self, node) }
+    fn visit_array_literal(&mut self, node: &ArrayLiteral) { walk_array_literal(self, node) }
+    fn visit_vector_literal(&mut self, node: &VectorLiteral) { walk_vector_literal(self, node) }
+    fn visit_array_element(&mut self, node: &Element) { walk_array_element(self, node) }
+    fn visit_object_initializer(&mut self, node: &ObjectInitializer) { walk_object_initializer(self, node) }
+    fn visit_initializer_field(&mut self, node: &Rc<InitializerField>) { walk_initializer_field(self, node) }
+    fn visit_function_expression(&mut self, node: &FunctionExpression) { walk_function_expression(self, node) }
+    fn visit_import_meta(&mut self, node: &ImportMeta) {}
+    fn visit_new_expression(&mut self, node: &NewExpression) { walk_new_expression(self, node) }
+    fn visit_member_expression(&mut self, node: &MemberExpression) { walk_member_expression(self, node) }
+    fn visit_computed_member_expression(&mut self, node: &ComputedMemberExpression) { walk_computed_member_expression(self, node) }
+    fn visit_descendants_expression(&mut self, node: &DescendantsExpression) { walk_descendants_expression(self, node) }
+    fn visit_filter_expression(&mut self, node: &FilterExpression) { walk_filter_expression(self, node) }
+    fn visit_super_expression(&mut self, node: &SuperExpression) { walk_super_expression(self, node) }
+    fn visit_call_expression(&mut self, node: &CallExpression) { walk_call_expression(self, node) }
+    fn visit_apply_type_expression(&mut self, node: &ApplyTypeExpression) { walk_apply_type_expression(self, node) }
+    fn visit_unary_expression(&mut self, node: &UnaryExpression) { walk_unary_expression(self, node) }
+    fn visit_optional_chaining_expression(&mut self, node: &OptionalChainingExpression) { walk_optional_chaining_expression(self, node) }
+    fn visit_optional_chaining_placeholder(&mut self, node: &OptionalChainingPlaceholder) {}
+    fn visit_binary_expression(&mut self, node: &BinaryExpression) { walk_binary_expression(self, node) }
+    fn visit_conditional_expression(&mut self, node: &ConditionalExpression) { walk_conditional_expression(self, node) }
+    fn visit_assignment_expression(&mut self, node: &AssignmentExpression) { walk_assignment_expression(self, node) }
+    fn visit_sequence_expression(&mut self, node: &SequenceExpression) { walk_sequence_expression(self, node) }
+    fn visit_nullable_type_expression(&mut self, node: &NullableTypeExpression) { walk_nullable_type_expression(self, node) }
+    fn visit_non_nullable_type_expression(&mut self, node: &NonNullableTypeExpression) { walk_non_nullable_type_expression(self, node) }
+    fn visit_any_type_expression(&mut self, node: &AnyTypeExpression) {}
+    fn visit_void_type_expression(&mut self, node: &VoidTypeExpression) {}
+    fn visit_array_type_expression(&mut self, node: &ArrayTypeExpression) { walk_array_type_expression(self, node) }
+    fn visit_tuple_type_expression(&mut self, node: &TupleTypeExpression) { walk_tuple_type_expression(self, node) }
+    fn visit_function_type_expression(&mut self, node: &FunctionTypeExpression) { walk_function_type_expression(self, node) }
+    fn visit_function_type_parameter(&mut self, node: &Rc<FunctionTypeParameter>) { walk_function_type_parameter(self, node) }
+    fn visit_invalidated_node(&mut self, node: &InvalidatedNode) {}
+    fn visit_reserved_namespace_expression(&mut self, node: &ReservedNamespaceExpression) {}
+
+    // Destructuring
+    fn visit_typed_destructuring(&mut self, node: &TypedDestructuring) { walk_typed_destructuring(self, node) }
+
+    // Statements
+    fn visit_directive(&mut self, node: &Rc<Directive>) { walk_directive(self, node) }
+    fn visit_empty_statement(&mut self, node: &EmptyStatement) {}
+    fn visit_expression_statement(&mut self, node: &ExpressionStatement) { walk_expression_statement(self, node) }
+    fn visit_super_statement(&mut self, node: &SuperStatement) { walk_super_statement(self, node) }
+    fn visit_block(&mut self, node: &Block) { walk_block(self, node) }
+    fn visit_labeled_statement(&mut self, node: &LabeledStatement) { walk_labeled_statement(self, node) }
+    fn visit_if_statement(&mut self, node: &IfStatement) { walk_if_statement(self, node) }
+    fn visit_switch_statement(&mut self, node: &SwitchStatement) { walk_switch_statement(self, node) }
+    fn visit_case(&mut self, node: &Case) { walk_case(self, node) }
+    fn visit_switch_type_statement(&mut self, node: &SwitchTypeStatement) { walk_switch_type_statement(self, node) }
+    fn visit_type_case(&mut self, node: &TypeCase) { walk_type_case(self, node) }
+    fn visit_do_statement(&mut self, node: &DoStatement) { walk_do_statement(self, node) }
+    fn visit_while_statement(&mut self, node: &WhileStatement) { walk_while_statement(self, node) }
+    fn visit_for_statement(&mut self, node: &ForStatement) { walk_for_statement(self, node) }
+    fn visit_for_in_statement(&mut self, node: &ForInStatement) { walk_for_in_statement(self, node) }
+    fn visit_simple_variable_definition(&mut self, node: &Rc<SimpleVariableDefinition>) { walk_simple_variable_definition(self, node) }
+    fn visit_break_statement(&mut self, node: &BreakStatement) {}
+    fn visit_continue_statement(&mut self, node: &ContinueStatement) {}
+    fn visit_with_statement(&mut self, node: &WithStatement) { walk_with_statement(self, node) }
+    fn visit_return_statement(&mut self, node: &ReturnStatement) { walk_return_statement(self, node) }
+    fn visit_throw_statement(&mut self, node: &ThrowStatement) { walk_throw_statement(self, node) }
+    fn visit_default_xml_namespace_statement(&mut self, node: &DefaultXmlNamespaceStatement) { walk_default_xml_namespace_statement(self, node) }
+    fn visit_try_statement(&mut self, node: &TryStatement) { walk_try_statement(self, node) }
+    fn visit_catch_clause(&mut self, node: &CatchClause) { walk_catch_clause(self, node) }
+    fn visit_finally_clause(&mut self, node: &FinallyClause) { walk_finally_clause(self, node) }
+
+    // Directives
+    fn visit_configuration_directive(&mut self, node: &ConfigurationDirective) { walk_configuration_directive(self, node) }
+    fn visit_import_directive(&mut self, node: &ImportDirective) {}
+    fn visit_use_namespace_directive(&mut self, node: &UseNamespaceDirective) { walk_use_namespace_directive(self, node) }
+    fn visit_include_directive(&mut self, node: &IncludeDirective) { walk_include_directive(self, node) }
+    fn visit_package_concat_directive(&mut self, node: &PackageConcatDirective) {}
+    fn visit_directive_injection_node(&mut self, node: &DirectiveInjectionNode) { walk_directive_injection_node(self, node) }
+
+    // Miscellaneous
+    fn visit_attribute(&mut self, node: &Attribute) { walk_attribute(self, node) }
+    fn visit_metadata(&mut self, node: &Rc<Metadata>) { walk_metadata(self, node) }
+    fn visit_metadata_entry(&mut self, node: &Rc<MetadataEntry>) {}
+    fn visit_asdoc(&mut self, node: &Rc<Asdoc>) { walk_asdoc(self, node) }
+    fn visit_asdoc_reference(&mut self, node: &Rc<AsdocReference>) { walk_asdoc_reference(self, node) }
+    fn visit_type_parameter(&mut self, node: &Rc<TypeParameter>) {}
+
+    // Definitions
+    fn visit_variable_definition(&mut self, node: &VariableDefinition) { walk_variable_definition(self, node) }
+    fn visit_variable_binding(&mut self, node: &Rc<VariableBinding>) { walk_variable_binding(self, node) }
+    fn visit_function_definition(&mut self, node: &FunctionDefinition) { walk_function_definition(self, node) }
+    fn visit_function_common(&mut self, node: &Rc<FunctionCommon>) { walk_function_common(self, node) }
+    fn visit_function_signature(&mut self, node: &FunctionSignature) { walk_function_signature(self, node) }
+    fn visit_this_parameter(&mut self, node: &Rc<ThisParameter>) { walk_this_parameter(self, node) }
+    fn visit_parameter(&mut self, node: &Rc<Parameter>) { walk_parameter(self, node) }
+    fn visit_class_definition(&mut self, node: &ClassDefinition) { walk_class_definition(self, node) }
+    fn visit_enum_definition(&mut self, node: &EnumDefinition) { walk_enum_definition(self, node) }
+    fn visit_interface_definition(&mut self, node: &InterfaceDefinition) { walk_interface_definition(self, node) }
+    fn visit_type_definition(&mut self, node: &TypeDefinition) { walk_type_definition(self, node) }
+    fn visit_namespace_definition(&mut self, node: &NamespaceDefinition) { walk_namespace_definition(self, node) }
+}
+
+pub fn walk_program<V: Visitor>(v: &mut V, node: &Rc<Program>) {
+    for pckg in &node.packages {
+        v.visit_package_definition(pckg);
+    }
+    for drtv in &node.directives {
+        v.visit_directive(drtv);
+    }
+}
+
+pub fn walk_package_definition<V: Visitor>(v: &mut V, node: &Rc<PackageDefinition>) {
+    if let Some(asdoc) = &node.asdoc {
+        v.visit_asdoc(asdoc);
+    }
+    v.visit_block(&node.block);
+}
+
+pub fn walk_expression<V: Visitor>(v: &mut V, node: &Rc<Expression>) {
+    match node.as_ref() {
+        Expression::QualifiedIdentifier(e) => v.visit_qualified_identifier(e),
+        Expression::Paren(e) => v.visit_paren_expression(e),
+        Expression::NullLiteral(e) => v.visit_null_literal(e),
+        Expression::BooleanLiteral(e) => v.visit_boolean_literal(e),
+        Expression::NumericLiteral(e) => v.visit_numeric_literal(e),
+        Expression::StringLiteral(e) => v.visit_string_literal(e),
+        Expression::ThisLiteral(e) => v.visit_this_literal(e),
+        Expression::RegExpLiteral(e) => v.visit_regexp_literal(e),
+        Expression::Xml(e) => v.visit_xml_expression(e),
+        Expression::XmlMarkup(e) => v.visit_xml_markup_expression(e),
+        Expression::XmlList(e) => v.visit_xml_list_expression(e),
+        Expression::ArrayLiteral(e) => v.visit_array_literal(e),
+        Expression::VectorLiteral(e) => v.visit_vector_literal(e),
+        Expression::ObjectInitializer(e) => v.visit_object_initializer(e),
+        Expression::Function(e) => v.visit_function_expression(e),
+        Expression::ImportMeta(e) => v.visit_import_meta(e),
+        Expression::New(e) => v.visit_new_expression(e),
+        Expression::Member(e) => v.visit_member_expression(e),
+        Expression::ComputedMember(e) => v.visit_computed_member_expression(e),
+        Expression::Descendants(e) => v.visit_descendants_expression(e),
+        Expression::Filter(e) => v.visit_filter_expression(e),
+        Expression::Super(e) => v.visit_super_expression(e),
+        Expression::Call(e) => v.visit_call_expression(e),
+        Expression::WithTypeArguments(e) => v.visit_apply_type_expression(e),
+        Expression::Unary(e) => v.visit_unary_expression(e),
+        Expression::OptionalChaining(e) => v.visit_optional_chaining_expression(e),
+        Expression::OptionalChainingPlaceholder(e) => v.visit_optional_chaining_placeholder(e),
+        Expression::Binary(e) => v.visit_binary_expression(e),
+        Expression::Conditional(e) => v.visit_conditional_expression(e),
+        Expression::Assignment(e) => v.visit_assignment_expression(e),
+        Expression::Sequence(e) => v.visit_sequence_expression(e),
+        Expression::NullableType(e) => v.visit_nullable_type_expression(e),
+        Expression::NonNullableType(e) => v.visit_non_nullable_type_expression(e),
+        Expression::AnyType(e) => v.visit_any_type_expression(e),
+        Expression::VoidType(e) => v.visit_void_type_expression(e),
+        Expression::ArrayType(e) => v.visit_array_type_expression(e),
+        Expression::TupleType(e) => v.visit_tuple_type_expression(e),
+        Expression::FunctionType(e) => v.visit_function_type_expression(e),
+        Expression::Invalidated(e) => v.visit_invalidated_node(e),
+        Expression::ReservedNamespace(e) => v.visit_reserved_namespace_expression(e),
+    }
+}
+
+fn walk_expressions<V: Visitor>(v: &mut V, list: &[Rc<Expression>]) {
+    for exp in list {
+        v.visit_expression(exp);
+    }
+}
+
+fn walk_directives<V: Visitor>(v: &mut V, list: &[Rc<Directive>]) {
+    for drtv in list {
+        v.visit_directive(drtv);
+    }
+}
+
+fn walk_asdoc_and_attributes<V: Visitor>(v: &mut V, asdoc: &Option<Rc<Asdoc>>, attributes: &[Attribute]) {
+    if let Some(asdoc) = asdoc {
+        v.visit_asdoc(asdoc);
+    }
+    for attr in attributes {
+        v.visit_attribute(attr);
+    }
+}
+
+pub fn walk_qualified_identifier<V: Visitor>(v: &mut V, node: &QualifiedIdentifier) {
+    if let Some(qual) = &node.qualifier {
+        v.visit_expression(qual);
+    }
+    if let QualifiedIdentifierIdentifier::Brackets(exp) = &node.id {
+        v.visit_expression(exp);
+    }
+}
+
+pub fn walk_paren_expression<V: Visitor>(v: &mut V, node: &ParenExpression) {
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_xml_expression<V: Visitor>(v: &mut V, node: &XmlExpression) {
+    v.visit_xml_element(&node.element);
+}
+
+pub fn walk_xml_list_expression<V: Visitor>(v: &mut V, node: &XmlListExpression) {
+    for content in &node.content {
+        v.visit_xml_content(content);
+    }
+}
+
+pub fn walk_xml_element<V: Visitor>(v: &mut V, node: &Rc<XmlElement>) {
+    if let XmlTagName::Expression(exp) = &node.name {
+        v.visit_expression(exp);
+    }
+    for attr in &node.attributes {
+        v.visit_xml_attribute(attr);
+    }
+    if let Some(exp) = &node.attribute_expression {
+        v.visit_expression(exp);
+    }
+    if let Some(content) = &node.content {
+        for content in content {
+            v.visit_xml_content(content);
+        }
+    }
+    if let Some(XmlTagName::Expression(exp)) = &node.closing_name {
+        v.visit_expression(exp);
+    }
+}
+
+pub fn walk_xml_attribute<V: Visitor>(v: &mut V, node: &Rc<XmlAttribute>) {
+    if let XmlAttributeValue::Expression(exp) = &node.value {
+        v.visit_expression(exp);
+    }
+}
+
+pub fn walk_xml_content<V: Visitor>(v: &mut V, node: &Rc<XmlContent>) {
+    match node.as_ref() {
+        XmlContent::Element(elem) => v.visit_xml_element(elem),
+        XmlContent::Expression(exp) => v.visit_expression(exp),
+        XmlContent::Characters(_) | XmlContent::Markup(_) => {},
+    }
+}
+
+pub fn walk_array_literal<V: Visitor>(v: &mut V, node: &ArrayLiteral) {
+    if let Some(asdoc) = &node.asdoc {
+        v.visit_asdoc(asdoc);
+    }
+    for elem in &node.elements {
+        v.visit_array_element(elem);
+    }
+}
+
+pub fn walk_vector_literal<V: Visitor>(v: &mut V, node: &VectorLiteral) {
+    v.visit_expression(&node.element_type);
+    for elem in &node.elements {
+        v.visit_array_element(elem);
+    }
+}
+
+pub fn walk_array_element<V: Visitor>(v: &mut V, node: &Element) {
+    match node {
+        Element::Elision => {},
+        Element::Expression(exp) => v.visit_expression(exp),
+        Element::Rest((exp, _)) => v.visit_expression(exp),
+    }
+}
+
+pub fn walk_object_initializer<V: Visitor>(v: &mut V, node: &ObjectInitializer) {
+    for field in &node.fields {
+        v.visit_initializer_field(field);
+    }
+}
+
+pub fn walk_initializer_field<V: Visitor>(v: &mut V, node: &Rc<InitializerField>) {
+    match node.as_ref() {
+        InitializerField::Field { name, value, .. } => {
+            match &name.0 {
+                FieldName::Identifier(id) => v.visit_qualified_identifier(id),
+                FieldName::Brackets(exp) |
+                FieldName::StringLiteral(exp) |
+                FieldName::NumericLiteral(exp) => v.visit_expression(exp),
+            }
+            if let Some(value) = value {
+                v.visit_expression(value);
+            }
+        },
+        InitializerField::Rest((exp, _)) => v.visit_expression(exp),
+    }
+}
+
+pub fn walk_function_expression<V: Visitor>(v: &mut V, node: &FunctionExpression) {
+    v.visit_function_common(&node.common);
+}
+
+pub fn walk_new_expression<V: Visitor>(v: &mut V, node: &NewExpression) {
+    v.visit_expression(&node.base);
+    if let Some(arguments) = &node.arguments {
+        walk_expressions(v, arguments);
+    }
+}
+
+pub fn walk_member_expression<V: Visitor>(v: &mut V, node: &MemberExpression) {
+    v.visit_expression(&node.base);
+    v.visit_qualified_identifier(&node.identifier);
+}
+
+pub fn walk_computed_member_expression<V: Visitor>(v: &mut V, node: &ComputedMemberExpression) {
+    v.visit_expression(&node.base);
+    if let Some(asdoc) = &node.asdoc {
+        v.visit_asdoc(asdoc);
+    }
+    v.visit_expression(&node.key);
+}
+
+pub fn walk_descendants_expression<V: Visitor>(v: &mut V, node: &DescendantsExpression) {
+    v.visit_expression(&node.base);
+    v.visit_qualified_identifier(&node.identifier);
+}
+
+pub fn walk_filter_expression<V: Visitor>(v: &mut V, node: &FilterExpression) {
+    v.visit_expression(&node.base);
+    v.visit_expression(&node.test);
+}
+
+pub fn walk_super_expression<V: Visitor>(v: &mut V, node: &SuperExpression) {
+    if let Some(object) = &node.object {
+        walk_expressions(v, object);
+    }
+}
+
+pub fn walk_call_expression<V: Visitor>(v: &mut V, node: &CallExpression) {
+    v.visit_expression(&node.base);
+    walk_expressions(v, &node.arguments);
+}
+
+pub fn walk_apply_type_expression<V: Visitor>(v: &mut V, node: &ApplyTypeExpression) {
+    v.visit_expression(&node.base);
+    walk_expressions(v, &node.arguments);
+}
+
+pub fn walk_unary_expression<V: Visitor>(v: &mut V, node: &UnaryExpression) {
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_optional_chaining_expression<V: Visitor>(v: &mut V, node: &OptionalChainingExpression) {
+    v.visit_expression(&node.base);
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_binary_expression<V: Visitor>(v: &mut V, node: &BinaryExpression) {
+    v.visit_expression(&node.left);
+    v.visit_expression(&node.right);
+}
+
+pub fn walk_conditional_expression<V: Visitor>(v: &mut V, node: &ConditionalExpression) {
+    v.visit_expression(&node.test);
+    v.visit_expression(&node.consequent);
+    v.visit_expression(&node.alternative);
+}
+
+pub fn walk_assignment_expression<V: Visitor>(v: &mut V, node: &AssignmentExpression) {
+    v.visit_expression(&node.left);
+    v.visit_expression(&node.right);
+}
+
+pub fn walk_sequence_expression<V: Visitor>(v: &mut V, node: &SequenceExpression) {
+    v.visit_expression(&node.left);
+    v.visit_expression(&node.right);
+}
+
+pub fn walk_nullable_type_expression<V: Visitor>(v: &mut V, node: &NullableTypeExpression) {
+    v.visit_expression(&node.base);
+}
+
+pub fn walk_non_nullable_type_expression<V: Visitor>(v: &mut V, node: &NonNullableTypeExpression) {
+    v.visit_expression(&node.base);
+}
+
+pub fn walk_array_type_expression<V: Visitor>(v: &mut V, node: &ArrayTypeExpression) {
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_tuple_type_expression<V: Visitor>(v: &mut V, node: &TupleTypeExpression) {
+    walk_expressions(v, &node.expressions);
+}
+
+pub fn walk_function_type_expression<V: Visitor>(v: &mut V, node: &FunctionTypeExpression) {
+    for param in &node.parameters {
+        v.visit_function_type_parameter(param);
+    }
+    if let Some(result_type) = &node.result_type {
+        v.visit_expression(result_type);
+    }
+}
+
+pub fn walk_function_type_parameter<V: Visitor>(v: &mut V, node: &Rc<FunctionTypeParameter>) {
+    if let Some(exp) = &node.type_expression {
+        v.visit_expression(exp);
+    }
+}
+
+pub fn walk_typed_destructuring<V: Visitor>(v: &mut V, node: &TypedDestructuring) {
+    v.visit_expression(&node.destructuring);
+    if let Some(annotation) = &node.type_annotation {
+        v.visit_expression(annotation);
+    }
+}
+
+pub fn walk_directive<V: Visitor>(v: &mut V, node: &Rc<Directive>) {
+    match node.as_ref() {
+        Directive::EmptyStatement(d) => v.visit_empty_statement(d),
+        Directive::ExpressionStatement(d) => v.visit_expression_statement(d),
+        Directive::SuperStatement(d) => v.visit_super_statement(d),
+        Directive::Block(d) => v.visit_block(d),
+        Directive::LabeledStatement(d) => v.visit_labeled_statement(d),
+        Directive::IfStatement(d) => v.visit_if_statement(d),
+        Directive::SwitchStatement(d) => v.visit_switch_statement(d),
+        Directive::SwitchTypeStatement(d) => v.visit_switch_type_statement(d),
+        Directive::DoStatement(d) => v.visit_do_statement(d),
+        Directive::WhileStatement(d) => v.visit_while_statement(d),
+        Directive::ForStatement(d) => v.visit_for_statement(d),
+        Directive::ForInStatement(d) => v.visit_for_in_statement(d),
+        Directive::BreakStatement(d) => v.visit_break_statement(d),
+        Directive::ContinueStatement(d) => v.visit_continue_statement(d),
+        Directive::WithStatement(d) => v.visit_with_statement(d),
+        Directive::ReturnStatement(d) => v.visit_return_statement(d),
+        Directive::ThrowStatement(d) => v.visit_throw_statement(d),
+        Directive::DefaultXmlNamespaceStatement(d) => v.visit_default_xml_namespace_statement(d),
+        Directive::TryStatement(d) => v.visit_try_statement(d),
+        Directive::Invalidated(d) => v.visit_invalidated_node(d),
+        Directive::ConfigurationDirective(d) => v.visit_configuration_directive(d),
+        Directive::ImportDirective(d) => v.visit_import_directive(d),
+        Directive::UseNamespaceDirective(d) => v.visit_use_namespace_directive(d),
+        Directive::IncludeDirective(d) => v.visit_include_directive(d),
+        Directive::PackageConcatDirective(d) => v.visit_package_concat_directive(d),
+        Directive::DirectiveInjection(d) => v.visit_directive_injection_node(d),
+        Directive::VariableDefinition(d) => v.visit_variable_definition(d),
+        Directive::FunctionDefinition(d) => v.visit_function_definition(d),
+        Directive::ClassDefinition(d) => v.visit_class_definition(d),
+        Directive::EnumDefinition(d) => v.visit_enum_definition(d),
+        Directive::InterfaceDefinition(d) => v.visit_interface_definition(d),
+        Directive::TypeDefinition(d) => v.visit_type_definition(d),
+        Directive::NamespaceDefinition(d) => v.visit_namespace_definition(d),
+    }
+}
+
+pub fn walk_expression_statement<V: Visitor>(v: &mut V, node: &ExpressionStatement) {
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_super_statement<V: Visitor>(v: &mut V, node: &SuperStatement) {
+    walk_expressions(v, &node.arguments);
+}
+
+pub fn walk_block<V: Visitor>(v: &mut V, node: &Block) {
+    walk_directives(v, &node.directives);
+}
+
+pub fn walk_labeled_statement<V: Visitor>(v: &mut V, node: &LabeledStatement) {
+    v.visit_directive(&node.substatement);
+}
+
+pub fn walk_if_statement<V: Visitor>(v: &mut V, node: &IfStatement) {
+    v.visit_expression(&node.test);
+    v.visit_directive(&node.consequent);
+    if let Some(alternative) = &node.alternative {
+        v.visit_directive(alternative);
+    }
+}
+
+pub fn walk_switch_statement<V: Visitor>(v: &mut V, node: &SwitchStatement) {
+    v.visit_expression(&node.discriminant);
+    for case in &node.cases {
+        v.visit_case(case);
+    }
+}
+
+pub fn walk_case<V: Visitor>(v: &mut V, node: &Case) {
+    for label in &node.labels {
+        if let CaseLabel::Case((exp, _)) = label {
+            v.visit_expression(exp);
+        }
+    }
+    walk_directives(v, &node.directives);
+}
+
+pub fn walk_switch_type_statement<V: Visitor>(v: &mut V, node: &SwitchTypeStatement) {
+    v.visit_expression(&node.discriminant);
+    for case in &node.cases {
+        v.visit_type_case(case);
+    }
+}
+
+pub fn walk_type_case<V: Visitor>(v: &mut V, node: &TypeCase) {
+    if let Some(param) = &node.parameter {
+        v.visit_typed_destructuring(param);
+    }
+    v.visit_block(&node.block);
+}
+
+pub fn walk_do_statement<V: Visitor>(v: &mut V, node: &DoStatement) {
+    v.visit_directive(&node.body);
+    v.visit_expression(&node.test);
+}
+
+pub fn walk_while_statement<V: Visitor>(v: &mut V, node: &WhileStatement) {
+    v.visit_expression(&node.test);
+    v.visit_directive(&node.body);
+}
+
+pub fn walk_for_statement<V: Visitor>(v: &mut V, node: &ForStatement) {
+    match &node.init {
+        Some(ForInitializer::Expression(exp)) => v.visit_expression(exp),
+        Some(ForInitializer::VariableDefinition(defn)) => v.visit_simple_variable_definition(defn),
+        None => {},
+    }
+    if let Some(test) = &node.test {
+        v.visit_expression(test);
+    }
+    if let Some(update) = &node.update {
+        v.visit_expression(update);
+    }
+    v.visit_directive(&node.body);
+}
+
+pub fn walk_for_in_statement<V: Visitor>(v: &mut V, node: &ForInStatement) {
+    match &node.left {
+        ForInBinding::Expression(exp) => v.visit_expression(exp),
+        ForInBinding::VariableDefinition(defn) => v.visit_simple_variable_definition(defn),
+    }
+    v.visit_expression(&node.right);
+    v.visit_directive(&node.body);
+}
+
+pub fn walk_simple_variable_definition<V: Visitor>(v: &mut V, node: &Rc<SimpleVariableDefinition>) {
+    for binding in &node.bindings {
+        v.visit_variable_binding(binding);
+    }
+}
+
+pub fn walk_with_statement<V: Visitor>(v: &mut V, node: &WithStatement) {
+    v.visit_expression(&node.object);
+    v.visit_directive(&node.body);
+}
+
+pub fn walk_return_statement<V: Visitor>(v: &mut V, node: &ReturnStatement) {
+    if let Some(exp) = &node.expression {
+        v.visit_expression(exp);
+    }
+}
+
+pub fn walk_throw_statement<V: Visitor>(v: &mut V, node: &ThrowStatement) {
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_default_xml_namespace_statement<V: Visitor>(v: &mut V, node: &DefaultXmlNamespaceStatement) {
+    v.visit_expression(&node.right);
+}
+
+pub fn walk_try_statement<V: Visitor>(v: &mut V, node: &TryStatement) {
+    v.visit_block(&node.block);
+    for catch_clause in &node.catch_clauses {
+        v.visit_catch_clause(catch_clause);
+    }
+    if let Some(finally_clause) = &node.finally_clause {
+        v.visit_finally_clause(finally_clause);
+    }
+}
+
+pub fn walk_catch_clause<V: Visitor>(v: &mut V, node: &CatchClause) {
+    v.visit_typed_destructuring(&node.parameter);
+    v.visit_block(&node.block);
+}
+
+pub fn walk_finally_clause<V: Visitor>(v: &mut V, node: &FinallyClause) {
+    v.visit_block(&node.block);
+}
+
+pub fn walk_configuration_directive<V: Visitor>(v: &mut V, node: &ConfigurationDirective) {
+    v.visit_directive(&node.directive);
+}
+
+pub fn walk_use_namespace_directive<V: Visitor>(v: &mut V, node: &UseNamespaceDirective) {
+    v.visit_expression(&node.expression);
+}
+
+pub fn walk_include_directive<V: Visitor>(v: &mut V, node: &IncludeDirective) {
+    for pckg in &node.nested_packages {
+        v.visit_package_definition(pckg);
+    }
+    walk_directives(v, &node.nested_directives);
+}
+
+pub fn walk_directive_injection_node<V: Visitor>(v: &mut V, node: &DirectiveInjectionNode) {
+    walk_directives(v, &node.directives.borrow());
+}
+
+pub fn walk_attribute<V: Visitor>(v: &mut V, node: &Attribute) {
+    match node {
+        Attribute::Metadata(metadata) => v.visit_metadata(metadata),
+        Attribute::Expression(exp) => v.visit_expression(exp),
+        _ => {},
+    }
+}
+
+pub fn walk_metadata<V: Visitor>(v: &mut V, node: &Rc<Metadata>) {
+    if let Some(asdoc) = &node.asdoc {
+        v.visit_asdoc(asdoc);
+    }
+    if let Some(entries) = &node.entries {
+        for entry in entries {
+            v.visit_metadata_entry(entry);
+        }
+    }
+}
+
+pub fn walk_asdoc<V: Visitor>(v: &mut V, node: &Rc<Asdoc>) {
+    for (tag, _) in &node.tags {
+        match tag {
+            AsdocTag::Copy(reference) => v.visit_asdoc_reference(reference),
+            AsdocTag::See { reference, .. } => v.visit_asdoc_reference(reference),
+            AsdocTag::EventType(exp) => v.visit_expression(exp),
+            AsdocTag::Throws { class_reference, .. } => v.visit_expression(class_reference),
+            _ => {},
+        }
+    }
+}
+
+pub fn walk_asdoc_reference<V: Visitor>(v: &mut V, node: &Rc<AsdocReference>) {
+    if let Some(base) = &node.base {
+        v.visit_expression(base);
+    }
+    if let Some(id) = &node.instance_property {
+        v.visit_qualified_identifier(id);
+    }
+}
+
+pub fn walk_variable_definition<V: Visitor>(v: &mut V, node: &VariableDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    for binding in &node.bindings {
+        v.visit_variable_binding(binding);
+    }
+}
+
+pub fn walk_variable_binding<V: Visitor>(v: &mut V, node: &Rc<VariableBinding>) {
+    v.visit_typed_destructuring(&node.destructuring);
+    if let Some(init) = &node.initializer {
+        v.visit_expression(init);
+    }
+}
+
+pub fn walk_function_definition<V: Visitor>(v: &mut V, node: &FunctionDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    v.visit_function_common(&node.common);
+}
+
+pub fn walk_function_common<V: Visitor>(v: &mut V, node: &Rc<FunctionCommon>) {
+    v.visit_function_signature(&node.signature);
+    match &node.body {
+        Some(FunctionBody::Expression(exp)) => v.visit_expression(exp),
+        Some(FunctionBody::Block(block)) => v.visit_block(block),
+        Some(FunctionBody::Lazy(_)) | None => {},
+    }
+}
+
+pub fn walk_function_signature<V: Visitor>(v: &mut V, node: &FunctionSignature) {
+    if let Some(this_param) = &node.this_parameter {
+        v.visit_this_parameter(this_param);
+    }
+    for param in &node.parameters {
+        v.visit_parameter(param);
+    }
+    if let Some(result_type) = &node.result_type {
+        v.visit_expression(result_type);
+    }
+}
+
+pub fn walk_this_parameter<V: Visitor>(v: &mut V, node: &Rc<ThisParameter>) {
+    v.visit_expression(&node.type_annotation);
+}
+
+pub fn walk_parameter<V: Visitor>(v: &mut V, node: &Rc<Parameter>) {
+    v.visit_typed_destructuring(&node.destructuring);
+    if let Some(default_value) = &node.default_value {
+        v.visit_expression(default_value);
+    }
+}
+
+pub fn walk_class_definition<V: Visitor>(v: &mut V, node: &ClassDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    if let Some(type_params) = &node.type_parameters {
+        for param in type_params {
+            v.visit_type_parameter(param);
+        }
+    }
+    if let Some(exp) = &node.extends_clause {
+        v.visit_expression(exp);
+    }
+    if let Some(list) = &node.implements_clause {
+        walk_expressions(v, list);
+    }
+    v.visit_block(&node.block);
+}
+
+pub fn walk_enum_definition<V: Visitor>(v: &mut V, node: &EnumDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    if let Some(exp) = &node.as_clause {
+        v.visit_expression(exp);
+    }
+    v.visit_block(&node.block);
+}
+
+pub fn walk_interface_definition<V: Visitor>(v: &mut V, node: &InterfaceDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    if let Some(type_params) = &node.type_parameters {
+        for param in type_params {
+            v.visit_type_parameter(param);
+        }
+    }
+    if let Some(list) = &node.extends_clause {
+        walk_expressions(v, list);
+    }
+    v.visit_block(&node.block);
+}
+
+pub fn walk_type_definition<V: Visitor>(v: &mut V, node: &TypeDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    v.visit_expression(&node.right);
+}
+
+pub fn walk_namespace_definition<V: Visitor>(v: &mut V, node: &NamespaceDefinition) {
+    walk_asdoc_and_attributes(v, &node.asdoc, &node.attributes);
+    if let Some(exp) = &node.right {
+        v.visit_expression(exp);
+    }
+}
@@ -14,10 +14,40 @@ pub use escaping::*;
 mod css;
 pub use css::*;
 
-pub use std::cell::{Cell, RefCell};
+mod binary;
+pub(crate) use binary::*;
+
 pub use std::collections::{HashMap, HashSet};
+
+#[cfg(not(feature = "sync"))]
+pub use std::cell::{Cell, RefCell, Ref, RefMut};
+#[cfg(not(feature = "sync"))]
 pub use std::rc::{Rc, Weak};
 
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(feature = "sync")]
+pub use sync::*;
+
+/// A dynamically typed value, such as compiler options attached
+/// to a compilation unit. It is `Send + Sync` when the `sync`
+/// feature is enabled.
+#[cfg(not(feature = "sync"))]
+pub type DynAny = dyn std::any::Any;
+#[cfg(feature = "sync")]
+pub type DynAny = dyn std::any::Any + Send + Sync;
+
+/// Implemented by every type, or by every `Send + Sync` type
+/// when the `sync` feature is enabled.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSync for T {}
+#[cfg(feature = "sync")]
+pub trait MaybeSync: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> MaybeSync for T {}
+
 pub fn default<T: Default>() -> T {
     T::default()
 }
@@ -0,0 +1,605 @@
+//! A compact binary serialization format.
+//!
+//! The format is not self-describing: values are written in declaration
+//! order, integers as LEB128 variable-length integers (zigzag-encoded
+//! if signed), and every string as an index into a string table that
+//! precedes the values.
+
+use crate::ns::*;
+use serde::{ser, de, Serialize};
+use serde::de::IntoDeserializer;
+use std::fmt::Display;
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct BinaryFormatError(pub String);
+
+impl Display for BinaryFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for BinaryFormatError {}
+
+impl ser::Error for BinaryFormatError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+impl de::Error for BinaryFormatError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, BinaryFormatError>;
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+pub(crate) struct BinarySerializer {
+    output: Vec<u8>,
+    strings: HashMap<String, u32>,
+    allow_skipped_fields: bool,
+}
+
+impl BinarySerializer {
+    pub fn new() -> Self {
+        Self { output: vec![], strings: HashMap::new(), allow_skipped_fields: false }
+    }
+
+    /// Allows fields to be skipped, for output that is
+    /// compared rather than deserialized.
+    pub fn allow_skipped_fields(mut self) -> Self {
+        self.allow_skipped_fields = true;
+        self
+    }
+
+    fn check_skipped_field(&self, key: &'static str) -> Result<()> {
+        if self.allow_skipped_fields {
+            return Ok(());
+        }
+        Err(BinaryFormatError(format!("field `{key}` cannot be skipped")))
+    }
+
+    /// Returns the string table followed by the serialized values.
+    pub fn finish(self) -> Vec<u8> {
+        let mut strings: Vec<&str> = vec![""; self.strings.len()];
+        for (string, index) in &self.strings {
+            strings[*index as usize] = string;
+        }
+        let mut output = vec![];
+        write_varint(&mut output, strings.len() as u64);
+        for string in strings {
+            write_varint(&mut output, string.len() as u64);
+            output.extend_from_slice(string.as_bytes());
+        }
+        output.extend_from_slice(&self.output);
+        output
+    }
+
+    fn write_varint(&mut self, value: u64) {
+        write_varint(&mut self.output, value);
+    }
+}
+
+impl ser::Serializer for &mut BinarySerializer {
+    type Ok = ();
+    type Error = BinaryFormatError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.output.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.write_varint(zigzag(v));
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.output.push(v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.write_varint(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.output.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.serialize_u64(v.into())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        let index = match self.strings.get(v) {
+            Some(index) => *index,
+            None => {
+                let index = self.strings.len() as u32;
+                self.strings.insert(v.to_owned(), index);
+                index
+            },
+        };
+        self.serialize_u32(index)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.write_varint(v.len() as u64);
+        self.output.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.serialize_bool(false)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        self.serialize_bool(true)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str) -> Result<()> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, variant_index: u32, _variant: &'static str, value: &T) -> Result<()> {
+        self.serialize_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self> {
+        let len = len.ok_or_else(|| BinaryFormatError("sequences must have a known length".into()))?;
+        self.write_varint(len as u64);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self> {
+        let len = len.ok_or_else(|| BinaryFormatError("maps must have a known length".into()))?;
+        self.write_varint(len as u64);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+}
+
+macro impl_serialize_compound($trait:ident, $method:ident) {
+    impl ser::$trait for &mut BinarySerializer {
+        type Ok = ();
+        type Error = BinaryFormatError;
+
+        fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+            value.serialize(&mut **self)
+        }
+
+        fn end(self) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+impl_serialize_compound!(SerializeSeq, serialize_element);
+impl_serialize_compound!(SerializeTuple, serialize_element);
+impl_serialize_compound!(SerializeTupleStruct, serialize_field);
+impl_serialize_compound!(SerializeTupleVariant, serialize_field);
+
+impl ser::SerializeStruct for &mut BinarySerializer {
+    type Ok = ();
+    type Error = BinaryFormatError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.check_skipped_field(key)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut BinarySerializer {
+    type Ok = ();
+    type Error = BinaryFormatError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.check_skipped_field(key)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut BinarySerializer {
+    type Ok = ();
+    type Error = BinaryFormatError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(crate) struct BinaryDeserializer<'de> {
+    input: &'de [u8],
+    position: usize,
+    strings: Vec<&'de str>,
+}
+
+impl<'de> BinaryDeserializer<'de> {
+    /// Reads the string table at the start of `input`.
+    pub fn new(input: &'de [u8]) -> Result<Self> {
+        let mut deserializer = Self { input, position: 0, strings: vec![] };
+        let count = deserializer.read_len()?;
+        let mut strings = Vec::with_capacity(count.min(input.len()));
+        for _ in 0..count {
+            let len = deserializer.read_len()?;
+            let bytes = deserializer.read_bytes(len)?;
+            strings.push(std::str::from_utf8(bytes).map_err(|_| BinaryFormatError("invalid UTF-8 in string table".into()))?);
+        }
+        deserializer.strings = strings;
+        Ok(deserializer)
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position == self.input.len()
+    }
+
+    fn read_byte(&mut self) -> Result<u8> {
+        let byte = *self.input.get(self.position).ok_or_else(|| BinaryFormatError("unexpected end of input".into()))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'de [u8]> {
+        let end = self.position.checked_add(len).filter(|end| *end <= self.input.len())
+            .ok_or_else(|| BinaryFormatError("unexpected end of input".into()))?;
+        let bytes = &self.input[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn read_varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                if shift == 63 && byte > 1 {
+                    break;
+                }
+                return Ok(value);
+            }
+        }
+        Err(BinaryFormatError("integer overflow".into()))
+    }
+
+    fn read_len(&mut self) -> Result<usize> {
+        self.read_integer()
+    }
+
+    fn read_integer<T: TryFrom<u64>>(&mut self) -> Result<T> {
+        T::try_from(self.read_varint()?).map_err(|_| BinaryFormatError("integer out of range".into()))
+    }
+
+    fn read_signed_integer<T: TryFrom<i64>>(&mut self) -> Result<T> {
+        T::try_from(unzigzag(self.read_varint()?)).map_err(|_| BinaryFormatError("integer out of range".into()))
+    }
+
+    fn read_bool(&mut self) -> Result<bool> {
+        match self.read_byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(BinaryFormatError("invalid boolean".into())),
+        }
+    }
+
+    fn read_str(&mut self) -> Result<&'de str> {
+        let index: usize = self.read_integer()?;
+        self.strings.get(index).copied().ok_or_else(|| BinaryFormatError(format!("unknown string {index}")))
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut BinaryDeserializer<'de> {
+    type Error = BinaryFormatError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(BinaryFormatError("the binary format is not self-describing".into()))
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_bool(self.read_bool()?)
+    }
+
+    fn deserialize_i8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i8(self.read_signed_integer()?)
+    }
+
+    fn deserialize_i16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i16(self.read_signed_integer()?)
+    }
+
+    fn deserialize_i32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i32(self.read_signed_integer()?)
+    }
+
+    fn deserialize_i64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_i64(self.read_signed_integer()?)
+    }
+
+    fn deserialize_u8<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u8(self.read_byte()?)
+    }
+
+    fn deserialize_u16<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u16(self.read_integer()?)
+    }
+
+    fn deserialize_u32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u32(self.read_integer()?)
+    }
+
+    fn deserialize_u64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_u64(self.read_varint()?)
+    }
+
+    fn deserialize_f32<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f32(f32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+    }
+
+    fn deserialize_f64<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_f64(f64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+    }
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let value: u32 = self.read_integer()?;
+        visitor.visit_char(char::from_u32(value).ok_or_else(|| BinaryFormatError("invalid character".into()))?)
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_borrowed_str(self.read_str()?)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_borrowed_bytes(self.read_bytes(len)?)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.read_bool()? {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_seq(BinaryAccess { deserializer: self, remaining: len })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(BinaryAccess { deserializer: self, remaining: len })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_map(BinaryAccess { deserializer: self, remaining: len })
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(BinaryFormatError("the binary format has no identifiers".into()))
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(BinaryFormatError("the binary format is not self-describing".into()))
+    }
+}
+
+struct BinaryAccess<'a, 'de> {
+    deserializer: &'a mut BinaryDeserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for BinaryAccess<'a, 'de> {
+    type Error = BinaryFormatError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for BinaryAccess<'a, 'de> {
+    type Error = BinaryFormatError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut BinaryDeserializer<'de> {
+    type Error = BinaryFormatError;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let index: u32 = self.read_integer()?;
+        let value = seed.deserialize(index.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut BinaryDeserializer<'de> {
+    type Error = BinaryFormatError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_tuple(self, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+    }
+}
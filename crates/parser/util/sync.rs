@@ -0,0 +1,145 @@
+//! Thread-safe replacements for the `std::rc` and `std::cell` types,
+//! used in place of them when the `sync` feature is enabled.
+
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Mutex, RwLock};
+
+pub use std::sync::{Arc as Rc, Weak};
+pub use std::sync::{RwLockReadGuard as Ref, RwLockWriteGuard as RefMut};
+
+/// A mutable memory location guarded by a read-write lock,
+/// with the interface of `std::cell::RefCell`.
+///
+/// Borrowing never fails; a conflicting borrow blocks until the
+/// other borrow is released.
+#[derive(Default)]
+pub struct RefCell<T> {
+    value: RwLock<T>,
+}
+
+impl<T> RefCell<T> {
+    pub const fn new(value: T) -> Self {
+        Self { value: RwLock::new(value) }
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.value.read().unwrap_or_else(|error| error.into_inner())
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.value.write().unwrap_or_else(|error| error.into_inner())
+    }
+
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    pub fn take(&self) -> T where T: Default {
+        self.replace(T::default())
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut().unwrap_or_else(|error| error.into_inner())
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner().unwrap_or_else(|error| error.into_inner())
+    }
+}
+
+impl<T: Clone> Clone for RefCell<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.borrow().clone())
+    }
+}
+
+impl<T: PartialEq> PartialEq for RefCell<T> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other) || *self.borrow() == *other.borrow()
+    }
+}
+
+impl<T: Debug> Debug for RefCell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RefCell").field("value", &*self.borrow()).finish()
+    }
+}
+
+impl<T: Serialize> Serialize for RefCell<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.borrow().serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for RefCell<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// A mutable memory location guarded by a mutex,
+/// with the interface of `std::cell::Cell`.
+#[derive(Default)]
+pub struct Cell<T> {
+    value: Mutex<T>,
+}
+
+impl<T> Cell<T> {
+    pub const fn new(value: T) -> Self {
+        Self { value: Mutex::new(value) }
+    }
+
+    pub fn get(&self) -> T where T: Copy {
+        *self.value.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
+    pub fn set(&self, value: T) {
+        self.replace(value);
+    }
+
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.value.lock().unwrap_or_else(|error| error.into_inner()), value)
+    }
+}
+
+impl<T: Copy> Clone for Cell<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
+}
+
+impl<T: Copy + Debug> Debug for Cell<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cell").field("value", &self.get()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ns::*;
+
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn test_parse_many() {
+        let compilation_units: Vec<Rc<CompilationUnit>> = (0..16)
+            .map(|i| CompilationUnit::new(None, format!("package p{i} {{\r\n    public class C{i} {{}}\r\n}}\r\nx = ;")))
+            .collect();
+        let programs = ParserFacade::parse_many(&compilation_units, &default());
+        assert_send_sync(&programs);
+        assert_send_sync(&compilation_units);
+        for (i, (cu, program)) in compilation_units.iter().zip(&programs).enumerate() {
+            assert_eq!(program.packages[0].name[0].0, format!("p{i}"));
+            let diagnostics = cu.diagnostics();
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!((diagnostics[0].location().first_line_number(), diagnostics[0].location().first_column()), (4, 4));
+            assert_eq!(cu.get_line_offset(2), Some(13 + i.to_string().len()));
+        }
+
+        // Trees can be shared with other threads.
+        let program = programs[0].clone();
+        let name = std::thread::spawn(move || program.location.text()).join().unwrap();
+        assert!(name.starts_with("package p0"));
+    }
+}
@@ -0,0 +1,103 @@
+use clap::Parser;
+use std::{fs, io, path::{Path, PathBuf}, process::ExitCode};
+use mxmlextrema_as3parser::ns::*;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Arguments {
+    /// Pattern such as `trace($x)`, where identifiers starting with `$` are placeholders.
+    pattern: String,
+
+    /// ActionScript 3 source files or directories.
+    source_paths: Vec<String>,
+
+    /// Prints the matches as JSON.
+    #[arg(long)]
+    json: bool,
+
+    /// Replaces the matches by this code, where the placeholders of the
+    /// pattern are substituted by the code they matched.
+    #[arg(short, long)]
+    rewrite: Option<String>,
+
+    /// Overwrites the source files with the rewritten code instead of
+    /// printing the edits.
+    #[arg(short, long, requires = "rewrite")]
+    write: bool,
+}
+
+fn main() -> io::Result<ExitCode> {
+    let arguments = Arguments::parse();
+    let pattern = match StructuralPattern::new(&arguments.pattern) {
+        Ok(pattern) => pattern,
+        Err(error) => {
+            eprintln!("{error}");
+            return Ok(ExitCode::FAILURE);
+        },
+    };
+    let source_paths = if arguments.source_paths.is_empty() { vec![".".to_owned()] } else { arguments.source_paths.clone() };
+    if let Some(replacement) = &arguments.rewrite {
+        return rewrite(&arguments, replacement, &source_paths);
+    }
+    let mut matches = vec![];
+    for source_path in &source_paths {
+        let path = Path::new(source_path);
+        if path.is_dir() {
+            matches.extend(pattern.find_in_directory(path)?);
+        } else {
+            matches.extend(pattern.find_in_file(path)?);
+        }
+    }
+    if arguments.json {
+        let matches: Vec<serde_json::Value> = matches.iter().map(|m| serde_json::json!({
+            "file": m.location.compilation_unit().file_path(),
+            "location": m.location,
+            "text": m.location.text(),
+            "captures": m.captures.iter().map(|(name, location)| (name.clone(), location.text().into())).collect::<serde_json::Map<_, _>>(),
+        })).collect();
+        println!("{}", serde_json::to_string_pretty(&matches).unwrap());
+    } else {
+        for m in &matches {
+            let file_path = m.location.compilation_unit().file_path().unwrap_or_default();
+            let text = m.location.text();
+            println!("{file_path}:{}:{}: {}", m.location.first_line_number(), m.location.first_column() + 1, text.lines().next().unwrap_or_default());
+        }
+    }
+    Ok(if matches.is_empty() { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
+
+fn rewrite(arguments: &Arguments, replacement: &str, source_paths: &[String]) -> io::Result<ExitCode> {
+    let rule = match StructuralRewrite::new(&arguments.pattern, replacement) {
+        Ok(rule) => rule,
+        Err(error) => {
+            eprintln!("{error}");
+            return Ok(ExitCode::FAILURE);
+        },
+    };
+    let rules = [rule];
+    let mut files: Vec<(PathBuf, Vec<TextEdit>)> = vec![];
+    for source_path in source_paths {
+        let path = Path::new(source_path);
+        if path.is_dir() {
+            files.extend(StructuralRewrite::edits_in_directory(&rules, path)?);
+        } else {
+            files.push((path.to_owned(), StructuralRewrite::edits_in_file(&rules, path)?));
+        }
+    }
+    for (path, edits) in &files {
+        if edits.is_empty() {
+            continue;
+        }
+        let text = fs::read_to_string(path)?;
+        if arguments.write {
+            fs::write(path, TextEdit::apply_all(&text, edits))?;
+            continue;
+        }
+        let compilation_unit = CompilationUnit::new(None, text);
+        for edit in edits {
+            let location = Location::with_offsets(&compilation_unit, edit.range.start, edit.range.end);
+            println!("{}:{}:{}: {:?} -> {:?}", path.display(), location.first_line_number(), location.first_column() + 1, location.text(), edit.new_text);
+        }
+    }
+    Ok(if files.iter().all(|(_, edits)| edits.is_empty()) { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+}
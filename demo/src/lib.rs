@@ -55,4 +55,27 @@ pub fn parse(input: &str, source_type: &str) -> String {
         css,
         diagnostics,
     }).unwrap()
+}
+
+#[derive(Serialize, Deserialize)]
+struct SemanticTokenResult {
+    kind: SemanticTokenKind,
+    column1: usize,
+    column2: usize,
+    line1: usize,
+    line2: usize,
+}
+
+#[wasm_bindgen]
+pub fn semantic_tokens(input: &str) -> String {
+    let compilation_unit = CompilationUnit::new(None, input.to_owned());
+    let program = ParserFacade(&compilation_unit, default()).parse_program();
+    let tokens: Vec<SemanticTokenResult> = SemanticToken::classify(&program).into_iter().map(|token| SemanticTokenResult {
+        kind: token.kind,
+        column1: token.location.first_column() + 1,
+        column2: token.location.last_column() + 1,
+        line1: token.location.first_line_number(),
+        line2: token.location.last_line_number(),
+    }).collect();
+    serde_json::to_string_pretty(&tokens).unwrap()
 }
\ No newline at end of file
@@ -13,7 +13,7 @@ fn main() {
 
     // Parse program
     let program = ParserFacade(&compilation_unit, default()).parse_program();
-    visit_program(&program);
+    AsdocPrinter.visit_program(&program);
 
     // Report diagnostics
     compilation_unit.sort_diagnostics();
@@ -22,30 +22,15 @@ fn main() {
     }
 }
 
-fn visit_program(program: &Rc<Program>) {
-    for package in program.packages.iter() {
-        for directive in package.block.directives.iter() {
-            // directive: Rc<Directive>
+struct AsdocPrinter;
 
-            match directive.as_ref() {
-                Directive::ClassDefinition(defn) => {
-                    visit_class(&defn);
-                },
-
-                _ => {},
-            }
-        }
-    }
-}
-
-fn visit_class(defn: &ClassDefinition) {
-    for directive in defn.block.directives.iter() {
-        if let Directive::VariableDefinition(defn) = directive.as_ref() {
-            // Print any found main body and @private tags
-            if let Some(asdoc) = &defn.asdoc {
-                print_asdoc(asdoc);
-            }
+impl Visitor for AsdocPrinter {
+    fn visit_variable_definition(&mut self, defn: &VariableDefinition) {
+        // Print any found main body and @private tags
+        if let Some(asdoc) = &defn.asdoc {
+            print_asdoc(asdoc);
         }
+        walk_variable_definition(self, defn);
     }
 }
 